@@ -0,0 +1,83 @@
+use crate::core::datamodel::{Instance, to_world_space};
+use crate::core::errors::LuDockError;
+use crate::core::loader::load_project;
+use crate::core::rbxl::write_binary;
+use crate::core::rbxlx::write_xml;
+use anyhow::{Context, Result};
+use glam::Mat4;
+use std::fs;
+use std::path::PathBuf;
+
+/// Serializes the project as a Roblox file. Places (`rbxl`, `rbxlx`) contain every
+/// service; models (`rbxm`, `rbxmx`) contain the `target` instance.
+pub fn export_project(format: &str, output: Option<PathBuf>, target: Option<&str>) -> Result<()> {
+    let root = std::env::current_dir()?;
+    let loaded = load_project(&root).with_context(|| "Failed to load project structure")?;
+    if !loaded.diagnostics.is_empty() {
+        eprintln!(
+            "Warning: {} problem(s) in project files; run `ludock run` for details.",
+            loaded.diagnostics.len()
+        );
+    }
+    let datamodel = &loaded.datamodel;
+
+    let items: Vec<_> = match target {
+        Some(path) => {
+            let instance = datamodel
+                .resolve_path(path)
+                .ok_or_else(|| LuDockError::ConfigError(format!("No instance at `{}`", path)))?;
+            if instance.class_name == "DataModel" {
+                instance.children.iter().collect()
+            } else {
+                vec![instance]
+            }
+        }
+        None if format == "rbxm" || format == "rbxmx" => {
+            return Err(LuDockError::ConfigError(format!("--format {} needs --target <path>", format)).into());
+        }
+        None => datamodel.children.iter().collect(),
+    };
+
+    // Roblox files store world-space placements; LuDock parts are relative to their models.
+    let items: Vec<Instance> = items
+        .into_iter()
+        .map(|item| {
+            let space = datamodel.space_of(&item.full_path).unwrap_or(Mat4::IDENTITY);
+            let mut item = item.clone();
+            to_world_space(&mut item, space);
+            item
+        })
+        .collect();
+    let items: Vec<&Instance> = items.iter().collect();
+
+    let content = match format {
+        "rbxl" | "rbxm" => write_binary(&items),
+        "rbxlx" | "rbxmx" => write_xml(&items).into_bytes(),
+        _ => {
+            return Err(LuDockError::ConfigError(format!(
+                "Unknown export format `{}` (expected rbxl, rbxm, rbxlx or rbxmx)",
+                format
+            ))
+            .into());
+        }
+    };
+
+    let output = output.unwrap_or_else(|| {
+        let stem = match (target, items.as_slice()) {
+            (Some(_), [single]) => single.name.clone(),
+            _ => "place".to_string(),
+        };
+        root.join("results").join(format!("{}.{}", stem, format))
+    });
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&output, content).with_context(|| format!("Failed to write {:?}", output))?;
+
+    fn count(instance: &Instance) -> usize {
+        1 + instance.children.iter().map(count).sum::<usize>()
+    }
+    let total: usize = items.iter().map(|i| count(i)).sum();
+    println!("Exported {} instance(s) to {}", total, output.display());
+    Ok(())
+}
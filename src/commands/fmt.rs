@@ -0,0 +1,163 @@
+use crate::core::errors::LuDockError;
+use crate::core::eval::Scope;
+use crate::core::loader::DSL_EXTENSIONS;
+use crate::core::parser::{DslNode, parse_instance_dsl};
+use crate::core::printer::format_dsl;
+use crate::core::rojo::{self, ProjectNode};
+use anyhow::Result;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Rewrites every DSL file in the project in canonical form. With `check`, nothing is
+/// written and the command fails if any file would change.
+pub fn format_project(check: bool) -> Result<()> {
+    let root = std::env::current_dir()?;
+    let files = project_files(&root)?;
+
+    let mut unformatted = 0;
+    let mut failed = 0;
+    for path in &files {
+        let display = path.strip_prefix(&root).unwrap_or(path).to_string_lossy().replace('\\', "/");
+        let content = fs::read_to_string(path)?;
+        match format_dsl(&content) {
+            Ok(formatted) if formatted == content => {}
+            Ok(formatted) => {
+                unformatted += 1;
+                if check {
+                    println!("Would reformat: {}", display);
+                } else {
+                    fs::write(path, formatted)?;
+                    println!("Formatted: {}", display);
+                }
+            }
+            Err(errors) => {
+                failed += 1;
+                for error in errors {
+                    eprintln!("{}:{}:{}: {}", display, error.line, error.column, error.message);
+                }
+            }
+        }
+    }
+
+    if failed > 0 {
+        return Err(LuDockError::DslError(format!("{} file(s) could not be formatted", failed)).into());
+    }
+    if check && unformatted > 0 {
+        return Err(anyhow::anyhow!("{} file(s) need formatting; run `ludock fmt`", unformatted));
+    }
+    if unformatted == 0 {
+        println!("{} file(s) already formatted.", files.len());
+    }
+    Ok(())
+}
+
+/// The DSL files the project loads: those under `game/` and the `$path`s of
+/// `default.project.json`, plus the templates they extend. Other `.part`/`.model` files
+/// in the directory are left alone.
+fn project_files(root: &Path) -> Result<Vec<PathBuf>> {
+    let mut sources = vec![root.join("game")];
+    let project_path = root.join("default.project.json");
+    if project_path.is_file() {
+        project_paths(&project_path, &mut sources)?;
+    }
+
+    let mut files = BTreeSet::new();
+    for source in sources.iter().filter(|s| s.exists()) {
+        // Skip `.git` and other dot-directories.
+        let walker = WalkDir::new(source).into_iter().filter_entry(|e| {
+            e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.')
+        });
+        for entry in walker {
+            let entry = entry?;
+            if entry.file_type().is_file() && is_dsl(entry.path()) {
+                files.insert(entry.into_path());
+            }
+        }
+    }
+
+    // Template paths are relative to the project root, like the loader resolves them.
+    let mut pending: Vec<PathBuf> = files.iter().cloned().collect();
+    while let Some(path) = pending.pop() {
+        let (node, _) = parse_instance_dsl(&fs::read_to_string(&path)?, &Scope::new());
+        let mut templates = Vec::new();
+        extends_paths(&node, &mut templates);
+        for template in templates {
+            let template = root.join(template);
+            if template.is_file() && is_dsl(&template) && files.insert(template.clone()) {
+                pending.push(template);
+            }
+        }
+    }
+    Ok(files.into_iter().collect())
+}
+
+fn is_dsl(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| DSL_EXTENSIONS.contains(&e))
+}
+
+/// Collects the `$path`s of a project file, following nested project files.
+fn project_paths(project_path: &Path, sources: &mut Vec<PathBuf>) -> Result<()> {
+    let content = fs::read_to_string(project_path)?;
+    let project = rojo::parse_project(&content)
+        .map_err(|e| anyhow::anyhow!("Invalid project file {:?}: {}", project_path, e))?;
+    let base_dir = project_path.parent().unwrap_or(Path::new("."));
+    node_paths(&project.tree, base_dir, sources)
+}
+
+fn node_paths(node: &ProjectNode, base_dir: &Path, sources: &mut Vec<PathBuf>) -> Result<()> {
+    if let Some(node_path) = &node.path {
+        let path = base_dir.join(&node_path.path);
+        if path.to_string_lossy().ends_with(".project.json") {
+            project_paths(&path, sources)?;
+        } else {
+            sources.push(path);
+        }
+    }
+    for (_, child) in &node.children {
+        node_paths(child, base_dir, sources)?;
+    }
+    Ok(())
+}
+
+fn extends_paths(node: &DslNode, paths: &mut Vec<String>) {
+    paths.extend(node.extends.iter().map(|e| e.path.clone()));
+    for child in &node.children {
+        extends_paths(&child.node, paths);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::TempProject;
+
+    #[test]
+    fn test_only_project_files_are_formatted() {
+        let root = TempProject::new(
+            "fmt",
+            &[
+                ("game/Workspace/Wall.part", "Extends = \"templates/Wall.basepart\""),
+                ("templates/Wall.basepart", "Extends = \"templates/Base.basepart\""),
+                ("templates/Base.basepart", "Anchored = true"),
+                ("templates/Unused.basepart", "Anchored = true"),
+                ("src/shared/Sign.part", "Anchored = true"),
+                ("default.project.json", r#"{"name": "P", "tree": {"$className": "DataModel", "ReplicatedStorage": {"$path": "src/shared"}}}"#),
+                ("notes/Scratch.part", "Anchored = true"),
+                ("game/.backup/Old.part", "Anchored = true"),
+            ],
+        );
+        let files: Vec<_> = project_files(&root)
+            .unwrap()
+            .iter()
+            .map(|f| f.strip_prefix(&*root).unwrap().to_string_lossy().replace('\\', "/"))
+            .collect();
+        assert_eq!(
+            files,
+            ["game/Workspace/Wall.part", "src/shared/Sign.part", "templates/Base.basepart", "templates/Wall.basepart"]
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use crate::core::datamodel::{Instance, PropertyValue, to_model_space};
+use crate::core::errors::LuDockError;
+use crate::core::loader::{DSL_EXTENSIONS, map_extension_to_class};
+use crate::core::printer::print_instance;
+use crate::core::rbxl::read_roblox_file;
+use crate::core::reflection::database;
+use crate::core::rojo::json_value;
+use crate::core::validator::validate_property;
+use anyhow::{Context, Result};
+use glam::Mat4;
+use serde_json::{Map, Value};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+#[derive(Default)]
+struct ImportStats {
+    instances: usize,
+    files: usize,
+    /// Properties LuDock can't represent or validate (unknown to the class database,
+    /// read-only, or of an unsupported type).
+    dropped: usize,
+}
+
+/// Explodes a Roblox file into the project: a place replaces `game/`, a model is added
+/// to `game/Workspace`. Folders and services become directories, scripts `.lua` files,
+/// and everything else DSL files.
+pub fn import_file(file: &Path, force: bool) -> Result<()> {
+    let root = std::env::current_dir()?;
+    let content = fs::read(file).with_context(|| format!("Failed to read {:?}", file))?;
+    let is_model = file.extension().is_some_and(|e| e == "rbxm" || e == "rbxmx");
+
+    let parent_full_path = if is_model { "game/Workspace" } else { "game" };
+    let items = read_roblox_file(&content, parent_full_path)
+        .map_err(|e| LuDockError::ConfigError(format!("{}: {}", file.display(), e)))?;
+
+    let destination = root.join(parent_full_path);
+    if !is_model && destination.exists() && fs::read_dir(&destination)?.next().is_some() {
+        if !force {
+            return Err(LuDockError::ConfigError(
+                "game/ is not empty; use --force to replace it".to_string(),
+            )
+            .into());
+        }
+        fs::remove_dir_all(&destination)?;
+    }
+    fs::create_dir_all(&destination)?;
+
+    let mut stats = ImportStats::default();
+    let mut used = existing_stems(&destination)?;
+    for mut item in items {
+        to_model_space(&mut item, Mat4::IDENTITY);
+        prune_properties(&mut item, &mut stats);
+        let top_level = !is_model;
+        write_instance(&item, &destination, top_level, force, &mut used, &mut stats)?;
+    }
+
+    println!(
+        "Imported {} instance(s) into {} ({} file(s)).",
+        stats.instances, parent_full_path, stats.files
+    );
+    if stats.dropped > 0 {
+        println!("Dropped {} property value(s) LuDock can't represent.", stats.dropped);
+    }
+    Ok(())
+}
+
+/// Removes properties that would fail validation, and those equal to the class default
+/// so the generated files only list what was changed in Studio.
+fn prune_properties(instance: &mut Instance, stats: &mut ImportStats) {
+    let db = database();
+    let class_name = instance.class_name.clone();
+    instance.properties.retain(|key, value| {
+        if validate_property(&class_name, key, value).is_some() {
+            stats.dropped += 1;
+            return false;
+        }
+        db.default_value(&class_name, key) != Some(value)
+    });
+    for child in &mut instance.children {
+        prune_properties(child, stats);
+    }
+}
+
+fn existing_stems(dir: &Path) -> Result<HashSet<String>> {
+    let mut stems = HashSet::new();
+    for entry in fs::read_dir(dir)? {
+        let name = entry?.file_name().to_string_lossy().to_lowercase();
+        stems.insert(name.split('.').next().unwrap_or("").to_string());
+    }
+    Ok(stems)
+}
+
+/// A file name for `name` that is valid everywhere and unique (case-insensitively)
+/// within its directory.
+fn file_stem(name: &str, used: &mut HashSet<String>) -> String {
+    let mut stem: String = name
+        .chars()
+        .map(|c| if c.is_control() || "<>:\"/\\|?*.".contains(c) { '_' } else { c })
+        .collect();
+    stem = stem.trim_end_matches(' ').to_string();
+    if stem.is_empty() {
+        stem = "_".to_string();
+    }
+    let mut candidate = stem.clone();
+    let mut n = 2;
+    while used.contains(&candidate.to_lowercase()) {
+        candidate = format!("{} ({})", stem, n);
+        n += 1;
+    }
+    used.insert(candidate.to_lowercase());
+    candidate
+}
+
+fn write_instance(
+    instance: &Instance,
+    dir: &Path,
+    top_level: bool,
+    force: bool,
+    used: &mut HashSet<String>,
+    stats: &mut ImportStats,
+) -> Result<()> {
+    let db = database();
+    let stem = file_stem(&instance.name, used);
+    // The loader names instances after their file, so keep the real name when it differs.
+    let mut instance = instance.clone();
+    if stem != instance.name {
+        let name = PropertyValue::String(instance.name.clone());
+        instance.properties.insert("Name".to_string(), name);
+    }
+    let write = |path: &Path, content: &str, stats: &mut ImportStats| -> Result<()> {
+        if path.exists() && !force {
+            return Err(LuDockError::ConfigError(format!("{} already exists; use --force to overwrite", path.display())).into());
+        }
+        fs::write(path, content)?;
+        stats.files += 1;
+        Ok(())
+    };
+
+    let script_suffix = match instance.class_name.as_str() {
+        "Script" => Some("server"),
+        "LocalScript" => Some("local"),
+        "ModuleScript" => Some("module"),
+        _ => None,
+    };
+    let is_directory = top_level
+        || (instance.class_name == "Folder" && !instance.children.is_empty())
+        || (script_suffix.is_some() && !instance.children.is_empty());
+    // Scripts keep their source in `.lua` files, so an instance with scripts inside
+    // becomes a directory described by `init.<ext>`.
+    let has_scripts = instance.children.iter().any(|c| c.is_a("LuaSourceContainer"));
+
+    if !is_directory && script_suffix.is_none() {
+        let ext = DSL_EXTENSIONS
+            .iter()
+            .find(|e| map_extension_to_class(e) == instance.class_name)
+            .copied()
+            .unwrap_or(if instance.is_a("BasePart") { "basepart" } else { "model" });
+        if has_scripts {
+            stats.instances += 1;
+            let child_dir = dir.join(&stem);
+            fs::create_dir_all(&child_dir)?;
+            let mut own = instance.clone();
+            own.children.clear();
+            write(&child_dir.join(format!("init.{}", ext)), &print_instance(&own), stats)?;
+            let mut used = existing_stems(&child_dir)?;
+            used.insert("init".to_string());
+            for child in &instance.children {
+                write_instance(child, &child_dir, false, force, &mut used, stats)?;
+            }
+            return Ok(());
+        }
+        // One DSL file holds the instance and its descendants as `Child` blocks.
+        write(&dir.join(format!("{}.{}", stem, ext)), &print_instance(&instance), stats)?;
+        stats.instances += count(&instance);
+        return Ok(());
+    }
+
+    stats.instances += 1;
+    // Scripts keep `Source` in the `.lua` file and other properties in a meta file.
+    let source = match instance.properties.remove("Source") {
+        Some(PropertyValue::String(source)) => source,
+        _ => String::new(),
+    };
+    let mut meta = Map::new();
+    let mut properties = Map::new();
+    let mut keys: Vec<&String> = instance.properties.keys().collect();
+    keys.sort();
+    for key in keys {
+        match json_value(&instance.properties[key]) {
+            Some(value) => {
+                properties.insert(key.clone(), value);
+            }
+            None => stats.dropped += 1,
+        }
+    }
+    // Directories load as Folders (or the service they are named after) unless told otherwise.
+    let inferred = db
+        .class(&instance.name)
+        .is_some_and(|c| c.name == instance.class_name && c.tags.iter().any(|t| t == "Service"));
+    if script_suffix.is_none() && instance.class_name != "Folder" && !inferred {
+        meta.insert("className".to_string(), Value::from(instance.class_name.clone()));
+    }
+    if !properties.is_empty() {
+        meta.insert("properties".to_string(), Value::Object(properties));
+    }
+    let meta = (!meta.is_empty()).then(|| serde_json::to_string_pretty(&Value::Object(meta)).unwrap() + "\n");
+
+    if !is_directory {
+        let suffix = script_suffix.unwrap();
+        write(&dir.join(format!("{}.{}.lua", stem, suffix)), &source, stats)?;
+        if let Some(meta) = meta {
+            write(&dir.join(format!("{}.meta.json", stem)), &meta, stats)?;
+        }
+        return Ok(());
+    }
+
+    let child_dir = dir.join(&stem);
+    fs::create_dir_all(&child_dir)?;
+    if let Some(suffix) = script_suffix {
+        write(&child_dir.join(format!("init.{}.lua", suffix)), &source, stats)?;
+    }
+    if let Some(meta) = meta {
+        write(&child_dir.join("init.meta.json"), &meta, stats)?;
+    }
+    let mut used = existing_stems(&child_dir)?;
+    used.insert("init".to_string());
+    for child in &instance.children {
+        write_instance(child, &child_dir, false, force, &mut used, stats)?;
+    }
+    Ok(())
+}
+
+fn count(instance: &Instance) -> usize {
+    1 + instance.children.iter().map(count).sum::<usize>()
+}
@@ -1,10 +1,17 @@
 use clap::{Parser, Subcommand};
 use anyhow::Result;
+use glam::Vec3;
+use std::path::PathBuf;
+
+use crate::core::renderer::{CameraView, SheetView};
 
 pub mod create;
 pub mod run;
 pub mod doctor;
 pub mod schema;
+pub mod fmt;
+pub mod export;
+pub mod import;
 
 #[derive(Parser)]
 #[command(name = "ludock")]
@@ -31,10 +38,15 @@ pub enum Commands {
         #[arg(long = "relaxed")]
         relaxed: bool,
 
-        /// Specific instance to render (optional path)
+        /// Instance to focus on: game/Workspace/House or Workspace.House. Frames the
+        /// camera on it and limits the diff and diagnostics to its subtree
         #[arg(long = "target")]
         target: Option<String>,
 
+        /// With --target, hide everything else instead of dimming it
+        #[arg(long = "isolate")]
+        isolate: bool,
+
         /// Enable diff mode (compare against previous results)
         #[arg(long = "diff")]
         diff: bool,
@@ -51,6 +63,48 @@ pub enum Commands {
         #[arg(long = "debug-axes")]
         debug_axes: bool,
 
+        /// Camera: top, front, side, iso, or the path of a Camera instance
+        /// (defaults to the Workspace's camera, or iso)
+        #[arg(long = "camera")]
+        camera: Option<CameraView>,
+
+        /// Orthographic projection instead of perspective
+        #[arg(long = "ortho")]
+        ortho: bool,
+
+        /// Camera position as x,y,z
+        #[arg(long = "eye", value_parser = parse_vec3, allow_hyphen_values = true)]
+        eye: Option<Vec3>,
+
+        /// Point the camera looks at as x,y,z
+        #[arg(long = "look-at", value_parser = parse_vec3, allow_hyphen_values = true)]
+        look_at: Option<Vec3>,
+
+        /// Also render views, tiled into results/render_views.png: a comma-separated
+        /// list of iso, top, front and right (all four when none are given)
+        #[arg(long = "views", value_delimiter = ',', num_args = 0..=1)]
+        views: Option<Vec<SheetView>>,
+
+        /// Cast shadows from the Lighting sun (slower)
+        #[arg(long = "shadows")]
+        shadows: bool,
+
+        /// Draw Transparency = 1 parts as ghosts and apply LocalTransparencyModifier
+        #[arg(long = "show-invisible")]
+        show_invisible: bool,
+
+        /// Near clipping distance (fitted to the scene by default)
+        #[arg(long = "near")]
+        near: Option<f32>,
+
+        /// Far clipping distance (fitted to the scene by default)
+        #[arg(long = "far")]
+        far: Option<f32>,
+
+        /// Also write the depth buffer to results/render_depth.png
+        #[arg(long = "depth")]
+        depth: bool,
+
         /// Execution preset (agent, ci, debug)
         #[arg(long = "preset")]
         preset: Option<String>,
@@ -59,6 +113,35 @@ pub enum Commands {
     Doctor,
     /// Generate JSON schemas
     Schema,
+    /// Rewrite DSL files in canonical form
+    Fmt {
+        /// Only report files that would change; exit non-zero if any do
+        #[arg(long = "check")]
+        check: bool,
+    },
+    /// Export the project as a Roblox place or model file
+    Export {
+        /// Output format (rbxl, rbxm, rbxlx, rbxmx)
+        #[arg(long = "format", default_value = "rbxlx")]
+        format: String,
+
+        /// Output file (defaults to results/place.<format>)
+        #[arg(long = "output")]
+        output: Option<PathBuf>,
+
+        /// Instance to export, e.g. game/Workspace/Door (required for rbxm and rbxmx)
+        #[arg(long = "target")]
+        target: Option<String>,
+    },
+    /// Import a Roblox place or model file into game/
+    Import {
+        /// The .rbxl, .rbxm, .rbxlx or .rbxmx file
+        file: PathBuf,
+
+        /// Replace an existing game/ directory and overwrite existing files
+        #[arg(long = "force")]
+        force: bool,
+    },
 }
 
 pub fn main() -> Result<()> {
@@ -72,20 +155,42 @@ pub fn main() -> Result<()> {
             render, 
             relaxed, 
             target, 
+            isolate,
             diff, 
             debug_bounds, 
             debug_origin, 
             debug_axes,
+            camera,
+            ortho,
+            eye,
+            look_at,
+            views,
+            shadows,
+            show_invisible,
+            near,
+            far,
+            depth,
             preset,
         } => {
             let mut opts = run::RunOptions {
                 render: *render,
                 relaxed: *relaxed,
                 target: target.clone(),
+                isolate: *isolate,
                 diff: *diff,
                 debug_bounds: *debug_bounds,
                 debug_origin: *debug_origin,
                 debug_axes: *debug_axes,
+                camera: camera.clone().unwrap_or_default(),
+                orthographic: *ortho,
+                eye: *eye,
+                look_at: *look_at,
+                views: views.clone(),
+                shadows: *shadows,
+                show_invisible: *show_invisible,
+                near: *near,
+                far: *far,
+                depth: *depth,
             };
             
             if let Some(p) = preset {
@@ -100,7 +205,29 @@ pub fn main() -> Result<()> {
         Commands::Schema => {
             schema::generate_schemas()?;
         }
+        Commands::Fmt { check } => {
+            fmt::format_project(*check)?;
+        }
+        Commands::Export { format, output, target } => {
+            export::export_project(format, output.clone(), target.as_deref())?;
+        }
+        Commands::Import { file, force } => {
+            import::import_file(file, *force)?;
+        }
     }
 
     Ok(())
 }
+
+/// Parses `--eye`/`--look-at` values such as `10,5,-3`.
+fn parse_vec3(s: &str) -> Result<Vec3, String> {
+    let components = s
+        .split(',')
+        .map(|c| c.trim().parse::<f32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    match components[..] {
+        [x, y, z] => Ok(Vec3::new(x, y, z)),
+        _ => Err(format!("expected x,y,z, got `{}`", s)),
+    }
+}
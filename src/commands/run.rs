@@ -1,18 +1,34 @@
-use crate::core::analysis::run_analysis;
+use crate::core::analysis::{run_analysis, DiagnosticsReport};
 use crate::core::diff::compare_worlds;
+use crate::core::datamodel::Instance;
+use crate::core::errors::LuDockError;
 use crate::core::loader::load_project;
-use crate::core::renderer::{render_scene, RenderOptions};
+use crate::core::renderer::{render_scene, render_views, CameraView, RenderOptions, SheetView};
 use anyhow::{Context, Result};
+use glam::Vec3;
 use std::fs;
+use std::path::{Path, PathBuf};
 
 pub struct RunOptions {
     pub render: bool,
     pub relaxed: bool,
     pub target: Option<String>,
+    pub isolate: bool,
     pub diff: bool,
     pub debug_bounds: bool,
     pub debug_origin: bool,
     pub debug_axes: bool,
+    pub camera: CameraView,
+    pub orthographic: bool,
+    pub eye: Option<Vec3>,
+    pub look_at: Option<Vec3>,
+    /// `--views`; empty for all of them.
+    pub views: Option<Vec<SheetView>>,
+    pub shadows: bool,
+    pub show_invisible: bool,
+    pub near: Option<f32>,
+    pub far: Option<f32>,
+    pub depth: bool,
 }
 
 pub fn apply_preset(opts: &mut RunOptions, preset: &str) -> Result<()> {
@@ -25,6 +41,7 @@ pub fn apply_preset(opts: &mut RunOptions, preset: &str) -> Result<()> {
             opts.debug_bounds = true;
             opts.debug_origin = true;
             opts.debug_axes = true;
+            opts.shadows = true;
         }
         "ci" => {
             // Strict mode, no render (unless forced), diff enabled for reports
@@ -39,12 +56,26 @@ pub fn apply_preset(opts: &mut RunOptions, preset: &str) -> Result<()> {
             opts.debug_bounds = true;
             opts.debug_origin = true;
             opts.debug_axes = true;
+            opts.show_invisible = true;
         }
         _ => return Err(anyhow::anyhow!("Unknown preset: {}", preset)),
     }
     Ok(())
 }
 
+/// The `full_path` of the `--target` instance.
+fn resolve_target(datamodel: &Instance, path: &str) -> Result<String> {
+    let instance = datamodel
+        .resolve_path(path)
+        .ok_or_else(|| LuDockError::ConfigError(format!("No instance at `{}`", path)))?;
+    Ok(instance.full_path.clone())
+}
+
+/// Whether diagnostics and scripts from `file` are reported for `target`.
+fn in_target(datamodel: &Instance, target: Option<&str>, file: &Path) -> bool {
+    target.is_none_or(|t| datamodel.file_in_subtree(t, file))
+}
+
 pub fn run_project(options: RunOptions) -> Result<()> {
     // 1. Determine root (current dir)
     let root = std::env::current_dir()?;
@@ -57,7 +88,13 @@ pub fn run_project(options: RunOptions) -> Result<()> {
     // Diff Mode: Load previous world.json if needed
     let old_world = if options.diff {
         if let Ok(content) = fs::read_to_string(results_dir.join("world.json")) {
-            serde_json::from_str::<crate::core::datamodel::Instance>(&content).ok()
+            match serde_json::from_str::<Instance>(&content) {
+                Ok(world) => Some(world),
+                Err(e) => {
+                    eprintln!("Warning: previous world.json could not be read, skipping diff: {}", e);
+                    None
+                }
+            }
         } else {
             None
         }
@@ -66,7 +103,26 @@ pub fn run_project(options: RunOptions) -> Result<()> {
     };
 
     println!("Loading project...");
-    let datamodel = load_project(&root).with_context(|| "Failed to load project structure")?;
+    let loaded = load_project(&root).with_context(|| "Failed to load project structure")?;
+    let datamodel = loaded.datamodel;
+
+    // `--target` narrows rendering, the diff and diagnostics to one subtree.
+    let target = match &options.target {
+        Some(path) => {
+            let full_path = resolve_target(&datamodel, path)?;
+            println!("Target: {}", full_path);
+            Some(full_path)
+        }
+        None => None,
+    };
+    let mut load_diagnostics = loaded.diagnostics;
+    load_diagnostics.retain(|d| in_target(&datamodel, target.as_deref(), Path::new(&d.file)));
+    let script_files: Vec<PathBuf> = loaded
+        .script_files
+        .into_iter()
+        .filter(|f| in_target(&datamodel, target.as_deref(), f))
+        .collect();
+    let dsl_error_count = load_diagnostics.len();
 
     // 2. Generate world.json
     println!("Generating world.json...");
@@ -76,53 +132,116 @@ pub fn run_project(options: RunOptions) -> Result<()> {
     // Handle Diff
     if let Some(old_inst) = old_world {
         println!("Computing structured diff...");
-        let diff_report = compare_worlds(&old_inst, &datamodel);
+        let mut diff_report = compare_worlds(&old_inst, &datamodel);
+        if let Some(target) = &target {
+            diff_report.restrict_to(target);
+        }
         let diff_json = serde_json::to_string_pretty(&diff_report)?;
         fs::write(results_dir.join("diff.json"), diff_json)?;
         println!("Diff report generated (Status: {})", diff_report.status);
     }
 
     // 3. Run Analysis (Strict vs Relaxed)
+    // DSL problems found while loading are reported alongside luau-analyze output.
     println!("Running Luau analysis...");
-    match run_analysis(&root, options.relaxed) {
-        Ok(diagnostics) => {
-            let diagnostics_json = serde_json::to_string_pretty(&diagnostics)?;
-            fs::write(results_dir.join("diagnostics.json"), diagnostics_json)?;
-            
-            // STRICT MODE: Fail if errors found and not relaxed
-            if !options.relaxed && !diagnostics.errors.is_empty() {
-                eprintln!("Strict Mode: {} errors found. Aborting render.", diagnostics.errors.len());
-                std::process::exit(1);
-            }
-        },
+    let mut diagnostics = DiagnosticsReport {
+        errors: load_diagnostics,
+        schema_version: "1.0".to_string(),
+    };
+    match run_analysis(&script_files, options.relaxed) {
+        Ok(report) => diagnostics.errors.extend(report.errors),
         Err(e) => {
             if !options.relaxed {
-                 eprintln!("Strict Mode Error: {}", e);
-                 std::process::exit(1);
-            } else {
-                 eprintln!("Analysis failed but continuing (relaxed): {}", e);
-                 fs::write(results_dir.join("diagnostics.json"), "{\"errors\": []}")?;
+                let diagnostics_json = serde_json::to_string_pretty(&diagnostics)?;
+                fs::write(results_dir.join("diagnostics.json"), diagnostics_json)?;
+                return Err(e.context("Strict mode: Luau analysis could not run"));
             }
+            eprintln!("Analysis failed but continuing (relaxed): {}", e);
         }
     }
 
+    let diagnostics_json = serde_json::to_string_pretty(&diagnostics)?;
+    fs::write(results_dir.join("diagnostics.json"), diagnostics_json)?;
+
+    // STRICT MODE: Fail if errors found and not relaxed
+    if !options.relaxed && !diagnostics.errors.is_empty() {
+        eprintln!("Strict Mode: {} errors found. Aborting render.", diagnostics.errors.len());
+        if dsl_error_count > 0 {
+            return Err(LuDockError::DslError(format!(
+                "{} error(s) in instance files, see results/diagnostics.json",
+                dsl_error_count
+            ))
+            .into());
+        }
+        return Err(LuDockError::AnalysisError(diagnostics.errors.len()).into());
+    }
+
     // 4. Render
-    if options.render {
+    if options.render || options.views.is_some() {
         println!("Rendering 3D view...");
-        let target_instance = &datamodel; // Target logic simplified for now
-
         let output_path = results_dir.join("render.png");
         
         let render_opts = RenderOptions {
             debug_bounds: options.debug_bounds,
             debug_origin: options.debug_origin,
             debug_axes: options.debug_axes,
+            target,
+            isolate: options.isolate,
+            camera: options.camera,
+            orthographic: options.orthographic,
+            eye: options.eye,
+            look_at: options.look_at,
+            shadows: options.shadows,
+            show_invisible: options.show_invisible,
+            near: options.near,
+            far: options.far,
+            depth: options.depth,
+            project_root: root.clone(),
         };
 
-        render_scene(target_instance, &output_path, render_opts).with_context(|| "Failed to render scene")?;
+        render_scene(&datamodel, &output_path, &render_opts).with_context(|| "Failed to render scene")?;
         println!("Render saved to {:?}", output_path);
+
+        if let Some(views) = &options.views {
+            render_views(&datamodel, &results_dir, views, &render_opts).with_context(|| "Failed to render views")?;
+            println!("Views saved to {:?}", results_dir.join("render_views.png"));
+        }
     }
 
     println!("LuDock run completed successfully.");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::TempProject;
+
+    #[test]
+    fn test_target_resolves_and_filters_diagnostics() {
+        let root = TempProject::new(
+            "target",
+            &[("game/Workspace/House/Wall.part", "Size = ("), ("game/Workspace/Tree.part", "Size = (")],
+        );
+        let loaded = load_project(&root).unwrap();
+        let datamodel = &loaded.datamodel;
+
+        // Slash and dotted paths, with or without `game`
+        for path in ["game/Workspace/House", "Workspace/House", "Workspace.House", "game.Workspace.House"] {
+            assert_eq!(resolve_target(datamodel, path).unwrap(), "game/Workspace/House");
+        }
+        let error = resolve_target(datamodel, "Workspace.Garage").unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(LuDockError::ConfigError(_))));
+
+        // Only the broken file inside the target is reported
+        assert_eq!(loaded.diagnostics.len(), 2);
+        let kept: Vec<_> = loaded
+            .diagnostics
+            .iter()
+            .filter(|d| in_target(datamodel, Some("game/Workspace/House"), Path::new(&d.file)))
+            .collect();
+        assert_eq!(kept.len(), 1);
+        assert!(kept[0].file.ends_with("House/Wall.part"));
+        assert_eq!(loaded.diagnostics.iter().filter(|d| in_target(datamodel, None, Path::new(&d.file))).count(), 2);
+    }
+}
@@ -1,14 +1,18 @@
+use crate::core::errors::LuDockError;
 use anyhow::Result;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Command;
-use walkdir::WalkDir;
 
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub struct Diagnostic {
     pub file: String,
     pub line: usize,
+    #[serde(default)]
+    pub column: Option<usize>,
+    #[serde(default)]
+    pub end_column: Option<usize>, // Exclusive, on the same line
     pub message: String,
     pub severity: String, // "error" or "warning"
     #[serde(default)]
@@ -28,7 +32,8 @@ fn default_schema_version() -> String {
     "1.0".to_string()
 }
 
-pub fn run_analysis(root_path: &Path, skip_analysis: bool) -> Result<DiagnosticsReport> {
+/// Runs luau-analyze on each of the project's script files.
+pub fn run_analysis(lua_files: &[PathBuf], skip_analysis: bool) -> Result<DiagnosticsReport> {
     if skip_analysis {
         return Ok(DiagnosticsReport { errors: Vec::new(), schema_version: "1.0".to_string() });
     }
@@ -45,22 +50,12 @@ pub fn run_analysis(root_path: &Path, skip_analysis: bool) -> Result<Diagnostics
     } else if Path::new("./luau-analyze.exe").exists() {
         "./luau-analyze.exe".to_string()
     } else {
-        return Err(anyhow::anyhow!("`luau-analyze` not found in PATH or current directory. Install it or use --relaxed to skip."));
+        return Err(LuDockError::ConfigError(
+            "`luau-analyze` not found in PATH or current directory. Install it or use --relaxed to skip.".to_string(),
+        )
+        .into());
     };
 
-    // Find all Lua files
-    let mut lua_files = Vec::new();
-    let game_path = root_path.join("game");
-
-    if game_path.exists() {
-        for entry in WalkDir::new(game_path) {
-            let entry = entry?;
-            if entry.path().extension().is_some_and(|e| e == "lua") {
-                lua_files.push(entry.path().to_owned());
-            }
-        }
-    }
-
     let mut diagnostics = Vec::new();
 
     // Check if luau-analyze is available
@@ -70,7 +65,7 @@ pub fn run_analysis(root_path: &Path, skip_analysis: bool) -> Result<Diagnostics
 
     // We run luau-analyze on each file individually for now
     for file in lua_files {
-        let output = Command::new(&analyze_cmd).arg(&file).output()?; // Propagate error if execution fails strangely, though we checked existence
+        let output = Command::new(&analyze_cmd).arg(file).output()?; // Propagate error if execution fails strangely, though we checked existence
 
         let stdout = String::from_utf8_lossy(&output.stdout);
         let stderr = String::from_utf8_lossy(&output.stderr); // luau-analyze usually prints to stdout/stderr
@@ -111,11 +106,8 @@ fn parse_luau_line(line: &str, filepath: &str) -> Option<Diagnostic> {
         let line_num = parts[1].trim().parse::<usize>().ok()?;
 
         // Check if parts[2] is a number (column)
-        let message_start_idx = if parts[2].trim().parse::<usize>().is_ok() {
-            3
-        } else {
-            2
-        };
+        let column = parts[2].trim().parse::<usize>().ok();
+        let message_start_idx = if column.is_some() { 3 } else { 2 };
 
         if parts.len() > message_start_idx {
             let raw_message = parts[message_start_idx..].join(":").trim().to_string();
@@ -142,6 +134,8 @@ fn parse_luau_line(line: &str, filepath: &str) -> Option<Diagnostic> {
             return Some(Diagnostic {
                 file: filepath.to_string(),
                 line: line_num,
+                column,
+                end_column: None,
                 message: raw_message,
                 severity: "error".to_string(), // Assume error mostly
                 code,
@@ -1,7 +1,9 @@
-use glam::Vec3;
+use crate::core::reflection;
+use glam::{Mat3, Mat4, Vec3, Vec4};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -19,6 +21,9 @@ pub struct Instance {
     pub world_bounds: Option<AabbWrapper>,
     #[serde(default)]
     pub center: Option<Vec3Wrapper>,
+    /// The file or directory the instance was loaded from. Not part of `world.json`.
+    #[serde(skip)]
+    pub source: Option<PathBuf>,
 }
 
 impl Instance {
@@ -36,20 +41,250 @@ impl Instance {
             full_path: String::new(),
             world_bounds: None,
             center: None,
+            source: None,
         }
     }
+
+    /// Roblox `IsA`, using the class hierarchy from the reflection database.
+    pub fn is_a(&self, class_name: &str) -> bool {
+        reflection::database().is_a(&self.class_name, class_name)
+    }
+
+    /// The descendant (or self) whose `full_path` is `path`.
+    pub fn find(&self, path: &str) -> Option<&Instance> {
+        if self.full_path == path {
+            return Some(self);
+        }
+        self.children.iter().find_map(|child| child.find(path))
+    }
+
+    /// Resolves a `--target`: a `full_path` (`game/Workspace/House`) or a dotted path
+    /// walked like `FindFirstChild` (`Workspace.House`, `game.Workspace.House`). The
+    /// leading `game` is optional in both forms.
+    pub fn resolve_path(&self, path: &str) -> Option<&Instance> {
+        if let Some(instance) = self.find(path) {
+            return Some(instance);
+        }
+        let separator = if path.contains('/') { '/' } else { '.' };
+        let mut segments = path.split(separator).filter(|s| !s.is_empty()).peekable();
+        if segments.peek() == Some(&"game") {
+            segments.next();
+        }
+        segments.try_fold(self, |instance, name| instance.children.iter().find(|c| c.name == name))
+    }
+
+    /// Whether `file` belongs to the subtree at `path`: the instance loaded from the
+    /// file, or from the closest directory containing it, is inside the subtree.
+    /// Files no instance comes from (templates, `.ludock/defs`) count as inside.
+    pub fn file_in_subtree(&self, path: &str, file: &Path) -> bool {
+        fn closest<'a>(instance: &'a Instance, file: &Path, best: &mut Option<(usize, &'a Instance)>) {
+            if let Some(source) = &instance.source
+                && file.starts_with(source)
+            {
+                let depth = source.components().count();
+                if best.is_none_or(|(d, _)| depth >= d) {
+                    *best = Some((depth, instance));
+                }
+            }
+            for child in &instance.children {
+                closest(child, file, best);
+            }
+        }
+        let mut best = None;
+        closest(self, file, &mut best);
+        best.is_none_or(|(_, owner)| owner.is_in(path))
+    }
+
+    /// Whether this instance is the one at `path` or one of its descendants.
+    pub fn is_in(&self, path: &str) -> bool {
+        self.full_path.strip_prefix(path).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    }
+
+    /// The property as set on this instance, or the class default when it isn't.
+    pub fn property_or_default(&self, name: &str) -> Option<PropertyValue> {
+        self.properties
+            .get(name)
+            .or_else(|| reflection::database().default_value(&self.class_name, name))
+            .cloned()
+    }
+
+    /// Resolves a part's placement from `CFrame`, falling back to `Position`.
+    /// `Orientation` (degrees, applied Y-X-Z like Roblox) replaces the rotation when present.
+    pub fn part_transform(&self) -> Mat4 {
+        let mut cframe = match self.properties.get("CFrame") {
+            Some(PropertyValue::CFrame(cf)) => cf.clone(),
+            _ => match self.properties.get("Position") {
+                Some(PropertyValue::Vector3(pos)) => CFrameWrapper::new(pos.x, pos.y, pos.z),
+                _ => CFrameWrapper::new(0.0, 0.0, 0.0),
+            },
+        };
+
+        if let Some(PropertyValue::Vector3(o)) = self.properties.get("Orientation") {
+            let rotation = CFrameWrapper::from_euler_angles_yxz(
+                o.x.to_radians(),
+                o.y.to_radians(),
+                o.z.to_radians(),
+            );
+            cframe = CFrameWrapper::from_position_rotation(cframe.translation(), rotation.rotation());
+        }
+
+        cframe.to_mat4()
+    }
+
+    /// The transform a Model applies to its descendants, which are authored in model
+    /// space. When `WorldPivot` is set, the model's pivot (the origin, or
+    /// `PrimaryPart.CFrame * PivotOffset` when `PrimaryPart` is set) is moved there, so
+    /// changing `WorldPivot` moves every descendant part. Identity otherwise.
+    /// This is LuDock's convention: Roblox keeps part CFrames in world space, which
+    /// `to_world_space` converts to on export.
+    pub fn pivot_transform(&self) -> Mat4 {
+        if !self.is_a("Model") || self.class_name == "Workspace" {
+            return Mat4::IDENTITY;
+        }
+        let world_pivot = match self.properties.get("WorldPivot") {
+            Some(PropertyValue::CFrame(cf)) => cf.to_mat4(),
+            _ => return Mat4::IDENTITY,
+        };
+        let pivot = match self.properties.get("PrimaryPart") {
+            Some(PropertyValue::Ref(r)) => {
+                self.transform_of(&r.instance).map(|(part, transform)| transform * part.pivot_offset())
+            }
+            _ => None,
+        };
+        world_pivot * pivot.unwrap_or(Mat4::IDENTITY).inverse()
+    }
+
+    /// The space children are placed in, given the space this instance is placed in.
+    pub fn children_transform(&self, parent: Mat4) -> Mat4 {
+        parent * self.pivot_transform()
+    }
+
+    /// The transform of the space the descendant at `path` is placed in, relative to
+    /// the space this instance is placed in. `None` if there is no such descendant.
+    pub fn space_of(&self, path: &str) -> Option<Mat4> {
+        let inner = self.pivot_transform();
+        self.children.iter().find_map(|child| {
+            if child.full_path == path {
+                Some(inner)
+            } else if path.strip_prefix(child.full_path.as_str()).is_some_and(|r| r.starts_with('/')) {
+                child.space_of(path).map(|space| inner * space)
+            } else {
+                None
+            }
+        })
+    }
+
+    fn pivot_offset(&self) -> Mat4 {
+        match self.properties.get("PivotOffset") {
+            Some(PropertyValue::CFrame(cf)) => cf.to_mat4(),
+            _ => Mat4::IDENTITY,
+        }
+    }
+
+    /// A descendant part and its transform in this instance's model space.
+    fn transform_of(&self, path: &str) -> Option<(&Instance, Mat4)> {
+        let child = self
+            .children
+            .iter()
+            .find(|c| c.full_path == path || path.strip_prefix(c.full_path.as_str()).is_some_and(|r| r.starts_with('/')))?;
+        if child.full_path == path {
+            return child.is_a("BasePart").then(|| (child, child.part_transform()));
+        }
+        let (part, transform) = child.transform_of(path)?;
+        Some((part, child.pivot_transform() * transform))
+    }
+}
+
+/// Rewrites model-space placements as world space, the way Roblox files store them:
+/// parts get a world `CFrame` and Models a world `WorldPivot`. `parent` maps the space
+/// `instance` is placed in to world space.
+pub fn to_world_space(instance: &mut Instance, parent: Mat4) {
+    let children_space = instance.children_transform(parent);
+    if instance.is_a("BasePart") {
+        let transform = parent * instance.part_transform();
+        set_placement(instance, "CFrame", transform);
+    }
+    if let Some(PropertyValue::CFrame(pivot)) = instance.properties.get("WorldPivot") {
+        let pivot = parent * pivot.to_mat4();
+        set_placement(instance, "WorldPivot", pivot);
+    }
+    for child in &mut instance.children {
+        to_world_space(child, children_space);
+    }
+}
+
+/// The inverse of `to_world_space`, for instances read from Roblox files. `inverse`
+/// maps world space to the space `instance` is placed in. Descendants of a Model
+/// become relative to its pivot, unless the pivot follows its `PrimaryPart`, in
+/// which case `WorldPivot` is recomputed from that part.
+pub fn to_model_space(instance: &mut Instance, inverse: Mat4) {
+    if instance.is_a("BasePart") {
+        let transform = inverse * instance.part_transform();
+        set_placement(instance, "CFrame", transform);
+    }
+    let mut children_inverse = inverse;
+    if instance.is_a("Model") && instance.class_name != "Workspace" {
+        let primary = match instance.properties.get("PrimaryPart") {
+            Some(PropertyValue::Ref(r)) => instance.find(&r.instance).filter(|p| p.is_a("BasePart")),
+            _ => None,
+        };
+        let world_pivot = match primary {
+            Some(part) => Some(part.part_transform() * part.pivot_offset()),
+            None => match instance.properties.get("WorldPivot") {
+                Some(PropertyValue::CFrame(cf)) => {
+                    let pivot = inverse * cf.to_mat4();
+                    children_inverse = pivot.inverse() * inverse;
+                    Some(cf.to_mat4())
+                }
+                _ => None,
+            },
+        };
+        if let Some(world_pivot) = world_pivot {
+            set_placement(instance, "WorldPivot", inverse * world_pivot);
+        }
+    }
+    for child in &mut instance.children {
+        to_model_space(child, children_inverse);
+    }
+}
+
+fn set_placement(instance: &mut Instance, key: &str, transform: Mat4) {
+    let cframe = CFrameWrapper::from_position_rotation(transform.w_axis.truncate(), Mat3::from_mat4(transform));
+    if key == "CFrame" {
+        instance.properties.remove("Position");
+        instance.properties.remove("Orientation");
+    }
+    instance.properties.insert(key.to_string(), PropertyValue::CFrame(cframe));
 }
 
+// Untagged: deserialization tries variants in order, so a struct must come before any
+// variant whose fields are a subset of its own (Vector3 before Vector2).
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 #[serde(untagged)]
 pub enum PropertyValue {
     String(String),
     Bool(bool),
-    Number(f64),
+    Number(
+        #[serde(with = "json_float")]
+        #[schemars(with = "json_float::JsonFloat")]
+        f64,
+    ),
     Vector3(Vec3Wrapper),
+    Vector2(Vector2Wrapper),
     CFrame(CFrameWrapper),
     Color3(Color3Wrapper),
     UDim2(UDim2Wrapper),
+    UDim(UDimWrapper),
+    Rect(RectWrapper),
+    NumberRange(NumberRangeWrapper),
+    NumberSequence(NumberSequenceWrapper),
+    ColorSequence(ColorSequenceWrapper),
+    BrickColor(BrickColorWrapper),
+    Font(FontWrapper),
+    PhysicalProperties(PhysicalPropertiesWrapper),
+    Faces(FacesWrapper),
+    Axes(AxesWrapper),
+    Ref(InstanceRef),
     Enum(String), // e.g. "Enum.PartType.Block"
 }
 
@@ -61,10 +296,122 @@ pub struct AabbWrapper {
 
 // Wrapper structs to implement Serde for external types or custom formatting
 
+/// Serde for the float fields of property values. JSON has no infinities or NaN (serde_json
+/// writes them as `null`, which can't be read back), but `math.huge` is valid DSL, so
+/// non-finite floats are stored as their DSL text: `math.huge`, `-math.huge`, `0 / 0`.
+mod json_float {
+    use crate::core::printer::format_number;
+    use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+    use serde::de::{self, Deserializer, Visitor};
+    use serde::{Deserialize, Serialize, Serializer};
+    use std::borrow::Cow;
+    use std::fmt;
+
+    /// Schema of a float stored this way.
+    pub struct JsonFloat;
+
+    impl JsonSchema for JsonFloat {
+        fn schema_name() -> Cow<'static, str> {
+            "JsonFloat".into()
+        }
+
+        fn json_schema(_: &mut SchemaGenerator) -> Schema {
+            json_schema!({
+                "anyOf": [
+                    { "type": "number" },
+                    { "enum": ["math.huge", "-math.huge", "0 / 0"] }
+                ]
+            })
+        }
+    }
+
+    pub trait Float: Copy + Into<f64> + fmt::Display + fmt::LowerExp + Serialize {
+        fn from_f64(n: f64) -> Self;
+    }
+
+    impl Float for f32 {
+        fn from_f64(n: f64) -> Self {
+            n as f32
+        }
+    }
+
+    impl Float for f64 {
+        fn from_f64(n: f64) -> Self {
+            n
+        }
+    }
+
+    pub fn serialize<T: Float, S: Serializer>(n: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        let wide: f64 = (*n).into();
+        if wide.is_finite() {
+            n.serialize(serializer)
+        } else {
+            serializer.serialize_str(&format_number(*n))
+        }
+    }
+
+    pub fn deserialize<'de, T: Float, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+        struct FloatVisitor;
+
+        impl Visitor<'_> for FloatVisitor {
+            type Value = f64;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a number, `math.huge`, `-math.huge` or `0 / 0`")
+            }
+
+            fn visit_f64<E>(self, n: f64) -> Result<f64, E> {
+                Ok(n)
+            }
+
+            fn visit_i64<E>(self, n: i64) -> Result<f64, E> {
+                Ok(n as f64)
+            }
+
+            fn visit_u64<E>(self, n: u64) -> Result<f64, E> {
+                Ok(n as f64)
+            }
+
+            fn visit_str<E: de::Error>(self, s: &str) -> Result<f64, E> {
+                match s {
+                    "math.huge" => Ok(f64::INFINITY),
+                    "-math.huge" => Ok(f64::NEG_INFINITY),
+                    "0 / 0" => Ok(f64::NAN),
+                    _ => Err(E::invalid_value(de::Unexpected::Str(s), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(FloatVisitor).map(T::from_f64)
+    }
+
+    /// The same, for `CFrame` components.
+    pub mod array {
+        use super::*;
+
+        #[derive(Serialize, Deserialize)]
+        struct Component(#[serde(with = "super")] f32);
+
+        pub fn serialize<S: Serializer>(components: &[f32; 12], serializer: S) -> Result<S::Ok, S::Error> {
+            components.map(Component).serialize(serializer)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[f32; 12], D::Error> {
+            <[Component; 12]>::deserialize(deserializer).map(|components| components.map(|c| c.0))
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct Vec3Wrapper {
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
     pub x: f32,
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
     pub y: f32,
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
     pub z: f32,
 }
 
@@ -87,11 +434,13 @@ impl From<Vec3Wrapper> for Vec3 {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct CFrameWrapper {
     pub position: Vec3Wrapper,
-    // For now, simple position + identity rotation or basic lookAt could be stored.
-    // Storing full matrix in a simple way.
+    #[serde(with = "json_float::array")]
+    #[schemars(with = "[json_float::JsonFloat; 12]")]
     pub components: [f32; 12],
 }
 
+// `components` follows Roblox's `CFrame:GetComponents()` order:
+// x, y, z, R00, R01, R02, R10, R11, R12, R20, R21, R22 (row-major rotation).
 impl CFrameWrapper {
     pub fn new(x: f32, y: f32, z: f32) -> Self {
         CFrameWrapper {
@@ -99,12 +448,94 @@ impl CFrameWrapper {
             components: [x, y, z, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0],
         }
     }
+
+    pub fn from_components(components: [f32; 12]) -> Self {
+        CFrameWrapper {
+            position: Vec3Wrapper {
+                x: components[0],
+                y: components[1],
+                z: components[2],
+            },
+            components,
+        }
+    }
+
+    pub fn from_position_rotation(position: Vec3, rotation: Mat3) -> Self {
+        let r = rotation.transpose().to_cols_array(); // rows of `rotation`
+        CFrameWrapper::from_components([
+            position.x, position.y, position.z, r[0], r[1], r[2], r[3], r[4], r[5], r[6], r[7],
+            r[8],
+        ])
+    }
+
+    /// `CFrame.Angles` / `CFrame.fromEulerAnglesXYZ`: rotates around Z, then Y, then X.
+    pub fn from_euler_angles_xyz(rx: f32, ry: f32, rz: f32) -> Self {
+        let rotation = Mat3::from_rotation_x(rx) * Mat3::from_rotation_y(ry) * Mat3::from_rotation_z(rz);
+        CFrameWrapper::from_position_rotation(Vec3::ZERO, rotation)
+    }
+
+    /// `CFrame.fromEulerAnglesYXZ` / `CFrame.fromOrientation`: rotates around Z, then X, then Y.
+    pub fn from_euler_angles_yxz(rx: f32, ry: f32, rz: f32) -> Self {
+        let rotation = Mat3::from_rotation_y(ry) * Mat3::from_rotation_x(rx) * Mat3::from_rotation_z(rz);
+        CFrameWrapper::from_position_rotation(Vec3::ZERO, rotation)
+    }
+
+    /// `CFrame.lookAt(at, target, up)`: positioned at `at` with its LookVector (-Z) facing `target`.
+    pub fn look_at(at: Vec3, target: Vec3, up: Vec3) -> Self {
+        let forward = (target - at).normalize_or_zero();
+        if forward == Vec3::ZERO {
+            return CFrameWrapper::new(at.x, at.y, at.z);
+        }
+        // Fall back to another up axis when looking straight along `up`.
+        let mut right = forward.cross(up);
+        if right.length_squared() < 1e-8 {
+            right = forward.cross(Vec3::Z);
+        }
+        let right = right.normalize();
+        let up = right.cross(forward);
+        CFrameWrapper::from_position_rotation(at, Mat3::from_cols(right, up, -forward))
+    }
+
+    pub fn translation(&self) -> Vec3 {
+        Vec3::new(self.components[0], self.components[1], self.components[2])
+    }
+
+    pub fn rotation(&self) -> Mat3 {
+        let c = &self.components;
+        Mat3::from_cols(
+            Vec3::new(c[3], c[6], c[9]),
+            Vec3::new(c[4], c[7], c[10]),
+            Vec3::new(c[5], c[8], c[11]),
+        )
+    }
+
+    pub fn to_mat4(&self) -> Mat4 {
+        let r = self.rotation();
+        Mat4::from_cols(
+            r.x_axis.extend(0.0),
+            r.y_axis.extend(0.0),
+            r.z_axis.extend(0.0),
+            Vec4::new(self.components[0], self.components[1], self.components[2], 1.0),
+        )
+    }
+
+    /// Composition `self * other`, as in Luau.
+    pub fn mul(&self, other: &CFrameWrapper) -> Self {
+        let position = self.translation() + self.rotation() * other.translation();
+        CFrameWrapper::from_position_rotation(position, self.rotation() * other.rotation())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct Color3Wrapper {
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
     pub r: f32,
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
     pub g: f32,
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
     pub b: f32,
 }
 
@@ -123,8 +554,288 @@ impl Color3Wrapper {
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
 pub struct UDim2Wrapper {
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
     pub xs: f32,
     pub xo: i32,
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
     pub ys: f32,
     pub yo: i32,
 }
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct Vector2Wrapper {
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
+    pub x: f32,
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
+    pub y: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct UDimWrapper {
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
+    pub scale: f32,
+    pub offset: i32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct RectWrapper {
+    pub min: Vector2Wrapper,
+    pub max: Vector2Wrapper,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct NumberRangeWrapper {
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
+    pub min: f32,
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
+    pub max: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct NumberSequenceKeypoint {
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
+    pub time: f32,
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
+    pub value: f32,
+    #[serde(default, with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
+    pub envelope: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct NumberSequenceWrapper {
+    pub keypoints: Vec<NumberSequenceKeypoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ColorSequenceKeypoint {
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
+    pub time: f32,
+    pub color: Color3Wrapper,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct ColorSequenceWrapper {
+    pub keypoints: Vec<ColorSequenceKeypoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct BrickColorWrapper {
+    pub number: u32,
+    pub name: String,
+}
+
+// Subset of the BrickColor palette: (number, name, RGB).
+const BRICK_COLORS: &[(u32, &str, [u8; 3])] = &[
+    (1, "White", [242, 243, 243]),
+    (2, "Grey", [161, 165, 162]),
+    (3, "Light yellow", [249, 233, 153]),
+    (5, "Brick yellow", [215, 197, 154]),
+    (6, "Light green (Mint)", [194, 218, 184]),
+    (9, "Light reddish violet", [232, 186, 200]),
+    (11, "Pastel Blue", [128, 187, 219]),
+    (12, "Light orange brown", [203, 132, 66]),
+    (18, "Nougat", [204, 142, 105]),
+    (21, "Bright red", [196, 40, 28]),
+    (22, "Med. reddish violet", [196, 112, 160]),
+    (23, "Bright blue", [13, 105, 172]),
+    (24, "Bright yellow", [245, 205, 48]),
+    (25, "Earth orange", [98, 71, 50]),
+    (26, "Black", [27, 42, 53]),
+    (27, "Dark grey", [109, 110, 108]),
+    (28, "Dark green", [40, 127, 71]),
+    (29, "Medium green", [161, 196, 140]),
+    (37, "Bright green", [75, 151, 75]),
+    (38, "Dark orange", [160, 95, 53]),
+    (45, "Light blue", [180, 210, 228]),
+    (101, "Medium red", [218, 134, 122]),
+    (102, "Medium blue", [110, 153, 202]),
+    (104, "Bright violet", [107, 50, 124]),
+    (105, "Br. yellowish orange", [226, 155, 64]),
+    (106, "Bright orange", [218, 133, 65]),
+    (107, "Bright bluish green", [0, 143, 156]),
+    (119, "Br. yellowish green", [164, 189, 71]),
+    (125, "Light orange", [234, 184, 146]),
+    (135, "Sand blue", [116, 134, 157]),
+    (141, "Earth green", [39, 70, 45]),
+    (151, "Sand green", [120, 144, 130]),
+    (153, "Sand red", [149, 121, 119]),
+    (192, "Reddish brown", [105, 64, 40]),
+    (194, "Medium stone grey", [163, 162, 165]),
+    (199, "Dark stone grey", [99, 95, 98]),
+    (208, "Light stone grey", [229, 228, 223]),
+    (217, "Brown", [124, 92, 70]),
+    (226, "Cool yellow", [253, 234, 141]),
+    (1001, "Institutional white", [248, 248, 248]),
+    (1002, "Mid gray", [205, 205, 205]),
+    (1003, "Really black", [17, 17, 17]),
+    (1004, "Really red", [255, 0, 0]),
+    (1005, "Deep orange", [255, 176, 0]),
+    (1006, "Alder", [180, 128, 255]),
+    (1007, "Dusty Rose", [163, 75, 75]),
+    (1008, "Olive", [193, 190, 66]),
+    (1009, "New Yeller", [255, 255, 0]),
+    (1010, "Really blue", [0, 0, 255]),
+    (1011, "Navy blue", [0, 32, 96]),
+    (1012, "Deep blue", [33, 84, 185]),
+    (1013, "Cyan", [4, 175, 236]),
+    (1014, "CGA brown", [170, 85, 0]),
+    (1015, "Magenta", [170, 0, 170]),
+    (1016, "Pink", [255, 102, 204]),
+    (1018, "Teal", [18, 238, 212]),
+    (1019, "Toothpaste", [0, 255, 255]),
+    (1020, "Lime green", [0, 255, 0]),
+    (1021, "Camo", [58, 125, 21]),
+    (1022, "Grime", [127, 142, 100]),
+    (1023, "Lavender", [140, 91, 159]),
+    (1024, "Pastel light blue", [175, 221, 255]),
+    (1025, "Pastel orange", [255, 201, 201]),
+    (1026, "Pastel violet", [177, 167, 255]),
+    (1027, "Pastel blue-green", [159, 243, 233]),
+    (1028, "Pastel green", [204, 255, 204]),
+    (1029, "Pastel yellow", [255, 255, 204]),
+    (1030, "Pastel brown", [255, 204, 153]),
+    (1031, "Royal purple", [98, 37, 209]),
+    (1032, "Hot pink", [255, 0, 191]),
+];
+
+impl BrickColorWrapper {
+    pub fn from_number(number: u32) -> Option<Self> {
+        BRICK_COLORS
+            .iter()
+            .find(|(n, _, _)| *n == number)
+            .map(|(n, name, _)| BrickColorWrapper { number: *n, name: name.to_string() })
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        BRICK_COLORS
+            .iter()
+            .find(|(_, n, _)| *n == name)
+            .map(|(n, name, _)| BrickColorWrapper { number: *n, name: name.to_string() })
+    }
+
+    pub fn color(&self) -> Color3Wrapper {
+        let [r, g, b] = BRICK_COLORS
+            .iter()
+            .find(|(n, _, _)| *n == self.number)
+            .map_or([163, 162, 165], |(_, _, rgb)| *rgb);
+        Color3Wrapper::from_rgb(r as f32, g as f32, b as f32)
+    }
+}
+
+/// Font face as in `Font.new(family, weight, style)`; weight and style hold enum item
+/// names (`Bold`, `Italic`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct FontWrapper {
+    pub family: String,
+    pub weight: String,
+    pub style: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct PhysicalPropertiesWrapper {
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
+    pub density: f32,
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
+    pub friction: f32,
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
+    pub elasticity: f32,
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
+    pub friction_weight: f32,
+    #[serde(with = "json_float")]
+    #[schemars(with = "json_float::JsonFloat")]
+    pub elasticity_weight: f32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct FacesWrapper {
+    pub top: bool,
+    pub bottom: bool,
+    pub left: bool,
+    pub right: bool,
+    pub back: bool,
+    pub front: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct AxesWrapper {
+    pub x: bool,
+    pub y: bool,
+    pub z: bool,
+}
+
+/// Reference to another instance by `full_path`. Relative paths written in the DSL
+/// (`Ref("Handle")`, `Ref("../Base")`) are resolved against the owning instance at load time.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, JsonSchema)]
+pub struct InstanceRef {
+    pub instance: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_value;
+
+    #[test]
+    fn test_model_pivot_moves_descendants() {
+        let mut model = Instance::new("Door", "Model", "door");
+        model.full_path = "game/Workspace/Door".to_string();
+        let mut frame = Instance::new("Frame", "Part", "door/Frame");
+        frame.full_path = "game/Workspace/Door/Frame".to_string();
+        frame.properties.insert("CFrame".to_string(), parse_value("CFrame.new(0, 4, 0)").unwrap());
+        frame.properties.insert("PivotOffset".to_string(), parse_value("CFrame.new(0, -4, 0)").unwrap());
+        model.children.push(frame);
+        assert_eq!(model.pivot_transform(), Mat4::IDENTITY);
+
+        // The pivot (Frame's base) goes to WorldPivot; Frame keeps its height above it.
+        model.properties.insert(
+            "PrimaryPart".to_string(),
+            PropertyValue::Ref(InstanceRef { instance: "game/Workspace/Door/Frame".to_string() }),
+        );
+        model.properties.insert("WorldPivot".to_string(), parse_value("CFrame.new(10, 0, 0)").unwrap());
+        let world = model.children_transform(Mat4::IDENTITY) * model.children[0].part_transform();
+        assert!(world.w_axis.truncate().abs_diff_eq(Vec3::new(10.0, 4.0, 0.0), 1e-5));
+
+        let mut exported = model.clone();
+        to_world_space(&mut exported, Mat4::IDENTITY);
+        assert_eq!(exported.children[0].part_transform(), world);
+        to_model_space(&mut exported, Mat4::IDENTITY);
+        let reloaded = exported.children_transform(Mat4::IDENTITY) * exported.children[0].part_transform();
+        assert!(reloaded.abs_diff_eq(world, 1e-5));
+    }
+
+    #[test]
+    fn test_non_finite_numbers_survive_world_json() {
+        let mut part = Instance::new("Floor", "Part", "floor");
+        part.full_path = "game/Floor".to_string();
+        for (key, value) in [
+            ("MaxForce", "math.huge"),
+            ("Size", "Vector3.new(-math.huge, 1, 0 / 0)"),
+            ("Range", "NumberRange.new(0, math.huge)"),
+        ] {
+            part.properties.insert(key.to_string(), parse_value(value).unwrap());
+        }
+
+        let json = serde_json::to_string(&part).unwrap();
+        let properties = serde_json::to_string(&part.properties).unwrap();
+        assert!(!properties.contains("null"), "{}", properties);
+        assert!(json.contains(r#""MaxForce":"math.huge""#), "{}", json);
+        let reloaded: Instance = serde_json::from_str(&json).unwrap();
+        assert!(matches!(&reloaded.properties["Size"], PropertyValue::Vector3(v) if v.x == f32::NEG_INFINITY && v.z.is_nan()));
+        assert_eq!(crate::core::diff::compare_worlds(&reloaded, &part).status, "unchanged");
+    }
+}
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use schemars::JsonSchema;
-use crate::core::datamodel::{Instance, Vec3Wrapper};
+use crate::core::datamodel::{Instance, PropertyValue, Vec3Wrapper};
+use crate::core::printer::print_value;
 use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
@@ -26,7 +27,7 @@ pub struct InstanceDiff {
 
 #[derive(Serialize, Deserialize, JsonSchema, Debug)]
 pub struct PropertyChange {
-    pub old: String, // Stringified for simplicity in diff
+    pub old: String, // DSL text (`Vector2.new(1, 2)`), or "null" for an added property
     pub new: String,
 }
 
@@ -51,14 +52,14 @@ pub fn compare_worlds(old: &Instance, new: &Instance) -> DiffReport {
     flatten_instance(new, &mut new_map);
 
     // Detect Added
-    for (path, _) in &new_map {
+    for path in new_map.keys() {
         if !old_map.contains_key(path) {
             report.changes.added_instances.push(path.clone());
         }
     }
 
     // Detect Removed
-    for (path, _) in &old_map {
+    for path in old_map.keys() {
         if !new_map.contains_key(path) {
             report.changes.removed_instances.push(path.clone());
         }
@@ -76,17 +77,17 @@ pub fn compare_worlds(old: &Instance, new: &Instance) -> DiffReport {
             // Compare Properties
             for (k, new_v) in &new_inst.properties {
                 if let Some(old_v) = old_inst.properties.get(k) {
-                    if new_v != old_v {
+                    if !same_value(old_v, new_v) {
                         diff.property_changes.insert(k.clone(), PropertyChange {
-                            old: format!("{:?}", old_v),
-                            new: format!("{:?}", new_v),
+                            old: print_value(old_v),
+                            new: print_value(new_v),
                         });
                     }
                 } else {
                     // Property Added (treated as change from None)
                     diff.property_changes.insert(k.clone(), PropertyChange {
                         old: "null".to_string(),
-                        new: format!("{:?}", new_v),
+                        new: print_value(new_v),
                     });
                 }
             }
@@ -126,9 +127,123 @@ pub fn compare_worlds(old: &Instance, new: &Instance) -> DiffReport {
     report
 }
 
+impl DiffReport {
+    /// Keeps only changes at `path` or below it, for `--target`.
+    pub fn restrict_to(&mut self, path: &str) {
+        let inside = |p: &String| p.strip_prefix(path).is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+        self.changes.added_instances.retain(inside);
+        self.changes.removed_instances.retain(inside);
+        self.changes.modified_instances.retain(|m| inside(&m.path));
+        let changes = &self.changes;
+        let changed = !changes.added_instances.is_empty()
+            || !changes.removed_instances.is_empty()
+            || !changes.modified_instances.is_empty();
+        self.status = if changed { "changed" } else { "unchanged" }.to_string();
+    }
+}
+
+// Values are compared as they are stored in `world.json`, since the previous world is
+// read back from disk: enums come back as plain strings. Numbers only need to agree to a
+// relative epsilon, so an expression that rounds differently (`0.1 + 0.2` against `0.3`)
+// isn't reported as a change.
+fn same_value(old: &PropertyValue, new: &PropertyValue) -> bool {
+    match (serde_json::to_value(old), serde_json::to_value(new)) {
+        (Ok(a), Ok(b)) => same_json(&a, &b),
+        _ => old == new,
+    }
+}
+
+fn same_json(a: &serde_json::Value, b: &serde_json::Value) -> bool {
+    use serde_json::Value;
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => (a - b).abs() <= 1e-5 * a.abs().max(b.abs()).max(1.0),
+            _ => a == b,
+        },
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_json(a, b)),
+        (Value::Object(a), Value::Object(b)) => {
+            a.len() == b.len() && a.iter().all(|(k, v)| b.get(k).is_some_and(|w| same_json(v, w)))
+        }
+        _ => a == b,
+    }
+}
+
 fn flatten_instance<'a>(root: &'a Instance, map: &mut HashMap<String, &'a Instance>) {
     map.insert(root.full_path.clone(), root);
     for child in &root.children {
         flatten_instance(child, map);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_value;
+
+    #[test]
+    fn test_restrict_to_target_subtree() {
+        let world = |names: &[&str]| {
+            let mut root = Instance::new("game", "DataModel", "game");
+            root.full_path = "game".to_string();
+            for name in names {
+                let mut child = Instance::new(name, "Part", name);
+                child.full_path = format!("game/{}", name);
+                root.children.push(child);
+            }
+            root
+        };
+        let old = world(&["House", "Tree"]);
+        let new = world(&["House", "HouseLamp", "Garden"]);
+
+        let mut report = compare_worlds(&old, &new);
+        report.restrict_to("game/House");
+        assert_eq!(report.status, "unchanged"); // `HouseLamp` is a sibling, not inside
+
+        let mut report = compare_worlds(&old, &new);
+        report.restrict_to("game/Tree");
+        assert_eq!(report.changes.removed_instances, ["game/Tree"]);
+        assert!(report.changes.added_instances.is_empty());
+        assert_eq!(report.status, "changed");
+    }
+
+    #[test]
+    fn test_typed_property_changes() {
+        let world = |properties: &[(&str, &str)]| {
+            let mut root = Instance::new("game", "DataModel", "game");
+            root.full_path = "game".to_string();
+            let mut part = Instance::new("Sign", "Part", "sign");
+            part.full_path = "game/Sign".to_string();
+            for (key, value) in properties {
+                part.properties.insert(key.to_string(), parse_value(value).unwrap());
+            }
+            root.children.push(part);
+            root
+        };
+        let old = world(&[
+            ("Offset", "Vector2.new(1, 2)"),
+            ("Position", "UDim2.new(0, 10, 0.5, 0)"),
+            ("CFrame", "CFrame.new(0, 5, 0)"),
+            ("Material", "Enum.Material.Wood"),
+            ("Transparency", "0.3"),
+        ]);
+        let new = world(&[
+            ("Offset", "Vector2.new(1, 3)"),
+            ("Position", "UDim2.new(0, 10, 0.5, 20)"),
+            ("CFrame", "CFrame.new(0, 5, 0) * CFrame.Angles(0, 0, math.pi)"),
+            ("Material", "Enum.Material.Wood"),
+            ("Transparency", "0.1 + 0.2"),
+        ]);
+
+        // The previous world is read back from world.json
+        let old: Instance = serde_json::from_str(&serde_json::to_string(&old).unwrap()).unwrap();
+        let report = compare_worlds(&old, &new);
+        let changes = &report.changes.modified_instances[0].property_changes;
+        let mut changed: Vec<_> = changes.keys().map(String::as_str).collect();
+        changed.sort();
+        assert_eq!(changed, ["CFrame", "Offset", "Position"]);
+        assert_eq!((changes["Offset"].old.as_str(), changes["Offset"].new.as_str()), ("Vector2.new(1, 2)", "Vector2.new(1, 3)"));
+        assert_eq!(changes["Position"].new, "UDim2.new(0, 10, 0.5, 20)");
+        assert_eq!(changes["CFrame"].old, "CFrame.new(0, 5, 0)");
+        assert!(changes["CFrame"].new.starts_with("CFrame.new(0, 5, 0, -1, "), "{}", changes["CFrame"].new);
+    }
+}
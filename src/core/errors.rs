@@ -8,6 +8,7 @@ pub enum LuDockError {
     #[error("DSL parsing failed: {0}")]
     DslError(String),
 
+    #[allow(dead_code)] // Not raised yet; its exit code is part of the CLI contract
     #[error("World validation failed: {0}")]
     WorldError(String),
 
@@ -20,6 +21,7 @@ pub enum LuDockError {
     #[error("IO Error: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[allow(dead_code)] // Not raised yet; its exit code is part of the CLI contract
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
@@ -0,0 +1,719 @@
+use crate::core::datamodel::{
+    AxesWrapper, BrickColorWrapper, CFrameWrapper, Color3Wrapper, ColorSequenceKeypoint,
+    ColorSequenceWrapper, FacesWrapper, FontWrapper, InstanceRef, NumberRangeWrapper,
+    NumberSequenceKeypoint, NumberSequenceWrapper, PhysicalPropertiesWrapper, PropertyValue,
+    RectWrapper, UDim2Wrapper, UDimWrapper, Vector2Wrapper,
+};
+use crate::core::parser::{BinaryOp, Expr};
+use crate::core::reflection::database;
+use glam::{Vec2, Vec3};
+use std::collections::HashMap;
+
+// Intermediate results: keypoints and tables only exist as constructor arguments.
+#[derive(Debug, Clone)]
+enum Value {
+    Property(PropertyValue),
+    Table(Vec<Value>),
+    NumberKeypoint(NumberSequenceKeypoint),
+    ColorKeypoint(ColorSequenceKeypoint),
+}
+
+/// Variables visible to an expression: project defs plus enclosing `local`s.
+pub type Scope = HashMap<String, PropertyValue>;
+
+type Builder = fn(&[PropertyValue]) -> Option<PropertyValue>;
+
+/// Evaluates a DSL expression into a property value, or explains why it can't.
+pub fn evaluate(expr: &Expr, scope: &Scope) -> Result<PropertyValue, String> {
+    property(eval(expr, scope)?)
+}
+
+fn eval(expr: &Expr, scope: &Scope) -> Result<Value, String> {
+    match expr {
+        Expr::Literal(value) => Ok(Value::Property(value.clone())),
+        Expr::Name(name) => match scope.get(name) {
+            Some(value) => Ok(Value::Property(value.clone())),
+            None => eval_name(name).map(Value::Property),
+        },
+        Expr::Table(items) => items
+            .iter()
+            .map(|item| eval(item, scope))
+            .collect::<Result<_, _>>()
+            .map(Value::Table),
+        Expr::Call { callee, args } => {
+            let args = args
+                .iter()
+                .map(|arg| eval(arg, scope))
+                .collect::<Result<Vec<_>, _>>()?;
+            eval_call(callee, args)
+        }
+        Expr::Neg(inner) => negate(property(eval(inner, scope)?)?).map(Value::Property),
+        Expr::Binary(op, lhs, rhs) => {
+            let lhs = property(eval(lhs, scope)?)?;
+            let rhs = property(eval(rhs, scope)?)?;
+            binary(*op, lhs, rhs).map(Value::Property)
+        }
+    }
+}
+
+fn property(value: Value) -> Result<PropertyValue, String> {
+    match value {
+        Value::Property(value) => Ok(value),
+        Value::Table(_) => Err("a table is only valid as a sequence argument".to_string()),
+        Value::NumberKeypoint(_) | Value::ColorKeypoint(_) => {
+            Err("a keypoint is only valid inside a sequence table".to_string())
+        }
+    }
+}
+
+fn type_name(value: &PropertyValue) -> &'static str {
+    match value {
+        PropertyValue::String(_) => "string",
+        PropertyValue::Bool(_) => "boolean",
+        PropertyValue::Number(_) => "number",
+        PropertyValue::Vector3(_) => "Vector3",
+        PropertyValue::Vector2(_) => "Vector2",
+        PropertyValue::CFrame(_) => "CFrame",
+        PropertyValue::Color3(_) => "Color3",
+        PropertyValue::UDim2(_) => "UDim2",
+        PropertyValue::UDim(_) => "UDim",
+        PropertyValue::Rect(_) => "Rect",
+        PropertyValue::NumberRange(_) => "NumberRange",
+        PropertyValue::NumberSequence(_) => "NumberSequence",
+        PropertyValue::ColorSequence(_) => "ColorSequence",
+        PropertyValue::BrickColor(_) => "BrickColor",
+        PropertyValue::Font(_) => "Font",
+        PropertyValue::PhysicalProperties(_) => "PhysicalProperties",
+        PropertyValue::Faces(_) => "Faces",
+        PropertyValue::Axes(_) => "Axes",
+        PropertyValue::Ref(_) => "Ref",
+        PropertyValue::Enum(_) => "EnumItem",
+    }
+}
+
+fn eval_name(name: &str) -> Result<PropertyValue, String> {
+    let value = match name {
+        "math.pi" => PropertyValue::Number(std::f64::consts::PI),
+        "math.huge" => PropertyValue::Number(f64::INFINITY),
+        "Vector3.zero" => vector3(Vec3::ZERO),
+        "Vector3.one" => vector3(Vec3::ONE),
+        "Vector3.xAxis" => vector3(Vec3::X),
+        "Vector3.yAxis" => vector3(Vec3::Y),
+        "Vector3.zAxis" => vector3(Vec3::Z),
+        "Vector2.zero" => vector2(Vec2::ZERO),
+        "Vector2.one" => vector2(Vec2::ONE),
+        "Vector2.xAxis" => vector2(Vec2::X),
+        "Vector2.yAxis" => vector2(Vec2::Y),
+        "CFrame.identity" => PropertyValue::CFrame(CFrameWrapper::new(0.0, 0.0, 0.0)),
+        // Enum.PartType.Block
+        _ if name.starts_with("Enum.") && name.split('.').count() == 3 => {
+            PropertyValue::Enum(name.to_string())
+        }
+        // Bare enum items (`Material = Wood`); the validator checks them against the property.
+        _ if !name.contains('.') && database().is_enum_item(name) => {
+            PropertyValue::String(name.to_string())
+        }
+        _ if !name.contains('.') => return Err(format!("unknown identifier `{}`", name)),
+        _ => return Err(format!("unknown value `{}`", name)),
+    };
+    Ok(value)
+}
+
+fn vector3(v: Vec3) -> PropertyValue {
+    PropertyValue::Vector3(v.into())
+}
+
+fn vector2(v: Vec2) -> PropertyValue {
+    PropertyValue::Vector2(Vector2Wrapper { x: v.x, y: v.y })
+}
+
+// --- Arithmetic ---
+
+fn negate(value: PropertyValue) -> Result<PropertyValue, String> {
+    Ok(match value {
+        PropertyValue::Number(n) => PropertyValue::Number(-n),
+        PropertyValue::Vector3(v) => vector3(-Vec3::from(v)),
+        PropertyValue::Vector2(v) => vector2(-Vec2::new(v.x, v.y)),
+        PropertyValue::UDim(u) => PropertyValue::UDim(UDimWrapper {
+            scale: -u.scale,
+            offset: -u.offset,
+        }),
+        PropertyValue::UDim2(u) => udim2(-u.xs, -u.xo, -u.ys, -u.yo),
+        other => return Err(format!("cannot negate a {}", type_name(&other))),
+    })
+}
+
+fn binary(op: BinaryOp, lhs: PropertyValue, rhs: PropertyValue) -> Result<PropertyValue, String> {
+    use BinaryOp::*;
+    use PropertyValue as P;
+
+    let result = match (op, &lhs, &rhs) {
+        (_, P::Number(a), P::Number(b)) => P::Number(match op {
+            Add => a + b,
+            Sub => a - b,
+            Mul => a * b,
+            Div => a / b,
+        }),
+
+        (_, P::Vector3(a), P::Vector3(b)) => {
+            let (a, b) = (Vec3::from(a.clone()), Vec3::from(b.clone()));
+            vector3(match op {
+                Add => a + b,
+                Sub => a - b,
+                Mul => a * b,
+                Div => a / b,
+            })
+        }
+        (Mul, P::Vector3(v), P::Number(n)) | (Mul, P::Number(n), P::Vector3(v)) => {
+            vector3(Vec3::from(v.clone()) * *n as f32)
+        }
+        (Div, P::Vector3(v), P::Number(n)) => vector3(Vec3::from(v.clone()) / *n as f32),
+        (Div, P::Number(n), P::Vector3(v)) => vector3(*n as f32 / Vec3::from(v.clone())),
+
+        (_, P::Vector2(a), P::Vector2(b)) => {
+            let (a, b) = (Vec2::new(a.x, a.y), Vec2::new(b.x, b.y));
+            vector2(match op {
+                Add => a + b,
+                Sub => a - b,
+                Mul => a * b,
+                Div => a / b,
+            })
+        }
+        (Mul, P::Vector2(v), P::Number(n)) | (Mul, P::Number(n), P::Vector2(v)) => {
+            vector2(Vec2::new(v.x, v.y) * *n as f32)
+        }
+        (Div, P::Vector2(v), P::Number(n)) => vector2(Vec2::new(v.x, v.y) / *n as f32),
+
+        (Mul, P::CFrame(a), P::CFrame(b)) => P::CFrame(a.mul(b)),
+        (Mul, P::CFrame(cf), P::Vector3(v)) => {
+            vector3(cf.translation() + cf.rotation() * Vec3::from(v.clone()))
+        }
+        (Add | Sub, P::CFrame(cf), P::Vector3(v)) => {
+            let offset = Vec3::from(v.clone());
+            let offset = if op == Add { offset } else { -offset };
+            P::CFrame(CFrameWrapper::from_position_rotation(
+                cf.translation() + offset,
+                cf.rotation(),
+            ))
+        }
+
+        (Add | Sub, P::UDim(a), P::UDim(b)) => {
+            let sign = if op == Add { 1 } else { -1 };
+            P::UDim(UDimWrapper {
+                scale: a.scale + sign as f32 * b.scale,
+                offset: a.offset + sign * b.offset,
+            })
+        }
+        (Add | Sub, P::UDim2(a), P::UDim2(b)) => {
+            let sign = if op == Add { 1 } else { -1 };
+            udim2(
+                a.xs + sign as f32 * b.xs,
+                a.xo + sign * b.xo,
+                a.ys + sign as f32 * b.ys,
+                a.yo + sign * b.yo,
+            )
+        }
+
+        _ => {
+            return Err(format!(
+                "cannot apply `{}` to {} and {}",
+                op.symbol(),
+                type_name(&lhs),
+                type_name(&rhs)
+            ));
+        }
+    };
+    Ok(result)
+}
+
+// --- Constructors and functions ---
+
+fn eval_call(callee: &str, args: Vec<Value>) -> Result<Value, String> {
+    // Sequences built from a table of keypoints.
+    match (callee, args.as_slice()) {
+        ("NumberSequence.new", [Value::Table(items)]) => {
+            return number_sequence_from_keypoints(items).map(Value::Property);
+        }
+        ("ColorSequence.new", [Value::Table(items)]) => {
+            return color_sequence_from_keypoints(items).map(Value::Property);
+        }
+        _ => {}
+    }
+
+    let args = args.into_iter().map(property).collect::<Result<Vec<_>, _>>()?;
+    let result = match callee {
+        "NumberSequenceKeypoint.new" => build_number_keypoint(&args).map(Value::NumberKeypoint),
+        "ColorSequenceKeypoint.new" => build_color_keypoint(&args).map(Value::ColorKeypoint),
+        _ => {
+            let build = builder(callee).ok_or_else(|| format!("unknown function `{}`", callee))?;
+            build(&args).map(Value::Property)
+        }
+    };
+    result.ok_or_else(|| {
+        let types: Vec<&str> = args.iter().map(type_name).collect();
+        format!("invalid arguments to `{}({})`", callee, types.join(", "))
+    })
+}
+
+fn builder(callee: &str) -> Option<Builder> {
+    let build: Builder = match callee {
+        "Vector3.new" => |a| match a {
+            [] => Some(vector3(Vec3::ZERO)),
+            _ => numbers(a).map(|[x, y, z]| vector3(Vec3::new(x, y, z))),
+        },
+        "Vector2.new" => |a| match a {
+            [] => Some(vector2(Vec2::ZERO)),
+            _ => numbers(a).map(|[x, y]| vector2(Vec2::new(x, y))),
+        },
+        "CFrame.new" => build_cframe,
+        "CFrame.lookAt" => build_cframe_look_at,
+        // Angles are in radians, as in Luau.
+        "CFrame.Angles" | "CFrame.fromEulerAnglesXYZ" => |a| {
+            let [rx, ry, rz] = numbers(a)?;
+            Some(PropertyValue::CFrame(CFrameWrapper::from_euler_angles_xyz(rx, ry, rz)))
+        },
+        "CFrame.fromEulerAnglesYXZ" | "CFrame.fromOrientation" => |a| {
+            let [rx, ry, rz] = numbers(a)?;
+            Some(PropertyValue::CFrame(CFrameWrapper::from_euler_angles_yxz(rx, ry, rz)))
+        },
+        "Color3.new" => |a| {
+            let [r, g, b] = numbers(a)?;
+            Some(PropertyValue::Color3(Color3Wrapper::new(r, g, b)))
+        },
+        "Color3.fromRGB" => |a| {
+            let [r, g, b] = numbers(a)?;
+            Some(PropertyValue::Color3(Color3Wrapper::from_rgb(r, g, b)))
+        },
+        "Color3.fromHSV" => build_color3_from_hsv,
+        "Color3.fromHex" => build_color3_from_hex,
+        "UDim.new" => |a| {
+            let [scale, offset] = numbers(a)?;
+            Some(PropertyValue::UDim(UDimWrapper {
+                scale,
+                offset: offset as i32,
+            }))
+        },
+        "UDim2.new" => build_udim2,
+        "UDim2.fromScale" => |a| {
+            let [xs, ys] = numbers(a)?;
+            Some(udim2(xs, 0, ys, 0))
+        },
+        "UDim2.fromOffset" => |a| {
+            let [xo, yo] = numbers(a)?;
+            Some(udim2(0.0, xo as i32, 0.0, yo as i32))
+        },
+        "Rect.new" => build_rect,
+        "NumberRange.new" => build_number_range,
+        "NumberSequence.new" => build_number_sequence,
+        "ColorSequence.new" => build_color_sequence,
+        "BrickColor.new" => build_brick_color,
+        "Font.new" => build_font,
+        "Font.fromName" => build_font_from_name,
+        "Font.fromEnum" => build_font_from_enum,
+        "PhysicalProperties.new" => build_physical_properties,
+        "Faces.new" => build_faces,
+        "Axes.new" => build_axes,
+        "Ref" => build_ref,
+
+        "math.rad" => |a| numbers_f64(a).map(|[x]| PropertyValue::Number(x.to_radians())),
+        "math.deg" => |a| numbers_f64(a).map(|[x]| PropertyValue::Number(x.to_degrees())),
+        "math.sqrt" => |a| numbers_f64(a).map(|[x]| PropertyValue::Number(x.sqrt())),
+        "math.abs" => |a| numbers_f64(a).map(|[x]| PropertyValue::Number(x.abs())),
+        "math.floor" => |a| numbers_f64(a).map(|[x]| PropertyValue::Number(x.floor())),
+        "math.ceil" => |a| numbers_f64(a).map(|[x]| PropertyValue::Number(x.ceil())),
+        "math.sin" => |a| numbers_f64(a).map(|[x]| PropertyValue::Number(x.sin())),
+        "math.cos" => |a| numbers_f64(a).map(|[x]| PropertyValue::Number(x.cos())),
+        "math.tan" => |a| numbers_f64(a).map(|[x]| PropertyValue::Number(x.tan())),
+        "math.clamp" => |a| {
+            let [x, min, max] = numbers_f64(a)?;
+            (min <= max).then(|| PropertyValue::Number(x.clamp(min, max)))
+        },
+        "math.min" => |a| fold_numbers(a, f64::min),
+        "math.max" => |a| fold_numbers(a, f64::max),
+        _ => return None,
+    };
+    Some(build)
+}
+
+/// Exactly `N` number arguments, as datatype components.
+fn numbers<const N: usize>(args: &[PropertyValue]) -> Option<[f32; N]> {
+    numbers_f64(args).map(|values: [f64; N]| values.map(|n| n as f32))
+}
+
+/// Exactly `N` number arguments, at full precision for the math functions.
+fn numbers_f64<const N: usize>(args: &[PropertyValue]) -> Option<[f64; N]> {
+    if args.len() != N {
+        return None;
+    }
+    let mut out = [0.0; N];
+    for (slot, arg) in out.iter_mut().zip(args) {
+        *slot = num(arg)?;
+    }
+    Some(out)
+}
+
+fn fold_numbers(args: &[PropertyValue], f: fn(f64, f64) -> f64) -> Option<PropertyValue> {
+    let mut values = args.iter().map(num);
+    let first = values.next()??;
+    values
+        .try_fold(first, |acc, n| Some(f(acc, n?)))
+        .map(PropertyValue::Number)
+}
+
+fn num(value: &PropertyValue) -> Option<f64> {
+    match value {
+        PropertyValue::Number(n) => Some(*n),
+        _ => None,
+    }
+}
+
+fn vec3_arg(value: &PropertyValue) -> Option<Vec3> {
+    match value {
+        PropertyValue::Vector3(v) => Some(v.clone().into()),
+        _ => None,
+    }
+}
+
+/// Item name of an `Enum.<enum_type>.<Item>` value.
+fn enum_item<'v>(value: &'v PropertyValue, enum_type: &str) -> Option<&'v str> {
+    match value {
+        PropertyValue::Enum(e) => e
+            .strip_prefix("Enum.")?
+            .strip_prefix(enum_type)?
+            .strip_prefix('.'),
+        _ => None,
+    }
+}
+
+fn udim2(xs: f32, xo: i32, ys: f32, yo: i32) -> PropertyValue {
+    PropertyValue::UDim2(UDim2Wrapper { xs, xo, ys, yo })
+}
+
+fn build_cframe(args: &[PropertyValue]) -> Option<PropertyValue> {
+    let cframe = match args {
+        [] => CFrameWrapper::new(0.0, 0.0, 0.0),
+        // CFrame.new(pos) / CFrame.new(pos, lookAt)
+        [pos] => {
+            let pos = vec3_arg(pos)?;
+            CFrameWrapper::new(pos.x, pos.y, pos.z)
+        }
+        [pos, target] if vec3_arg(pos).is_some() => {
+            CFrameWrapper::look_at(vec3_arg(pos)?, vec3_arg(target)?, Vec3::Y)
+        }
+        // CFrame.new(x, y, z) / CFrame.new(x, y, z, R00, ..., R22)
+        _ if args.len() == 3 => {
+            let [x, y, z] = numbers(args)?;
+            CFrameWrapper::new(x, y, z)
+        }
+        _ => CFrameWrapper::from_components(numbers(args)?),
+    };
+    Some(PropertyValue::CFrame(cframe))
+}
+
+fn build_cframe_look_at(args: &[PropertyValue]) -> Option<PropertyValue> {
+    // CFrame.lookAt(at, target [, up])
+    let (at, target, up) = match args {
+        [at, target] => (vec3_arg(at)?, vec3_arg(target)?, Vec3::Y),
+        [at, target, up] => (vec3_arg(at)?, vec3_arg(target)?, vec3_arg(up)?),
+        _ => return None,
+    };
+    Some(PropertyValue::CFrame(CFrameWrapper::look_at(at, target, up)))
+}
+
+fn build_color3_from_hsv(args: &[PropertyValue]) -> Option<PropertyValue> {
+    let [h, s, v]: [f32; 3] = numbers(args)?;
+    let i = (h * 6.0).floor();
+    let f = h * 6.0 - i;
+    let (p, q, t) = (v * (1.0 - s), v * (1.0 - f * s), v * (1.0 - (1.0 - f) * s));
+    let (r, g, b) = match (i as i32).rem_euclid(6) {
+        0 => (v, t, p),
+        1 => (q, v, p),
+        2 => (p, v, t),
+        3 => (p, q, v),
+        4 => (t, p, v),
+        _ => (v, p, q),
+    };
+    Some(PropertyValue::Color3(Color3Wrapper::new(r, g, b)))
+}
+
+fn build_color3_from_hex(args: &[PropertyValue]) -> Option<PropertyValue> {
+    let [PropertyValue::String(hex)] = args else { return None };
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    if hex.len() != 6 {
+        return None;
+    }
+    let (r, g, b) = (channel(0)?, channel(2)?, channel(4)?);
+    Some(PropertyValue::Color3(Color3Wrapper::from_rgb(r as f32, g as f32, b as f32)))
+}
+
+fn build_udim2(args: &[PropertyValue]) -> Option<PropertyValue> {
+    match args {
+        [PropertyValue::UDim(x), PropertyValue::UDim(y)] => {
+            Some(udim2(x.scale, x.offset, y.scale, y.offset))
+        }
+        _ => {
+            let [xs, xo, ys, yo] = numbers(args)?;
+            Some(udim2(xs, xo as i32, ys, yo as i32))
+        }
+    }
+}
+
+fn build_rect(args: &[PropertyValue]) -> Option<PropertyValue> {
+    let (min, max) = match args {
+        [PropertyValue::Vector2(min), PropertyValue::Vector2(max)] => (min.clone(), max.clone()),
+        _ => {
+            let [x0, y0, x1, y1] = numbers(args)?;
+            (Vector2Wrapper { x: x0, y: y0 }, Vector2Wrapper { x: x1, y: y1 })
+        }
+    };
+    Some(PropertyValue::Rect(RectWrapper { min, max }))
+}
+
+fn build_number_range(args: &[PropertyValue]) -> Option<PropertyValue> {
+    let (min, max) = match args {
+        [_] => {
+            let [v] = numbers(args)?;
+            (v, v)
+        }
+        _ => {
+            let [min, max] = numbers(args)?;
+            (min, max)
+        }
+    };
+    (min <= max).then_some(PropertyValue::NumberRange(NumberRangeWrapper { min, max }))
+}
+
+fn build_number_sequence(args: &[PropertyValue]) -> Option<PropertyValue> {
+    let (start, end) = match args {
+        [_] => {
+            let [v] = numbers(args)?;
+            (v, v)
+        }
+        _ => {
+            let [start, end] = numbers(args)?;
+            (start, end)
+        }
+    };
+    Some(PropertyValue::NumberSequence(NumberSequenceWrapper {
+        keypoints: vec![
+            NumberSequenceKeypoint { time: 0.0, value: start, envelope: 0.0 },
+            NumberSequenceKeypoint { time: 1.0, value: end, envelope: 0.0 },
+        ],
+    }))
+}
+
+fn build_number_keypoint(args: &[PropertyValue]) -> Option<NumberSequenceKeypoint> {
+    let (time, value, envelope) = match args.len() {
+        2 => {
+            let [t, v] = numbers(args)?;
+            (t, v, 0.0)
+        }
+        _ => {
+            let [t, v, e] = numbers(args)?;
+            (t, v, e)
+        }
+    };
+    Some(NumberSequenceKeypoint { time, value, envelope })
+}
+
+fn build_color_sequence(args: &[PropertyValue]) -> Option<PropertyValue> {
+    let (start, end) = match args {
+        [PropertyValue::Color3(c)] => (c.clone(), c.clone()),
+        [PropertyValue::Color3(start), PropertyValue::Color3(end)] => (start.clone(), end.clone()),
+        _ => return None,
+    };
+    Some(PropertyValue::ColorSequence(ColorSequenceWrapper {
+        keypoints: vec![
+            ColorSequenceKeypoint { time: 0.0, color: start },
+            ColorSequenceKeypoint { time: 1.0, color: end },
+        ],
+    }))
+}
+
+fn build_color_keypoint(args: &[PropertyValue]) -> Option<ColorSequenceKeypoint> {
+    match args {
+        [t, PropertyValue::Color3(color)] => Some(ColorSequenceKeypoint {
+            time: num(t)? as f32,
+            color: color.clone(),
+        }),
+        _ => None,
+    }
+}
+
+/// Sequences need at least two keypoints, in order, starting at time 0 and ending at 1.
+fn check_keypoint_times(times: &[f32]) -> Result<(), String> {
+    let ordered = times.windows(2).all(|w| w[0] <= w[1]);
+    if times.len() >= 2 && ordered && times.first() == Some(&0.0) && times.last() == Some(&1.0) {
+        Ok(())
+    } else {
+        Err("sequence keypoints must be ordered by time, from 0 to 1".to_string())
+    }
+}
+
+fn number_sequence_from_keypoints(items: &[Value]) -> Result<PropertyValue, String> {
+    let keypoints = items
+        .iter()
+        .map(|item| match item {
+            Value::NumberKeypoint(k) => Ok(k.clone()),
+            _ => Err("NumberSequence.new expects NumberSequenceKeypoint values".to_string()),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    check_keypoint_times(&keypoints.iter().map(|k| k.time).collect::<Vec<_>>())?;
+    Ok(PropertyValue::NumberSequence(NumberSequenceWrapper { keypoints }))
+}
+
+fn color_sequence_from_keypoints(items: &[Value]) -> Result<PropertyValue, String> {
+    let keypoints = items
+        .iter()
+        .map(|item| match item {
+            Value::ColorKeypoint(k) => Ok(k.clone()),
+            _ => Err("ColorSequence.new expects ColorSequenceKeypoint values".to_string()),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    check_keypoint_times(&keypoints.iter().map(|k| k.time).collect::<Vec<_>>())?;
+    Ok(PropertyValue::ColorSequence(ColorSequenceWrapper { keypoints }))
+}
+
+fn build_brick_color(args: &[PropertyValue]) -> Option<PropertyValue> {
+    let color = match args {
+        [PropertyValue::String(name)] => BrickColorWrapper::from_name(name)?,
+        [PropertyValue::Number(n)] => BrickColorWrapper::from_number(*n as u32)?,
+        _ => return None,
+    };
+    Some(PropertyValue::BrickColor(color))
+}
+
+fn font(family: String, weight: Option<&PropertyValue>, style: Option<&PropertyValue>) -> Option<PropertyValue> {
+    let weight = match weight {
+        Some(w) => enum_item(w, "FontWeight")?,
+        None => "Regular",
+    };
+    let style = match style {
+        Some(s) => enum_item(s, "FontStyle")?,
+        None => "Normal",
+    };
+    Some(PropertyValue::Font(FontWrapper {
+        family,
+        weight: weight.to_string(),
+        style: style.to_string(),
+    }))
+}
+
+fn build_font(args: &[PropertyValue]) -> Option<PropertyValue> {
+    match args {
+        [PropertyValue::String(family), rest @ ..] if rest.len() <= 2 => {
+            font(family.clone(), rest.first(), rest.get(1))
+        }
+        _ => None,
+    }
+}
+
+fn build_font_from_name(args: &[PropertyValue]) -> Option<PropertyValue> {
+    match args {
+        [PropertyValue::String(name), rest @ ..] if rest.len() <= 2 => font(
+            format!("rbxasset://fonts/families/{}.json", name),
+            rest.first(),
+            rest.get(1),
+        ),
+        _ => None,
+    }
+}
+
+fn build_font_from_enum(args: &[PropertyValue]) -> Option<PropertyValue> {
+    match args {
+        [value] => font(
+            format!("rbxasset://fonts/families/{}.json", enum_item(value, "Font")?),
+            None,
+            None,
+        ),
+        _ => None,
+    }
+}
+
+// (density, friction, elasticity) for `PhysicalProperties.new(Enum.Material.X)`.
+fn material_physics(material: &str) -> Option<(f32, f32, f32)> {
+    Some(match material {
+        "Plastic" => (0.7, 0.3, 0.5),
+        "SmoothPlastic" => (0.7, 0.2, 0.5),
+        "Neon" => (0.7, 0.3, 0.2),
+        "Wood" | "WoodPlanks" => (0.35, 0.48, 0.2),
+        "Slate" | "Granite" => (2.691, 0.4, 0.2),
+        "Concrete" => (2.403, 0.7, 0.2),
+        "Brick" => (1.92, 0.8, 0.15),
+        "Marble" => (2.563, 0.2, 0.17),
+        "Metal" | "CorrodedMetal" | "DiamondPlate" => (7.85, 0.4, 0.25),
+        "Foil" => (2.7, 0.4, 0.25),
+        "Grass" => (0.9, 0.4, 0.1),
+        "Sand" => (1.6, 0.5, 0.05),
+        "Fabric" => (0.7, 0.35, 0.05),
+        "Ice" => (0.919, 0.02, 0.15),
+        "Glass" | "ForceField" => (2.4, 0.25, 0.2),
+        "Pebble" | "Cobblestone" => (2.401, 0.4, 0.17),
+        _ => return None,
+    })
+}
+
+fn build_physical_properties(args: &[PropertyValue]) -> Option<PropertyValue> {
+    let (density, friction, elasticity, friction_weight, elasticity_weight) = match args.len() {
+        1 => {
+            let (d, f, e) = material_physics(enum_item(&args[0], "Material")?)?;
+            (d, f, e, 1.0, 1.0)
+        }
+        3 => {
+            let [d, f, e] = numbers(args)?;
+            (d, f, e, 1.0, 1.0)
+        }
+        _ => {
+            let [d, f, e, fw, ew] = numbers(args)?;
+            (d, f, e, fw, ew)
+        }
+    };
+    Some(PropertyValue::PhysicalProperties(PhysicalPropertiesWrapper {
+        density,
+        friction,
+        elasticity,
+        friction_weight,
+        elasticity_weight,
+    }))
+}
+
+fn build_faces(args: &[PropertyValue]) -> Option<PropertyValue> {
+    let mut faces = FacesWrapper::default();
+    for arg in args {
+        match enum_item(arg, "NormalId")? {
+            "Top" => faces.top = true,
+            "Bottom" => faces.bottom = true,
+            "Left" => faces.left = true,
+            "Right" => faces.right = true,
+            "Back" => faces.back = true,
+            "Front" => faces.front = true,
+            _ => return None,
+        }
+    }
+    Some(PropertyValue::Faces(faces))
+}
+
+fn build_axes(args: &[PropertyValue]) -> Option<PropertyValue> {
+    let mut axes = AxesWrapper::default();
+    for arg in args {
+        // Axes.new accepts Enum.Axis items as well as the NormalIds on each axis.
+        let item = enum_item(arg, "Axis").or_else(|| enum_item(arg, "NormalId"))?;
+        match item {
+            "X" | "Right" | "Left" => axes.x = true,
+            "Y" | "Top" | "Bottom" => axes.y = true,
+            "Z" | "Back" | "Front" => axes.z = true,
+            _ => return None,
+        }
+    }
+    Some(PropertyValue::Axes(axes))
+}
+
+fn build_ref(args: &[PropertyValue]) -> Option<PropertyValue> {
+    match args {
+        [PropertyValue::String(path)] if !path.is_empty() => {
+            Some(PropertyValue::Ref(InstanceRef { instance: path.clone() }))
+        }
+        _ => None,
+    }
+}
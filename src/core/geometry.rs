@@ -0,0 +1,499 @@
+//! Part geometry shared by the renderer and the loader's bounds: which shape a BasePart
+//! has and the triangles that make it up, in part space, including mesh files that
+//! `MeshId = "file://..."` points at.
+use crate::core::datamodel::{Instance, PropertyValue};
+use anyhow::{anyhow, Context, Result};
+use glam::{Mat4, Vec3};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Triangles in part space, counter-clockwise seen from outside.
+#[derive(Clone)]
+pub struct Mesh {
+    pub vertices: Vec<Vec3>,
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    fn new() -> Self {
+        Mesh { vertices: Vec::new(), indices: Vec::new() }
+    }
+
+    /// Adds a convex face, its corners in counter-clockwise order seen from outside.
+    fn push_face(&mut self, corners: &[Vec3]) {
+        let base = self.vertices.len() as u32;
+        self.vertices.extend_from_slice(corners);
+        for i in 1..corners.len() as u32 - 1 {
+            self.indices.extend([base, base + i, base + i + 1]);
+        }
+    }
+
+    /// Moves and scales every vertex, keeping the triangles facing outwards when
+    /// `scale` mirrors the mesh.
+    fn scaled(mut self, center: Vec3, scale: Vec3, offset: Vec3) -> Mesh {
+        for v in &mut self.vertices {
+            *v = (*v - center) * scale + offset;
+        }
+        if scale.x * scale.y * scale.z < 0.0 {
+            for tri in self.indices.chunks_exact_mut(3) {
+                tri.swap(1, 2);
+            }
+        }
+        self
+    }
+
+    /// The smallest and largest corner around the vertices.
+    fn extent(&self) -> (Vec3, Vec3) {
+        self.vertices.iter().fold((Vec3::splat(f32::INFINITY), Vec3::splat(f32::NEG_INFINITY)), |(min, max), v| {
+            (min.min(*v), max.max(*v))
+        })
+    }
+
+    /// Adds an axis-aligned box.
+    fn push_box(&mut self, center: Vec3, size: Vec3) {
+        let base = self.vertices.len() as u32;
+        self.vertices.extend(box_corners(size).map(|c| center + c));
+        self.indices.extend(BOX_INDICES.iter().map(|i| base + i));
+    }
+}
+
+/// What a BasePart looks like.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PartShape {
+    Block,
+    Ball,
+    Cylinder,
+    Wedge,
+    CornerWedge,
+    /// A `TrussPart` lattice; `supports` adds the cross bars.
+    Truss { supports: bool },
+}
+
+impl PartShape {
+    /// The shape of a BasePart: from its class for `WedgePart`, `CornerWedgePart` and
+    /// `TrussPart`, from `Shape` for `Part` and its subclasses (`SpawnLocation`,
+    /// `Seat`), and a block of `Size` for the rest (`VehicleSeat`, `MeshPart`, ...).
+    pub fn of(instance: &Instance) -> PartShape {
+        if instance.is_a("WedgePart") {
+            return PartShape::Wedge;
+        }
+        if instance.is_a("CornerWedgePart") {
+            return PartShape::CornerWedge;
+        }
+        if instance.is_a("TrussPart") {
+            let style = match instance.property_or_default("Style") {
+                Some(PropertyValue::Enum(s)) => s,
+                _ => String::new(),
+            };
+            return PartShape::Truss { supports: !style.ends_with("NoSupports") };
+        }
+        match instance.property_or_default("Shape") {
+            // "Enum.PartType.Ball" -> "Ball"
+            Some(PropertyValue::Enum(s)) => match s.rsplit('.').next() {
+                Some("Ball") => PartShape::Ball,
+                Some("Cylinder") => PartShape::Cylinder,
+                Some("Wedge") => PartShape::Wedge,
+                Some("CornerWedge") => PartShape::CornerWedge,
+                _ => PartShape::Block,
+            },
+            _ => PartShape::Block,
+        }
+    }
+
+    pub fn mesh(self, size: Vec3) -> Mesh {
+        match self {
+            PartShape::Block => cube_mesh(size),
+            PartShape::Ball => sphere_mesh(size),
+            PartShape::Cylinder => cylinder_mesh(size),
+            PartShape::Wedge => wedge_mesh(size),
+            PartShape::CornerWedge => corner_wedge_mesh(size),
+            PartShape::Truss { supports } => truss_mesh(size, supports),
+        }
+    }
+}
+
+/// The mesh files that `MeshId = "file://..."` points at, each loaded once. Paths are
+/// relative to the project root.
+pub struct MeshFiles {
+    root: PathBuf,
+    loaded: HashMap<PathBuf, Result<Mesh, String>>,
+}
+
+impl MeshFiles {
+    pub fn new(root: &Path) -> Self {
+        MeshFiles { root: root.to_path_buf(), loaded: HashMap::new() }
+    }
+
+    /// The file mesh a BasePart is drawn with, in part space, or `None` when it keeps
+    /// its shape. A `MeshPart`'s mesh is stretched so its bounds fill `Size`; a
+    /// `SpecialMesh` child keeps the file's size, multiplied by its `Scale` and moved
+    /// by its `Offset`. Meshes are centred on their bounds, like Roblox does on upload.
+    pub fn part_mesh(&mut self, instance: &Instance, size: Vec3) -> Result<Option<Mesh>> {
+        if instance.is_a("MeshPart")
+            && let Some(path) = file_url(instance)
+        {
+            let mesh = self.load(path)?;
+            let (min, max) = mesh.extent();
+            let native = max - min;
+            let scale = Vec3::select(native.cmpgt(Vec3::splat(1e-6)), size / native, Vec3::ONE);
+            return Ok(Some(mesh.scaled((min + max) * 0.5, scale, Vec3::ZERO)));
+        }
+        let special = instance.children.iter().find(|c| c.is_a("SpecialMesh"));
+        if let Some(special) = special
+            && let Some(path) = file_url(special)
+        {
+            let vector = |name: &str, default: Vec3| match special.property_or_default(name) {
+                Some(PropertyValue::Vector3(v)) => v.into(),
+                _ => default,
+            };
+            let mesh = self.load(path)?;
+            let (min, max) = mesh.extent();
+            return Ok(Some(mesh.scaled((min + max) * 0.5, vector("Scale", Vec3::ONE), vector("Offset", Vec3::ZERO))));
+        }
+        Ok(None)
+    }
+
+    fn load(&mut self, path: &str) -> Result<Mesh> {
+        let path = self.root.join(path);
+        let mesh = self
+            .loaded
+            .entry(path.clone())
+            .or_insert_with(|| load_mesh_file(&path).map_err(|e| format!("{:#}", e)));
+        mesh.clone().map_err(|e| anyhow!(e))
+    }
+}
+
+/// The path in a `MeshId = "file://..."`; other asset URLs aren't loaded.
+fn file_url(instance: &Instance) -> Option<&str> {
+    match instance.properties.get("MeshId") {
+        Some(PropertyValue::String(id)) => id.strip_prefix("file://"),
+        _ => None,
+    }
+}
+
+/// Reads every triangle of an OBJ, glTF or GLB file.
+fn load_mesh_file(path: &Path) -> Result<Mesh> {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or_default().to_ascii_lowercase();
+    let mesh = match extension.as_str() {
+        "obj" => load_obj(path),
+        "gltf" | "glb" => load_gltf(path),
+        _ => Err(anyhow!("unsupported mesh format, expected .obj, .gltf or .glb")),
+    }
+    .with_context(|| format!("Failed to load mesh {}", path.display()))?;
+    if mesh.indices.is_empty() {
+        return Err(anyhow!("Mesh {} has no triangles", path.display()));
+    }
+    Ok(mesh)
+}
+
+fn load_obj(path: &Path) -> Result<Mesh> {
+    let options = tobj::LoadOptions { triangulate: true, single_index: true, ..Default::default() };
+    let (models, _) = tobj::load_obj(path, &options)?;
+    let mut mesh = Mesh::new();
+    for model in models {
+        let base = mesh.vertices.len() as u32;
+        mesh.vertices.extend(model.mesh.positions.chunks_exact(3).map(|p| Vec3::new(p[0], p[1], p[2])));
+        mesh.indices.extend(model.mesh.indices.iter().map(|i| base + i));
+    }
+    Ok(mesh)
+}
+
+/// The triangles of the default scene, with node transforms applied.
+fn load_gltf(path: &Path) -> Result<Mesh> {
+    let gltf = gltf::Gltf::open(path)?;
+    let buffers = gltf::import_buffers(&gltf.document, path.parent(), gltf.blob.clone())?;
+    let scene = gltf.document.default_scene().or_else(|| gltf.document.scenes().next());
+
+    fn visit(node: gltf::Node, parent: Mat4, buffers: &[gltf::buffer::Data], mesh: &mut Mesh) {
+        let transform = parent * Mat4::from_cols_array_2d(&node.transform().matrix());
+        for primitive in node.mesh().iter().flat_map(|m| m.primitives()) {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                continue;
+            }
+            let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data[..]));
+            let Some(positions) = reader.read_positions() else { continue };
+            let base = mesh.vertices.len() as u32;
+            mesh.vertices.extend(positions.map(|p| transform.transform_point3(Vec3::from(p))));
+            let count = mesh.vertices.len() as u32 - base;
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..count).collect(),
+            };
+            for tri in indices.chunks_exact(3) {
+                // A mirroring node flips the winding
+                let tri = if transform.determinant() < 0.0 { [tri[0], tri[2], tri[1]] } else { [tri[0], tri[1], tri[2]] };
+                mesh.indices.extend(tri.map(|i| base + i));
+            }
+        }
+        for child in node.children() {
+            visit(child, transform, buffers, mesh);
+        }
+    }
+
+    let mut mesh = Mesh::new();
+    for node in scene.iter().flat_map(|s| s.nodes()) {
+        visit(node, Mat4::IDENTITY, &buffers, &mut mesh);
+    }
+    Ok(mesh)
+}
+
+pub fn box_corners(size: Vec3) -> [Vec3; 8] {
+    let half = size * 0.5;
+    [
+        Vec3::new(-half.x, -half.y, -half.z), // 0
+        Vec3::new(half.x, -half.y, -half.z),  // 1
+        Vec3::new(-half.x, half.y, -half.z),  // 2
+        Vec3::new(half.x, half.y, -half.z),   // 3
+        Vec3::new(-half.x, -half.y, half.z),  // 4
+        Vec3::new(half.x, -half.y, half.z),   // 5
+        Vec3::new(-half.x, half.y, half.z),   // 6
+        Vec3::new(half.x, half.y, half.z),    // 7
+    ]
+}
+
+const BOX_INDICES: [u32; 36] = [
+    4, 5, 7, 4, 7, 6, // Front
+    1, 0, 2, 1, 2, 3, // Back
+    0, 4, 6, 0, 6, 2, // Left
+    5, 1, 3, 5, 3, 7, // Right
+    6, 7, 3, 6, 3, 2, // Top
+    0, 1, 5, 0, 5, 4, // Bottom
+];
+
+pub fn cube_mesh(size: Vec3) -> Mesh {
+    Mesh { vertices: box_corners(size).to_vec(), indices: BOX_INDICES.to_vec() }
+}
+
+/// Roblox's wedge: full bottom and back (+Z) faces, sloping down towards the front (-Z).
+fn wedge_mesh(size: Vec3) -> Mesh {
+    let [b0, b1, _, _, b4, b5, t6, t7] = box_corners(size);
+    let mut mesh = Mesh::new();
+    mesh.push_face(&[b0, b1, b5, b4]); // Bottom
+    mesh.push_face(&[b4, b5, t7, t6]); // Back
+    mesh.push_face(&[b1, b0, t6, t7]); // Slope
+    mesh.push_face(&[b0, b4, t6]); // Left
+    mesh.push_face(&[b5, b1, t7]); // Right
+    mesh
+}
+
+/// Roblox's corner wedge: a square bottom rising to a point above the right-front
+/// (+X, -Z) corner, with vertical right and front faces.
+fn corner_wedge_mesh(size: Vec3) -> Mesh {
+    let [b0, b1, _, apex, b4, b5, _, _] = box_corners(size);
+    let mut mesh = Mesh::new();
+    mesh.push_face(&[b0, b1, b5, b4]); // Bottom
+    mesh.push_face(&[b1, b0, apex]); // Front
+    mesh.push_face(&[b5, b1, apex]); // Right
+    mesh.push_face(&[b0, b4, apex]); // Left slope
+    mesh.push_face(&[b4, b5, apex]); // Back slope
+    mesh
+}
+
+/// A square lattice along the part's longest axis: four corner posts and, with
+/// `supports`, a ring of cross bars every 2 studs.
+fn truss_mesh(size: Vec3, supports: bool) -> Mesh {
+    // Build along Y, then turn Y onto the longest axis
+    let (along, rotation) = if size.x > size.y && size.x >= size.z {
+        (Vec3::new(size.y, size.x, size.z), Mat4::from_rotation_z(-std::f32::consts::FRAC_PI_2))
+    } else if size.z > size.y {
+        (Vec3::new(size.x, size.z, size.y), Mat4::from_rotation_x(std::f32::consts::FRAC_PI_2))
+    } else {
+        (size, Mat4::IDENTITY)
+    };
+    let beam = (along.x.min(along.z) * 0.15).max(0.05);
+    let half = (along - Vec3::splat(beam)) * 0.5;
+
+    let mut mesh = Mesh::new();
+    for (x, z) in [(-half.x, -half.z), (half.x, -half.z), (-half.x, half.z), (half.x, half.z)] {
+        mesh.push_box(Vec3::new(x, 0.0, z), Vec3::new(beam, along.y, beam));
+    }
+    if supports {
+        let rungs = (along.y / 2.0).round().max(1.0) as u32;
+        for i in 0..=rungs {
+            let y = -half.y + (2.0 * half.y) * i as f32 / rungs as f32;
+            mesh.push_box(Vec3::new(0.0, y, -half.z), Vec3::new(along.x, beam, beam));
+            mesh.push_box(Vec3::new(0.0, y, half.z), Vec3::new(along.x, beam, beam));
+            mesh.push_box(Vec3::new(-half.x, y, 0.0), Vec3::new(beam, beam, along.z));
+            mesh.push_box(Vec3::new(half.x, y, 0.0), Vec3::new(beam, beam, along.z));
+        }
+    }
+    for v in &mut mesh.vertices {
+        *v = rotation.transform_point3(*v);
+    }
+    mesh
+}
+
+pub fn sphere_mesh(size: Vec3) -> Mesh {
+    // Generate sphere mesh (icosphere or UV sphere). Using simple UV sphere.
+    let lat_segments = 12;
+    let lon_segments = 12;
+    let radius = size.min_element() * 0.5; // Approximation: use min dimension as radius
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    for lat in 0..=lat_segments {
+        let theta = lat as f32 * std::f32::consts::PI / lat_segments as f32;
+        let sin_theta = theta.sin();
+        let cos_theta = theta.cos();
+
+        for lon in 0..=lon_segments {
+            let phi = lon as f32 * 2.0 * std::f32::consts::PI / lon_segments as f32;
+            let sin_phi = phi.sin();
+            let cos_phi = phi.cos();
+
+            let x = cos_phi * sin_theta;
+            let y = cos_theta;
+            let z = sin_phi * sin_theta;
+
+            vertices.push(Vec3::new(x * radius, y * radius, z * radius));
+        }
+    }
+
+    for lat in 0..lat_segments {
+        for lon in 0..lon_segments {
+            let first = (lat * (lon_segments + 1)) + lon;
+            let second = first + lon_segments + 1;
+
+            indices.push(first);
+            indices.push(first + 1);
+            indices.push(second);
+
+            indices.push(second);
+            indices.push(first + 1);
+            indices.push(second + 1);
+        }
+    }
+
+    Mesh { vertices, indices }
+}
+
+pub fn cylinder_mesh(size: Vec3) -> Mesh {
+    let segments = 16;
+    let radius = size.y.min(size.z) * 0.5; // Roblox cylinders lie along X
+    let half_height = size.x * 0.5;
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+
+    // Side vertices
+    for i in 0..=segments {
+        let theta = i as f32 * 2.0 * std::f32::consts::PI / segments as f32;
+        let x = theta.cos() * radius;
+        let z = theta.sin() * radius;
+
+        vertices.push(Vec3::new(x, -half_height, z)); // Bottom ring
+        vertices.push(Vec3::new(x, half_height, z));  // Top ring
+    }
+
+    // Center points for caps
+    let bottom_center_idx = vertices.len() as u32;
+    vertices.push(Vec3::new(0.0, -half_height, 0.0));
+    let top_center_idx = vertices.len() as u32;
+    vertices.push(Vec3::new(0.0, half_height, 0.0));
+
+    for i in 0..segments {
+        let base = i * 2;
+        let next_base = (i + 1) * 2;
+
+        // Side quads (2 tris)
+        indices.push(base);
+        indices.push(base + 1);
+        indices.push(next_base);
+
+        indices.push(next_base);
+        indices.push(base + 1);
+        indices.push(next_base + 1);
+
+        // Bottom Cap
+        indices.push(bottom_center_idx);
+        indices.push(base);
+        indices.push(next_base);
+
+        // Top Cap
+        indices.push(top_center_idx);
+        indices.push(next_base + 1);
+        indices.push(base + 1);
+    }
+
+    // Generated along Y; Roblox's Cylinder is aligned with the X axis, so rotate it
+    // 90 degrees around Z.
+    let rotation = Mat4::from_rotation_z(90.0_f32.to_radians());
+    let vertices: Vec<Vec3> = vertices.iter().map(|v| rotation.transform_point3(*v)).collect();
+
+    Mesh { vertices, indices }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::test_support::TempProject;
+
+    #[test]
+    fn test_meshes_are_closed_and_face_outwards() {
+        let size = Vec3::new(4.0, 2.0, 6.0);
+        let shapes = [
+            PartShape::Block,
+            PartShape::Ball,
+            PartShape::Cylinder,
+            PartShape::Wedge,
+            PartShape::CornerWedge,
+            PartShape::Truss { supports: false },
+            PartShape::Truss { supports: true },
+        ];
+        for shape in shapes {
+            let mesh = shape.mesh(size);
+            let centroid = mesh.vertices.iter().sum::<Vec3>() / mesh.vertices.len() as f32;
+            let mut volume = 0.0;
+            for tri in mesh.indices.chunks_exact(3) {
+                let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| mesh.vertices[i as usize]);
+                // Signed volume of the tetrahedron to the centroid: positive when outwards
+                volume += (a - centroid).dot((b - centroid).cross(c - centroid)) / 6.0;
+                for v in [a, b, c] {
+                    assert!(v.abs().cmple(size * 0.5 + 1e-4).all(), "{:?} leaves its box", shape);
+                }
+            }
+            assert!(volume > 0.0, "{:?} is inside out", shape);
+        }
+
+        // A 4x2x6 wedge is half its box; a corner wedge a third
+        let volume = |mesh: Mesh| -> f32 {
+            mesh.indices
+                .chunks_exact(3)
+                .map(|t| {
+                    let [a, b, c] = [t[0], t[1], t[2]].map(|i| mesh.vertices[i as usize]);
+                    a.dot(b.cross(c)) / 6.0
+                })
+                .sum()
+        };
+        assert!((volume(wedge_mesh(size)) - 24.0).abs() < 1e-3);
+        assert!((volume(corner_wedge_mesh(size)) - 16.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_file_meshes_fit_size_or_follow_special_mesh() {
+        // A pyramid 2 wide and 4 tall, standing on the origin
+        let obj = "v -1 0 -1\nv 1 0 -1\nv 1 0 1\nv -1 0 1\nv 0 4 0\nf 1 2 3 4\nf 4 3 5\nf 3 2 5\nf 2 1 5\nf 1 4 5\n";
+        let root = TempProject::new("meshes", &[("assets/spike.obj", obj)]);
+        let mut meshes = MeshFiles::new(&root);
+        let extent = |mesh: Option<Mesh>| mesh.unwrap().extent();
+
+        let mut part = Instance::new("Spike", "MeshPart", "spike");
+        part.properties.insert("MeshId".to_string(), PropertyValue::String("file://assets/spike.obj".to_string()));
+        let size = Vec3::new(4.0, 2.0, 1.0);
+        assert_eq!(extent(meshes.part_mesh(&part, size).unwrap()), (-size * 0.5, size * 0.5));
+
+        let mut part = Instance::new("Spike", "Part", "spike");
+        let mut special = Instance::new("Mesh", "SpecialMesh", "spike/Mesh");
+        special.properties.insert("MeshId".to_string(), PropertyValue::String("file://assets/spike.obj".to_string()));
+        special.properties.insert("Scale".to_string(), PropertyValue::Vector3(Vec3::new(2.0, 1.0, 1.0).into()));
+        special.properties.insert("Offset".to_string(), PropertyValue::Vector3(Vec3::new(0.0, 3.0, 0.0).into()));
+        part.children.push(special);
+        assert_eq!(extent(meshes.part_mesh(&part, size).unwrap()), (Vec3::new(-2.0, 1.0, -1.0), Vec3::new(2.0, 5.0, 1.0)));
+
+        part.children[0].properties.insert("MeshId".to_string(), PropertyValue::String("file://assets/gone.obj".to_string()));
+        assert!(meshes.part_mesh(&part, size).is_err());
+        part.children.clear();
+        assert!(meshes.part_mesh(&part, size).unwrap().is_none());
+    }
+}
@@ -1,14 +1,45 @@
-use crate::core::datamodel::{Instance, PropertyValue};
-use crate::core::parser::parse_instance_dsl;
+use crate::core::analysis::Diagnostic;
+use crate::core::datamodel::{Instance, PropertyValue, to_model_space};
+use crate::core::eval::Scope;
+use crate::core::geometry::{self, MeshFiles};
+use crate::core::reflection;
+use crate::core::parser::{DslError, DslNode, parse_defs, parse_instance_dsl};
+use crate::core::rbxl;
+use crate::core::rojo::{self, ProjectNode};
+use crate::core::validator::{unknown_property, validate_property};
 use anyhow::Result;
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-pub fn load_project(root_path: &Path) -> Result<Instance> {
+/// The loaded DataModel plus any problems found in the project files while loading it.
+pub struct LoadedProject {
+    pub datamodel: Instance,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Every Luau source file that became a script, for luau-analyze.
+    pub script_files: Vec<PathBuf>,
+}
+
+/// State shared by every DSL file in a project load.
+struct LoadContext<'a> {
+    root: &'a Path,
+    /// Variables from `.ludock/defs`, in scope for every DSL file.
+    defs: Scope,
+    /// `Extends` targets already loaded, so each template is parsed (and reported) once.
+    templates: HashMap<PathBuf, DslNode>,
+    diagnostics: Vec<Diagnostic>,
+    script_files: Vec<PathBuf>,
+    /// `MeshId = "file://..."` meshes, for bounds.
+    meshes: MeshFiles,
+}
+
+pub fn load_project(root_path: &Path) -> Result<LoadedProject> {
+    // A Rojo project file takes precedence over the `game/` layout.
+    let project_path = root_path.join("default.project.json");
     let game_path = root_path.join("game");
-    if !game_path.exists() {
+    if !project_path.is_file() && !game_path.exists() {
         return Err(anyhow::anyhow!(
-            "Game directory not found at {:?}",
+            "Game directory not found at {:?} (and no default.project.json)",
             game_path
         ));
     }
@@ -26,168 +57,744 @@ pub fn load_project(root_path: &Path) -> Result<Instance> {
     // But since `Instance` owns its children, we might need a recursive approach or a multi-pass approach.
     // Recursive approach is cleaner for directory walking.
 
-    datamodel.children = load_directory(&game_path, "game")?;
+    let mut ctx = LoadContext {
+        root: root_path,
+        defs: Scope::new(),
+        templates: HashMap::new(),
+        diagnostics: Vec::new(),
+        script_files: Vec::new(),
+        meshes: MeshFiles::new(root_path),
+    };
+    let defs_path = root_path.join(".ludock/defs");
+    if defs_path.is_file() {
+        let (defs, errors) = parse_defs(&fs::read_to_string(&defs_path)?);
+        ctx.report(&defs_path, errors);
+        ctx.defs = defs;
+    }
+    datamodel.children = if project_path.is_file() {
+        load_rojo_project(&project_path, &mut ctx)?
+    } else {
+        load_children(&game_path, "game", &mut ctx)?
+    };
 
     // Post-process derived data (AABB, Center) for Root
     // Actually, AABB for root should encompass all children.
     // We can do a recursive pass or compute it during load.
     // Let's compute it during load by returning the AABB from load_directory or computing it here.
     // For simplicity, we can do a bottom-up pass now.
-    compute_derived_data(&mut datamodel);
+    // Pivots need `PrimaryPart` as a full path.
+    resolve_references(&mut datamodel);
+    compute_derived_data(&mut datamodel, Mat4::IDENTITY, &mut ctx);
 
-    Ok(datamodel)
+    Ok(LoadedProject {
+        datamodel,
+        diagnostics: ctx.diagnostics,
+        script_files: ctx.script_files,
+    })
 }
 
-fn load_directory(dir: &Path, parent_full_path: &str) -> Result<Vec<Instance>> {
-    let mut children = Vec::new();
+impl LoadContext<'_> {
+    fn report(&mut self, path: &Path, errors: Vec<DslError>) {
+        let file = path.to_string_lossy().replace('\\', "/");
+        for error in errors {
+            self.diagnostics.push(Diagnostic {
+                file: file.clone(),
+                line: error.line,
+                column: Some(error.column),
+                end_column: Some(error.end_column),
+                message: error.message,
+                severity: "error".to_string(),
+                code: Some("DslError".to_string()),
+                hint: None,
+            });
+        }
+    }
 
-    // Read directory entries and collect them into a vector
-    let mut entries = fs::read_dir(dir)?
-        .collect::<Result<Vec<_>, std::io::Error>>()?;
+    fn issue(&mut self, path: &Path, line: usize, code: &str, message: String, hint: Option<String>) {
+        self.diagnostics.push(Diagnostic {
+            file: path.to_string_lossy().replace('\\', "/"),
+            line,
+            column: None,
+            end_column: None,
+            message,
+            severity: "error".to_string(),
+            code: Some(code.to_string()),
+            hint,
+        });
+    }
 
-    // Sort entries by file name to ensure deterministic order
-    entries.sort_by_key(|entry| entry.file_name());
+    /// Reports a mesh file that failed to load, at the `MeshId` line of the file the
+    /// part comes from (or at the part's path when it has no file of its own).
+    fn mesh_issue(&mut self, part: &Instance, message: String) {
+        let file = part.source.clone().unwrap_or_else(|| PathBuf::from(&part.full_path));
+        let line = fs::read_to_string(&file)
+            .ok()
+            .and_then(|content| content.lines().position(|l| l.contains("MeshId")))
+            .map_or(1, |i| i + 1);
+        let hint = Some("`file://` paths are relative to the project root; use .obj, .gltf or .glb".to_string());
+        self.issue(&file, line, "MeshError", message, hint);
+    }
 
-    for entry in entries {
-        let path = entry.path();
-        let name = path.file_stem().unwrap().to_string_lossy().to_string();
-        // Create a stable path identifier string for UUID generation
-        // using forward slashes for cross-platform consistency
-        let path_str = path.to_string_lossy().replace('\\', "/");
-
-        if path.is_dir() {
-            let class_name = infer_class_from_name(&name, true);
-            let clean_name = clean_name(&name);
-            let current_full_path = format!("{}/{}", parent_full_path, clean_name);
-
-            let mut instance = Instance::new(&clean_name, &class_name, &path_str);
-            instance.full_path = current_full_path.clone();
-            instance.children = load_directory(&path, &current_full_path)?;
-            children.push(instance);
-        } else {
-            // It's a file
-            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    fn load_script(&mut self, path: &Path, name: &str, class_name: &str, seed: &str) -> Result<Instance> {
+        let mut instance = Instance::new(name, class_name, seed);
+        instance
+            .properties
+            .insert("Source".to_string(), PropertyValue::String(fs::read_to_string(path)?));
+        self.script_files.push(path.to_path_buf());
+        Ok(instance)
+    }
 
-            // Skip non-instance files (like .DS_Store)
-            if ext == "json" || ext.is_empty() {
-                continue;
+    /// Applies an `init.meta.json` / `Name.meta.json`. `className` only changes plain
+    /// folders, as in Rojo.
+    fn apply_meta(&mut self, instance: &mut Instance, path: &Path) -> Result<()> {
+        let content = fs::read_to_string(path)?;
+        let meta: rojo::MetaFile = match serde_json::from_str(&content) {
+            Ok(meta) => meta,
+            Err(e) => {
+                self.issue(path, e.line(), "ProjectError", format!("Invalid meta file: {}", e), None);
+                return Ok(());
             }
+        };
+        if let Some(class_name) = meta.class_name
+            && instance.class_name == "Folder"
+        {
+            instance.class_name = class_name;
+        }
+        self.apply_json_properties(instance, meta.properties.iter(), path, &content);
+        Ok(())
+    }
 
-            // Handle Scripts
-            if name.ends_with(".server") && ext == "lua" {
-                let clean_name = name.trim_end_matches(".server").to_string();
-                let mut inst = Instance::new(&clean_name, "Script", &path_str);
-                inst.properties.insert(
-                    "Source".to_string(),
-                    PropertyValue::String(fs::read_to_string(&path)?),
-                );
-                children.push(inst);
+    /// Converts and validates Rojo JSON properties. Diagnostics point at the line that
+    /// names the property.
+    fn apply_json_properties<'v>(
+        &mut self,
+        instance: &mut Instance,
+        properties: impl Iterator<Item = (&'v String, &'v serde_json::Value)>,
+        path: &Path,
+        content: &str,
+    ) {
+        for (key, json) in properties {
+            let line = content
+                .lines()
+                .position(|l| l.contains(&format!("\"{}\"", key)))
+                .map_or(1, |i| i + 1);
+            if let Some(issue) = unknown_property(&instance.class_name, key) {
+                self.issue(path, line, issue.code, issue.message, issue.hint);
                 continue;
             }
-            if name.ends_with(".local") && ext == "lua" {
-                let clean_name = name.trim_end_matches(".local").to_string();
-                let mut inst = Instance::new(&clean_name, "LocalScript", &path_str);
-                inst.properties.insert(
-                    "Source".to_string(),
-                    PropertyValue::String(fs::read_to_string(&path)?),
-                );
-                children.push(inst);
-                continue;
+            match rojo::property_value(&instance.class_name, key, json) {
+                Ok(value) => {
+                    if let Some(issue) = validate_property(&instance.class_name, key, &value) {
+                        self.issue(path, line, issue.code, issue.message, issue.hint);
+                    }
+                    if key == "Name"
+                        && let PropertyValue::String(name) = &value
+                    {
+                        instance.name = name.clone();
+                    }
+                    instance.properties.insert(key.clone(), value);
+                }
+                Err(e) => self.issue(
+                    path,
+                    line,
+                    "ProjectError",
+                    format!("Invalid value for property `{}`: {}", key, e),
+                    None,
+                ),
+            }
+        }
+    }
+
+    /// Loads a `Name.model.json` instance tree.
+    fn load_json_model(&mut self, path: &Path, name: &str, seed: &str) -> Result<Instance> {
+        let content = fs::read_to_string(path)?;
+        match serde_json::from_str::<rojo::JsonModel>(&content) {
+            Ok(model) => Ok(self.build_json_model(model, name, seed, path, &content)),
+            Err(e) => {
+                self.issue(path, e.line(), "ProjectError", format!("Invalid model file: {}", e), None);
+                Ok(Instance::new(name, "Folder", seed))
             }
-            if name.ends_with(".module") && ext == "lua" {
-                let clean_name = name.trim_end_matches(".module").to_string();
-                let mut inst = Instance::new(&clean_name, "ModuleScript", &path_str);
-                inst.properties.insert(
-                    "Source".to_string(),
-                    PropertyValue::String(fs::read_to_string(&path)?),
-                );
-                children.push(inst);
+        }
+    }
+
+    fn build_json_model(
+        &mut self,
+        model: rojo::JsonModel,
+        name: &str,
+        seed: &str,
+        path: &Path,
+        content: &str,
+    ) -> Instance {
+        let mut instance = Instance::new(name, &model.class_name, seed);
+        self.apply_json_properties(&mut instance, model.properties.iter(), path, content);
+
+        let mut seen: HashMap<String, usize> = HashMap::new();
+        for child in model.children {
+            let child_name = child.name.clone().unwrap_or_else(|| child.class_name.clone());
+            let occurrence = seen.entry(child_name.clone()).or_insert(0);
+            let child_seed = match *occurrence {
+                0 => format!("{}/{}", seed, child_name),
+                n => format!("{}/{}#{}", seed, child_name, n),
+            };
+            *occurrence += 1;
+            let child_instance = self.build_json_model(child, &child_name, &child_seed, path, content);
+            instance.children.push(child_instance);
+        }
+        instance
+    }
+
+    /// A `.rbxm`/`.rbxmx` model file holding a single instance, which is named after
+    /// the file like every other entry.
+    fn load_roblox_model(&mut self, path: &Path, name: &str, parent_full_path: &str, seed: &str) -> Result<Instance> {
+        let content = fs::read(path)?;
+        let mut items = match rbxl::read_roblox_file(&content, parent_full_path) {
+            Ok(items) => items,
+            Err(e) => {
+                self.issue(path, 1, "ProjectError", format!("Invalid model file: {}", e), None);
+                return Ok(Instance::new(name, "Folder", seed));
+            }
+        };
+        if items.len() != 1 {
+            let message = format!("Model files must contain exactly one instance, found {}", items.len());
+            self.issue(path, 1, "ProjectError", message, None);
+            return Ok(Instance::new(name, "Folder", seed));
+        }
+        let mut instance = items.remove(0);
+        // Stored in world space; treated as placed in its parent's space.
+        to_model_space(&mut instance, Mat4::IDENTITY);
+        let old_path = instance.full_path.clone();
+        instance.name = name.to_string();
+        assign_full_paths(&mut instance, parent_full_path);
+        let new_path = instance.full_path.clone();
+        rebase_references(&mut instance, &old_path, &new_path);
+        Ok(instance)
+    }
+
+    /// One node of a project tree: the file or directory at `$path` if there is one,
+    /// otherwise a new instance of `$className` (services are inferred from the name).
+    fn load_project_node(
+        &mut self,
+        name: &str,
+        node: &ProjectNode,
+        project: &ProjectFile,
+        parent_full_path: &str,
+        seed: &str,
+    ) -> Result<Instance> {
+        let mut instance = None;
+        if let Some(node_path) = &node.path {
+            let path = project.dir.join(&node_path.path);
+            if path.file_name().is_some_and(|f| f.to_string_lossy().ends_with(".project.json")) {
+                // Nested projects contribute their tree root.
+                let content = fs::read_to_string(&path)?;
+                let nested = rojo::parse_project(&content)
+                    .map_err(|e| anyhow::anyhow!("Invalid project file {:?}: {}", path, e))?;
+                let nested_project = ProjectFile {
+                    path: &path,
+                    dir: path.parent().unwrap_or(Path::new(".")),
+                    content: &content,
+                };
+                instance = Some(self.load_project_node(name, &nested.tree, &nested_project, parent_full_path, seed)?);
+            } else if path.exists() {
+                instance = load_entry(&path, parent_full_path, self)?;
+            } else if !node_path.optional {
+                let message = format!("`$path` of `{}` not found: {}", name, node_path.path);
+                self.issue(project.path, 1, "ProjectError", message, None);
+            }
+        }
+
+        let mut instance = instance.unwrap_or_else(|| {
+            let class_name = node.class_name.clone().unwrap_or_else(|| infer_class_from_name(name, true));
+            Instance::new(name, &class_name, seed)
+        });
+        // The node name replaces the file name; Refs inside loaded models follow it.
+        let old_path = std::mem::take(&mut instance.full_path);
+        instance.name = name.to_string();
+        assign_full_paths(&mut instance, parent_full_path);
+        if !old_path.is_empty() {
+            let new_path = instance.full_path.clone();
+            rebase_references(&mut instance, &old_path, &new_path);
+        }
+        if let Some(class_name) = &node.class_name {
+            instance.class_name = class_name.clone();
+        }
+
+        let properties = node.properties.iter().map(|(k, v)| (k, v));
+        self.apply_json_properties(&mut instance, properties, project.path, project.content);
+
+        for (child_name, child) in &node.children {
+            let child_seed = format!("{}/{}", seed, child_name);
+            let child_instance = self.load_project_node(child_name, child, project, &instance.full_path, &child_seed)?;
+            instance.children.push(child_instance);
+        }
+        Ok(instance)
+    }
+
+    /// Parses a DSL file and resolves its `Extends` chain. `stack` holds the files
+    /// currently being resolved, to detect cycles.
+    fn load_dsl_file(&mut self, path: &Path, stack: &mut Vec<PathBuf>) -> Result<DslNode> {
+        let content = fs::read_to_string(path)?;
+        let (node, errors) = parse_instance_dsl(&content, &self.defs);
+        self.report(path, errors);
+        let own = node.clone();
+
+        stack.push(path.canonicalize()?);
+        let node = self.resolve_extends(node, path, stack);
+        stack.pop();
+
+        let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        self.validate(path, &own, &node, &map_extension_to_class(ext));
+        Ok(node)
+    }
+
+    /// Checks the properties written in this file (`own`) against the class the
+    /// instance ends up with, which may come from a template (`merged`).
+    fn validate(&mut self, path: &Path, own: &DslNode, merged: &DslNode, default_class: &str) {
+        let class_name = match merged.properties.get("ClassName") {
+            Some(PropertyValue::String(class_name)) => class_name.as_str(),
+            _ => default_class,
+        };
+
+        let mut keys: Vec<&String> = own.properties.keys().collect();
+        keys.sort_by_key(|k| own.spans.get(*k).map(|s| (s.line, s.column)));
+        for key in keys {
+            let Some(issue) = validate_property(class_name, key, &own.properties[key]) else {
                 continue;
+            };
+            let span = own.spans.get(key);
+            self.diagnostics.push(Diagnostic {
+                file: path.to_string_lossy().replace('\\', "/"),
+                line: span.map_or(1, |s| s.line),
+                column: span.map(|s| s.column),
+                end_column: span.map(|s| s.end_column),
+                message: issue.message,
+                severity: "error".to_string(),
+                code: Some(issue.code.to_string()),
+                hint: issue.hint,
+            });
+        }
+
+        for child in &own.children {
+            let merged_child = merged.children.iter().find(|c| c.name == child.name);
+            // Child blocks without a ClassName are Folders.
+            self.validate(path, &child.node, merged_child.map_or(&child.node, |c| &c.node), "Folder");
+        }
+    }
+
+    /// Merges `node` (and its child blocks) over the templates they extend.
+    /// Template paths are relative to the project root.
+    fn resolve_extends(&mut self, mut node: DslNode, file: &Path, stack: &mut Vec<PathBuf>) -> DslNode {
+        node.children = std::mem::take(&mut node.children)
+            .into_iter()
+            .map(|mut child| {
+                child.node = self.resolve_extends(child.node, file, stack);
+                child
+            })
+            .collect();
+
+        let Some(extends) = node.extends.take() else {
+            return node;
+        };
+        let error = |message: String| DslError {
+            line: extends.span.line,
+            column: extends.span.column,
+            end_column: extends.span.end_column,
+            message,
+        };
+
+        let target = match self.root.join(&extends.path).canonicalize() {
+            Ok(target) if target.is_file() => target,
+            _ => {
+                let message = format!("Extends target `{}` not found", extends.path);
+                self.report(file, vec![error(message)]);
+                return node;
             }
+        };
+        if let Some(start) = stack.iter().position(|p| *p == target) {
+            let chain: Vec<String> = stack[start..]
+                .iter()
+                .chain(std::iter::once(&target))
+                .map(|p| self.display_path(p))
+                .collect();
+            self.report(file, vec![error(format!("Extends cycle: {}", chain.join(" -> ")))]);
+            return node;
+        }
 
-            // Handle Declarative Instances
-            let class_name = map_extension_to_class(ext);
-            let mut instance = Instance::new(&name, &class_name, &path_str);
-
-            // Parse DSL
-            let content = fs::read_to_string(&path)?;
-            match parse_instance_dsl(&content) {
-                Ok((_, props)) => {
-                    // Override properties from file
-                    for (k, v) in props {
-                        // If file specifies ClassName, use it
-                        if k == "ClassName" 
-                            && let PropertyValue::String(ref s) = v 
-                        {
-                            instance.class_name = s.clone();
-                        }
-                        // If file specifies Name, use it
-                        if k == "Name" 
-                            && let PropertyValue::String(ref s) = v 
-                        {
-                            instance.name = s.clone();
-                        }
-                        instance.properties.insert(k, v);
-                    }
+        let base = match self.templates.get(&target) {
+            Some(base) => base.clone(),
+            None => match self.load_dsl_file(&target, stack) {
+                Ok(base) => {
+                    self.templates.insert(target, base.clone());
+                    base
                 }
                 Err(e) => {
-                    eprintln!("Failed to parse DSL for {:?}: {}", path, e);
+                    let message = format!("Failed to load Extends target `{}`: {}", extends.path, e);
+                    self.report(file, vec![error(message)]);
+                    return node;
                 }
+            },
+        };
+        merge_dsl_node(base, node)
+    }
+
+    fn display_path(&self, path: &Path) -> String {
+        let root = self.root.canonicalize().unwrap_or_else(|_| self.root.to_path_buf());
+        path.strip_prefix(&root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+}
+
+/// Overlays `derived` on `base`: properties override, and child blocks with the same
+/// name are merged recursively; other children are appended after the template's.
+fn merge_dsl_node(mut base: DslNode, derived: DslNode) -> DslNode {
+    base.properties.extend(derived.properties);
+    for child in derived.children {
+        match base.children.iter_mut().find(|c| c.name == child.name) {
+            Some(existing) => {
+                let merged = merge_dsl_node(std::mem::take(&mut existing.node), child.node);
+                existing.node = merged;
             }
-            // Update full path after Name might have changed? 
-            // Name is property. But instance.name is struct field.
-            // We updated instance.name above if found.
-            instance.full_path = format!("{}/{}", parent_full_path, instance.name);
+            None => base.children.push(child),
+        }
+    }
+    base
+}
+
+fn load_children(
+    dir: &Path,
+    parent_full_path: &str,
+    ctx: &mut LoadContext,
+) -> Result<Vec<Instance>> {
+    let mut children = Vec::new();
 
+    // Read directory entries and collect them into a vector
+    let mut entries = fs::read_dir(dir)?
+        .collect::<Result<Vec<_>, std::io::Error>>()?;
+
+    // Sort entries by file name to ensure deterministic order
+    entries.sort_by_key(|entry| entry.file_name());
+
+    let mut meta_files = Vec::new();
+    for entry in entries {
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().to_string();
+
+        // `init.*` files describe the directory itself; see `load_directory`.
+        if path.is_file() && is_init_file(&file_name) {
+            continue;
+        }
+        if let Some(name) = file_name.strip_suffix(".meta.json") {
+            meta_files.push((name.to_string(), path));
+            continue;
+        }
+        if let Some(instance) = load_entry(&path, parent_full_path, ctx)? {
             children.push(instance);
         }
     }
 
+    // `Name.meta.json` adds properties to the sibling instance called `Name`.
+    for (name, path) in meta_files {
+        match children.iter_mut().find(|c| c.name == name) {
+            Some(instance) => ctx.apply_meta(instance, &path)?,
+            None => ctx.issue(&path, 1, "ProjectError", format!("No instance named `{}` for this meta file", name), None),
+        }
+    }
+
     Ok(children)
 }
 
+/// Loads a directory as one instance with its other entries as children. An `init`
+/// file decides what the directory is: `init.server.lua` makes it a Script,
+/// `init.model` a Model described by that file, and so on. Without one it is a Folder
+/// or the service it is named after.
+fn load_directory(dir: &Path, parent_full_path: &str, ctx: &mut LoadContext) -> Result<Instance> {
+    let stem = dir.file_stem().unwrap().to_string_lossy().to_string();
+    let name = clean_name(&stem);
+    let seed = dir.to_string_lossy().replace('\\', "/");
+
+    let mut init_files: Vec<PathBuf> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    init_files.retain(|path| {
+        let file_name = path.file_name().unwrap().to_string_lossy();
+        path.is_file() && file_name != "init.meta.json" && is_init_file(&file_name)
+    });
+    init_files.sort();
+    if init_files.len() > 1 {
+        let names: Vec<String> = init_files.iter().map(|p| p.file_name().unwrap().to_string_lossy().to_string()).collect();
+        let message = format!("Multiple init files ({}); using {}", names.join(", "), names[0]);
+        // Reported on each ignored file.
+        for ignored in &init_files[1..] {
+            ctx.issue(ignored, 1, "ProjectError", message.clone(), None);
+        }
+    }
+
+    let mut instance = match init_files.first() {
+        Some(init_path) => {
+            let file_name = init_path.file_name().unwrap().to_string_lossy().to_string();
+            match script_class(&file_name) {
+                Some((_, class_name)) => ctx.load_script(init_path, &name, class_name, &seed)?,
+                None => {
+                    let ext = init_path.extension().and_then(|e| e.to_str()).unwrap_or("");
+                    let mut instance = Instance::new(&name, &map_extension_to_class(ext), &seed);
+                    let node = ctx.load_dsl_file(init_path, &mut Vec::new())?;
+                    apply_dsl_node(&mut instance, node, &seed);
+                    instance
+                }
+            }
+        }
+        None => Instance::new(&name, &infer_class_from_name(&stem, true), &seed),
+    };
+    assign_full_paths(&mut instance, parent_full_path);
+    let children = load_children(dir, &instance.full_path, ctx)?;
+    instance.children.extend(children);
+
+    let meta_path = dir.join("init.meta.json");
+    if meta_path.is_file() {
+        ctx.apply_meta(&mut instance, &meta_path)?;
+    }
+    Ok(instance)
+}
+
+/// Loads one file or directory as an instance. Returns `None` for files that don't
+/// describe instances.
+fn load_entry(path: &Path, parent_full_path: &str, ctx: &mut LoadContext) -> Result<Option<Instance>> {
+    let instance = if path.is_dir() {
+        Some(load_directory(path, parent_full_path, ctx)?)
+    } else {
+        load_file(path, parent_full_path, ctx)?
+    };
+    Ok(instance.map(|mut instance| {
+        instance.source = Some(path.to_path_buf());
+        instance
+    }))
+}
+
+fn load_file(path: &Path, parent_full_path: &str, ctx: &mut LoadContext) -> Result<Option<Instance>> {
+    let file_name = path.file_name().unwrap().to_string_lossy().to_string();
+    let name = path.file_stem().unwrap().to_string_lossy().to_string();
+    // Create a stable path identifier string for UUID generation
+    // using forward slashes for cross-platform consistency
+    let path_str = path.to_string_lossy().replace('\\', "/");
+
+    let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+
+    // Handle Scripts (`.server.lua`, `.client.luau`, plain `.lua` modules, ...)
+    if let Some((script_name, class_name)) = script_class(&file_name) {
+        let mut instance = ctx.load_script(path, script_name, class_name, &path_str)?;
+        assign_full_paths(&mut instance, parent_full_path);
+        return Ok(Some(instance));
+    }
+    if let Some(model_name) = file_name.strip_suffix(".model.json") {
+        let mut instance = ctx.load_json_model(path, model_name, &path_str)?;
+        assign_full_paths(&mut instance, parent_full_path);
+        return Ok(Some(instance));
+    }
+    if ext == "rbxm" || ext == "rbxmx" {
+        return ctx.load_roblox_model(path, &name, parent_full_path, &path_str).map(Some);
+    }
+    if ext == "txt" {
+        let mut instance = Instance::new(&name, "StringValue", &path_str);
+        instance
+            .properties
+            .insert("Value".to_string(), PropertyValue::String(fs::read_to_string(path)?));
+        assign_full_paths(&mut instance, parent_full_path);
+        return Ok(Some(instance));
+    }
+
+    // Skip non-instance files (like .DS_Store, READMEs and project files)
+    if !DSL_EXTENSIONS.contains(&ext) {
+        return Ok(None);
+    }
+
+    // Handle Declarative Instances
+    let class_name = map_extension_to_class(ext);
+    let mut instance = Instance::new(&name, &class_name, &path_str);
+
+    // Parse DSL
+    let node = ctx.load_dsl_file(path, &mut Vec::new())?;
+    apply_dsl_node(&mut instance, node, &path_str);
+    // Name may have been overridden by the DSL, so resolve paths afterwards.
+    assign_full_paths(&mut instance, parent_full_path);
+
+    Ok(Some(instance))
+}
+
+/// The instance name and class of a Luau source file, following Rojo's naming:
+/// `Name.server.lua` is a Script, `Name.client.lua` (or `Name.local.lua`) a
+/// LocalScript, and `Name.lua` / `Name.module.lua` a ModuleScript. `.luau` works too.
+fn script_class(file_name: &str) -> Option<(&str, &'static str)> {
+    let stem = file_name
+        .strip_suffix(".luau")
+        .or_else(|| file_name.strip_suffix(".lua"))?;
+    Some(if let Some(name) = stem.strip_suffix(".server") {
+        (name, "Script")
+    } else if let Some(name) = stem.strip_suffix(".client").or_else(|| stem.strip_suffix(".local")) {
+        (name, "LocalScript")
+    } else {
+        (stem.strip_suffix(".module").unwrap_or(stem), "ModuleScript")
+    })
+}
+
+fn is_init_file(file_name: &str) -> bool {
+    file_name == "init.meta.json"
+        || matches!(script_class(file_name), Some(("init", _)))
+        || file_name.strip_prefix("init.").is_some_and(|ext| DSL_EXTENSIONS.contains(&ext))
+}
+
+/// Builds the instances of a Rojo `default.project.json` tree. `$path` entries are loaded
+/// like directories under `game/`, relative to the project file.
+fn load_rojo_project(project_path: &Path, ctx: &mut LoadContext) -> Result<Vec<Instance>> {
+    let content = fs::read_to_string(project_path)?;
+    let project = rojo::parse_project(&content)
+        .map_err(|e| anyhow::anyhow!("Invalid project file {:?}: {}", project_path, e))?;
+    if project.tree.class_name.as_deref() != Some("DataModel") {
+        return Err(anyhow::anyhow!(
+            "Project `{}` is not a place: the root of its tree must have \"$className\": \"DataModel\"",
+            project.name
+        ));
+    }
+
+    let file = ProjectFile {
+        path: project_path,
+        dir: project_path.parent().unwrap_or(Path::new(".")),
+        content: &content,
+    };
+    let seed = project_path.to_string_lossy().replace('\\', "/");
+    let mut children = Vec::new();
+    for (name, node) in &project.tree.children {
+        let child_seed = format!("{}/{}", seed, name);
+        children.push(ctx.load_project_node(name, node, &file, "game", &child_seed)?);
+    }
+    Ok(children)
+}
+
+/// A project file being loaded: `$path`s are relative to `dir`, and `content` is kept
+/// to point property diagnostics at their line.
+struct ProjectFile<'a> {
+    path: &'a Path,
+    dir: &'a Path,
+    content: &'a str,
+}
+
+/// Copies parsed properties onto `instance` and builds its `Child` blocks.
+/// Child UUIDs are seeded from the parent's seed plus the child name, so they stay
+/// deterministic; repeated names get an occurrence suffix to keep ids unique.
+fn apply_dsl_node(instance: &mut Instance, node: DslNode, seed: &str) {
+    for (k, v) in node.properties {
+        // If file specifies ClassName, use it
+        if k == "ClassName"
+            && let PropertyValue::String(ref s) = v
+        {
+            instance.class_name = s.clone();
+        }
+        // If file specifies Name, use it
+        if k == "Name"
+            && let PropertyValue::String(ref s) = v
+        {
+            instance.name = s.clone();
+        }
+        instance.properties.insert(k, v);
+    }
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for child in node.children {
+        let occurrence = seen.entry(child.name.clone()).or_insert(0);
+        let child_seed = if *occurrence == 0 {
+            format!("{}/{}", seed, child.name)
+        } else {
+            format!("{}/{}#{}", seed, child.name, occurrence)
+        };
+        *occurrence += 1;
+
+        // Child blocks without a ClassName behave like plain directories.
+        let mut child_instance = Instance::new(&child.name, "Folder", &child_seed);
+        apply_dsl_node(&mut child_instance, child.node, &child_seed);
+        instance.children.push(child_instance);
+    }
+}
+
+fn assign_full_paths(instance: &mut Instance, parent_full_path: &str) {
+    instance.full_path = format!("{}/{}", parent_full_path, instance.name);
+    let full_path = instance.full_path.clone();
+    for child in &mut instance.children {
+        assign_full_paths(child, &full_path);
+    }
+}
+
+/// Rewrites relative `Ref(...)` paths into absolute `full_path`s. Paths are relative to
+/// the instance holding the property; `..` steps up to the parent.
+fn resolve_references(instance: &mut Instance) {
+    for value in instance.properties.values_mut() {
+        if let PropertyValue::Ref(r) = value
+            && r.instance != "game"
+            && !r.instance.starts_with("game/")
+        {
+            let mut segments: Vec<&str> = instance.full_path.split('/').collect();
+            for segment in r.instance.split('/') {
+                match segment {
+                    "" | "." => {}
+                    ".." => {
+                        segments.pop();
+                    }
+                    name => segments.push(name),
+                }
+            }
+            r.instance = segments.join("/");
+        }
+    }
+    for child in &mut instance.children {
+        resolve_references(child);
+    }
+}
+
+/// Points `Ref`s at `old_path` (or below it) to `new_path` after a rename.
+fn rebase_references(instance: &mut Instance, old_path: &str, new_path: &str) {
+    for value in instance.properties.values_mut() {
+        if let PropertyValue::Ref(r) = value
+            && let Some(rest) = r.instance.strip_prefix(old_path)
+            && (rest.is_empty() || rest.starts_with('/'))
+        {
+            r.instance = format!("{}{}", new_path, rest);
+        }
+    }
+    for child in &mut instance.children {
+        rebase_references(child, old_path, new_path);
+    }
+}
+
 use crate::core::datamodel::{AabbWrapper, Vec3Wrapper};
 use glam::{Mat4, Vec3};
 
-fn compute_derived_data(instance: &mut Instance) -> Option<AabbWrapper> {
+/// Bounds and centers in world space. `parent` is the transform of the space the
+/// instance is placed in (see `Instance::children_transform`). Mesh files that fail to
+/// load are reported.
+fn compute_derived_data(instance: &mut Instance, parent: Mat4, ctx: &mut LoadContext) -> Option<AabbWrapper> {
     // 1. Compute bounds for self if BasePart
     let mut my_min = Vec3::splat(f32::INFINITY);
     let mut my_max = Vec3::splat(f32::NEG_INFINITY);
     let mut has_bounds = false;
 
-    if instance.class_name == "Part" || instance.class_name == "BasePart" {
+    if instance.is_a("BasePart") {
          // Get Size and CFrame
-         let size = if let Some(PropertyValue::Vector3(v)) = instance.properties.get("Size") {
-             Vec3::new(v.x, v.y, v.z)
-         } else {
-             Vec3::new(4.0, 1.0, 2.0)
+         let size = match instance.property_or_default("Size") {
+             Some(PropertyValue::Vector3(v)) => Vec3::new(v.x, v.y, v.z),
+             _ => Vec3::ZERO,
          };
          
-         let transform = if let Some(PropertyValue::CFrame(cf)) = instance.properties.get("CFrame") {
-             let c = &cf.components;
-             let col0 = glam::Vec4::new(c[3], c[6], c[9], 0.0);
-             let col1 = glam::Vec4::new(c[4], c[7], c[10], 0.0);
-             let col2 = glam::Vec4::new(c[5], c[8], c[11], 0.0);
-             let col3 = glam::Vec4::new(c[0], c[1], c[2], 1.0);
-             Mat4::from_cols(col0, col1, col2, col3)
-         } else if let Some(PropertyValue::Vector3(pos)) = instance.properties.get("Position") {
-             Mat4::from_translation(Vec3::new(pos.x, pos.y, pos.z))
-         } else {
-             Mat4::IDENTITY
-         };
-
-        let half_size = size * 0.5;
-        let corners = [
-            Vec3::new(-half_size.x, -half_size.y, -half_size.z),
-            Vec3::new(half_size.x, -half_size.y, -half_size.z),
-            Vec3::new(-half_size.x, half_size.y, -half_size.z),
-            Vec3::new(half_size.x, half_size.y, -half_size.z),
-            Vec3::new(-half_size.x, -half_size.y, half_size.z),
-            Vec3::new(half_size.x, -half_size.y, half_size.z),
-            Vec3::new(-half_size.x, half_size.y, half_size.z),
-            Vec3::new(half_size.x, half_size.y, half_size.z),
-        ];
+         let transform = parent * instance.part_transform();
 
+        // The part's box, whatever its shape, like Roblox's GetBoundingBox; file meshes
+        // are measured by their vertices
+        let corners = match ctx.meshes.part_mesh(instance, size) {
+            Ok(Some(mesh)) => mesh.vertices,
+            Ok(None) => geometry::box_corners(size).to_vec(),
+            Err(e) => {
+                ctx.mesh_issue(instance, format!("{:#}", e));
+                geometry::box_corners(size).to_vec()
+            }
+        };
         for corner in corners {
             let p = transform.transform_point3(corner);
             my_min = my_min.min(p);
@@ -197,8 +804,9 @@ fn compute_derived_data(instance: &mut Instance) -> Option<AabbWrapper> {
     }
 
     // 2. Aggregate children bounds
+    let children_transform = instance.children_transform(parent);
     for child in &mut instance.children {
-        if let Some(child_bounds) = compute_derived_data(child) {
+        if let Some(child_bounds) = compute_derived_data(child, children_transform, ctx) {
             let min = Vec3::new(child_bounds.min.x, child_bounds.min.y, child_bounds.min.z);
             let max = Vec3::new(child_bounds.max.x, child_bounds.max.y, child_bounds.max.z);
             my_min = my_min.min(min);
@@ -233,24 +841,15 @@ fn infer_class_from_name(name: &str, is_dir: bool) -> String {
     }
 
     // Known Services (Direct children of game usually)
-    match name {
-        "Workspace" => "Workspace".to_string(),
-        "Lighting" => "Lighting".to_string(),
-        "ReplicatedStorage" => "ReplicatedStorage".to_string(),
-        "ReplicatedFirst" => "ReplicatedFirst".to_string(),
-        "ServerScriptService" => "ServerScriptService".to_string(),
-        "ServerStorage" => "ServerStorage".to_string(),
-        "StarterGui" => "StarterGui".to_string(),
-        "StarterPack" => "StarterPack".to_string(),
-        "StarterPlayer" => "StarterPlayer".to_string(),
-        "SoundService" => "SoundService".to_string(),
-        _ => {
-            if is_dir {
-                "Folder".to_string()
-            } else {
-                "Unknown".to_string()
-            }
-        }
+    let is_service = reflection::database()
+        .class(name)
+        .is_some_and(|class| class.tags.iter().any(|t| t == "Service"));
+    if is_service {
+        name.to_string()
+    } else if is_dir {
+        "Folder".to_string()
+    } else {
+        "Unknown".to_string()
     }
 }
 
@@ -267,7 +866,13 @@ fn clean_name(name: &str) -> String {
     name.to_string()
 }
 
-fn map_extension_to_class(ext: &str) -> String {
+/// File extensions loaded as instance DSL files.
+pub const DSL_EXTENSIONS: &[&str] = &[
+    "basepart", "part", "model", "folder", "script", "localscript", "modulescript", "gui",
+    "frame", "button", "label",
+];
+
+pub fn map_extension_to_class(ext: &str) -> String {
     match ext {
         "basepart" => "Part".to_string(), // Default BasePart is Part
         "part" => "Part".to_string(),
@@ -283,3 +888,171 @@ fn map_extension_to_class(ext: &str) -> String {
         _ => "Folder".to_string(), // Default for directory, or fallback
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::datamodel::InstanceRef;
+    use crate::core::test_support::TempProject;
+
+    #[test]
+    fn test_text_files_are_string_values_with_paths() {
+        let root = TempProject::new(
+            "txt",
+            &[("game/ReplicatedStorage/Strings/Greeting.txt", "Hello")],
+        );
+        let loaded = load_project(&root).unwrap();
+        let storage = loaded.datamodel.children.iter().find(|c| c.name == "ReplicatedStorage").unwrap();
+        let greeting = &storage.children[0].children[0];
+        assert_eq!(greeting.class_name, "StringValue");
+        assert_eq!(greeting.full_path, "game/ReplicatedStorage/Strings/Greeting");
+    }
+
+    #[test]
+    fn test_init_files_make_the_directory() {
+        let root = TempProject::new(
+            "init",
+            &[
+                ("game/ServerScriptService/Main/init.server.luau", "print('main')"),
+                ("game/ServerScriptService/Main/Helper.luau", "return {}"),
+                ("game/ReplicatedStorage/Util/init.luau", "return {}"),
+                ("game/ReplicatedStorage/Settings/init.meta.json", r#"{"className": "Configuration"}"#),
+                ("game/ReplicatedStorage/Both/init.luau", "return {}"),
+                ("game/ReplicatedStorage/Both/init.server.luau", "print('both')"),
+                ("game/ReplicatedStorage/Both/init.part", "Anchored = true"),
+            ],
+        );
+        let loaded = load_project(&root).unwrap();
+        let find = |path: &str| loaded.datamodel.find(path).unwrap();
+
+        let main = find("game/ServerScriptService/Main");
+        assert_eq!(main.class_name, "Script");
+        assert_eq!(main.properties["Source"], PropertyValue::String("print('main')".to_string()));
+        assert_eq!(main.children[0].full_path, "game/ServerScriptService/Main/Helper");
+        assert_eq!(find("game/ReplicatedStorage/Util").class_name, "ModuleScript");
+        assert_eq!(find("game/ReplicatedStorage/Settings").class_name, "Configuration");
+
+        // Several init files: the first by name wins and each of the others is reported
+        assert_eq!(find("game/ReplicatedStorage/Both").class_name, "ModuleScript");
+        assert_eq!(loaded.diagnostics.len(), 2);
+        assert!(loaded.diagnostics[0].file.ends_with("Both/init.part"));
+        assert!(loaded.diagnostics[1].file.ends_with("Both/init.server.luau"));
+        for diagnostic in &loaded.diagnostics {
+            assert_eq!(
+                diagnostic.message,
+                "Multiple init files (init.luau, init.part, init.server.luau); using init.luau"
+            );
+        }
+    }
+
+    #[test]
+    fn test_only_dsl_files_are_parsed() {
+        let root = TempProject::new(
+            "extensions",
+            &[("game/Workspace/Wall.part", "Anchored = true"), ("game/Workspace/README.md", "# Notes\nNot = a { DSL")],
+        );
+        let loaded = load_project(&root).unwrap();
+        let workspace = loaded.datamodel.children.iter().find(|c| c.name == "Workspace").unwrap();
+        assert_eq!(workspace.children.iter().map(|c| c.name.as_str()).collect::<Vec<_>>(), ["Wall"]);
+        assert!(loaded.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn test_extends_merges_templates() {
+        let root = TempProject::new(
+            "extends",
+            &[
+                (
+                    "templates/Base.basepart",
+                    "Anchored = true\nTransparency = 0.5\nChild \"Trim\" {\n    ClassName = Part\n    Transparency = 0.5\n}",
+                ),
+                ("templates/Wall.basepart", "Extends = \"templates/Base.basepart\"\nTransparency = 0.25"),
+                ("templates/A.basepart", "Extends = \"templates/B.basepart\""),
+                ("templates/B.basepart", "Extends = \"templates/A.basepart\""),
+                (
+                    "game/Workspace/Wall.part",
+                    "Extends = \"templates/Wall.basepart\"\nChild \"Trim\" {\n    Transparency = 0\n}\nChild \"Sign\" {\n    ClassName = Part\n}",
+                ),
+                ("game/Workspace/Ghost.part", "Extends = \"templates/Missing.basepart\"\nAnchored = true"),
+                ("game/Workspace/Loop.part", "Extends = \"templates/A.basepart\""),
+            ],
+        );
+        let loaded = load_project(&root).unwrap();
+        let find = |path: &str| loaded.datamodel.find(path).unwrap();
+
+        // The file overrides its template, which overrides its own base; child blocks merge by name
+        let wall = find("game/Workspace/Wall");
+        assert_eq!(wall.properties["Anchored"], PropertyValue::Bool(true));
+        assert_eq!(wall.properties["Transparency"], PropertyValue::Number(0.25));
+        let children: Vec<_> = wall.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(children, ["Trim", "Sign"]);
+        assert_eq!(wall.children[0].class_name, "Part");
+        assert_eq!(wall.children[0].properties["Transparency"], PropertyValue::Number(0.0));
+
+        // A missing template is reported and the file loads without it
+        assert_eq!(find("game/Workspace/Ghost").properties["Anchored"], PropertyValue::Bool(true));
+        let mut messages: Vec<_> = loaded.diagnostics.iter().map(|d| (d.file.as_str(), d.message.as_str())).collect();
+        messages.sort();
+        assert_eq!(messages.len(), 2, "{:?}", messages);
+        assert!(messages[0].0.ends_with("game/Workspace/Ghost.part"));
+        assert_eq!(messages[0].1, "Extends target `templates/Missing.basepart` not found");
+
+        // So is a cycle, on the file that closes it
+        assert!(messages[1].0.ends_with("templates/B.basepart"));
+        assert_eq!(messages[1].1, "Extends cycle: templates/A.basepart -> templates/B.basepart -> templates/A.basepart");
+        assert!(loaded.datamodel.find("game/Workspace/Loop").is_some());
+    }
+
+    #[test]
+    fn test_project_models_keep_their_refs() {
+        let mut door = Instance::new("Door", "Model", "door");
+        door.full_path = "game/Workspace/Door".to_string();
+        let mut frame = Instance::new("Frame", "Part", "door/Frame");
+        frame.full_path = "game/Workspace/Door/Frame".to_string();
+        door.properties.insert(
+            "PrimaryPart".to_string(),
+            PropertyValue::Ref(InstanceRef { instance: frame.full_path.clone() }),
+        );
+        door.children.push(frame);
+        let model = crate::core::rbxlx::write_xml(&[&door]);
+
+        let project = r#"{"name": "P", "tree": {"$className": "DataModel",
+            "ReplicatedStorage": {"Assets": {"$className": "Folder", "Gate": {"$path": "models/Door.rbxmx"}}}}}"#;
+        let root = TempProject::new("project-refs", &[("default.project.json", project), ("models/Door.rbxmx", &model)]);
+        let loaded = load_project(&root).unwrap();
+        assert!(loaded.diagnostics.is_empty());
+
+        // Named and placed by the project node, with the Ref following it
+        let gate = loaded.datamodel.find("game/ReplicatedStorage/Assets/Gate").unwrap();
+        assert_eq!(gate.children[0].full_path, "game/ReplicatedStorage/Assets/Gate/Frame");
+        assert_eq!(
+            gate.properties["PrimaryPart"],
+            PropertyValue::Ref(InstanceRef { instance: "game/ReplicatedStorage/Assets/Gate/Frame".to_string() })
+        );
+    }
+
+    #[test]
+    fn test_world_pivot_moves_the_model() {
+        let door = |pivot: &str| {
+            format!(
+                "{}\nChild \"Frame\" {{\n    ClassName = Part\n    CFrame = CFrame.new(0, 4, 0)\n    Size = Vector3.new(2, 8, 1)\n}}",
+                pivot
+            )
+        };
+        let root = TempProject::new(
+            "world-pivot",
+            &[
+                ("game/Workspace/Door.model", &door("")),
+                ("game/Workspace/Moved.model", &door("WorldPivot = CFrame.new(10, 0, 0) * CFrame.Angles(0, math.pi / 2, 0)")),
+            ],
+        );
+        let loaded = load_project(&root).unwrap();
+        let center = |path: &str| Vec3::from(loaded.datamodel.find(path).unwrap().center.clone().unwrap());
+
+        // Parts are authored relative to the model; WorldPivot carries them along
+        assert!(center("game/Workspace/Door/Frame").abs_diff_eq(Vec3::new(0.0, 4.0, 0.0), 1e-5));
+        assert!(center("game/Workspace/Moved/Frame").abs_diff_eq(Vec3::new(10.0, 4.0, 0.0), 1e-5));
+        let bounds = loaded.datamodel.find("game/Workspace/Moved").unwrap().world_bounds.clone().unwrap();
+        assert!(Vec3::from(bounds.max).abs_diff_eq(Vec3::new(10.5, 8.0, 1.0), 1e-5));
+    }
+}
@@ -5,3 +5,13 @@ pub mod analysis;
 pub mod renderer;
 pub mod errors;
 pub mod diff;
+pub mod eval;
+pub mod geometry;
+pub mod printer;
+pub mod reflection;
+pub mod rojo;
+pub mod rbxl;
+pub mod rbxlx;
+pub mod validator;
+#[cfg(test)]
+pub mod test_support;
@@ -1,26 +1,149 @@
-use crate::core::datamodel::{
-    CFrameWrapper, Color3Wrapper, PropertyValue, UDim2Wrapper, Vec3Wrapper,
-};
+use crate::core::datamodel::PropertyValue;
+use crate::core::eval::{Scope, evaluate};
 use nom::{
     IResult, Parser,
     branch::alt,
-    bytes::complete::{tag, take_while},
-    character::complete::{alpha1, char, digit1, multispace0},
+    bytes::complete::{tag, take_till, take_while, take_while1},
+    character::complete::{alpha1, char, digit1, multispace1, one_of},
     combinator::{map, map_res, opt, recognize},
-    multi::many0,
-    sequence::{delimited, pair},
+    error::ErrorKind,
+    multi::{many0, many0_count, separated_list0},
+    sequence::{delimited, pair, preceded, terminated},
 };
 use std::collections::HashMap;
 
+/// A parsed instance block: its own properties plus any nested `Child` blocks.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DslNode {
+    pub properties: HashMap<String, PropertyValue>,
+    /// Where each property was assigned, for diagnostics about its value.
+    pub spans: HashMap<String, DslSpan>,
+    pub children: Vec<DslChild>,
+    /// `Extends = "templates/Wall.basepart"`, resolved by the loader.
+    pub extends: Option<DslExtends>,
+}
+
+/// A statement's position: 1-based line and column, `end_column` exclusive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DslSpan {
+    pub line: usize,
+    pub column: usize,
+    pub end_column: usize,
+}
+
+/// The template a block inherits from, with the span of its `Extends` statement so
+/// resolution failures can be reported where they were written.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DslExtends {
+    pub path: String,
+    pub span: DslSpan,
+}
+
+/// A `Child "Name" { ... }` block declared inside another instance.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DslChild {
+    pub name: String,
+    pub node: DslNode,
+}
+
+/// A value expression as written in the DSL, evaluated at load time by `core::eval`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// Numbers, strings and booleans.
+    Literal(PropertyValue),
+    /// Dotted name: variables and bare words (`Part`), enums (`Enum.Material.Wood`) and constants (`math.pi`).
+    Name(String),
+    /// `Vector3.new(1, 2, 3)`, `math.rad(90)`.
+    Call { callee: String, args: Vec<Expr> },
+    /// `{ a, b, c }`
+    Table(Vec<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl BinaryOp {
+    pub fn symbol(self) -> char {
+        match self {
+            BinaryOp::Add => '+',
+            BinaryOp::Sub => '-',
+            BinaryOp::Mul => '*',
+            BinaryOp::Div => '/',
+        }
+    }
+}
+
+/// One statement of a DSL file, with the byte offset where it starts.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub offset: usize,
+    pub kind: StatementKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatementKind {
+    Assignment { key: String, value: Expr },
+    /// `local NAME = value`, visible to later statements and nested blocks.
+    Local { name: String, value: Expr },
+    Child { name: String, body: Vec<Statement> },
+    /// A comment between statements, kept for the formatter. `trailing` comments sit
+    /// on the same line as the statement before them.
+    Comment { text: String, trailing: bool },
+}
+
+/// A region of a DSL file that could not be parsed. Lines and columns are 1-based;
+/// `end_column` is exclusive and marks the end of the skipped input on that line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DslError {
+    pub line: usize,
+    pub column: usize,
+    pub end_column: usize,
+    pub message: String,
+}
+
 // --- Parsers ---
 
-fn ws<'a, F, O, E: nom::error::ParseError<&'a str>>(
-    inner: F,
-) -> impl Parser<&'a str, Output = O, Error = E>
+type ParseError<'a> = nom::error::Error<&'a str>;
+
+fn fail<T>(input: &str, kind: ErrorKind) -> IResult<&str, T> {
+    Err(nom::Err::Error(nom::error::Error::new(input, kind)))
+}
+
+/// `[[...]]` / `[==[...]==]` long bracket; returns the raw contents.
+fn parse_long_bracket(input: &str) -> IResult<&str, &str> {
+    let (rest, level) = delimited(char('['), many0_count(char('=')), char('[')).parse(input)?;
+    let close = format!("]{}]", "=".repeat(level));
+    match rest.find(&close) {
+        Some(end) => Ok((&rest[end + close.len()..], &rest[..end])),
+        None => fail(input, ErrorKind::TakeUntil),
+    }
+}
+
+/// Luau comments: `-- line` and `--[[ block ]]` (with optional `=` levels).
+fn parse_comment(input: &str) -> IResult<&str, &str> {
+    let (input, _) = tag("--")(input)?;
+    alt((parse_long_bracket, take_till(|c| c == '\n'))).parse(input)
+}
+
+/// Whitespace and comments between tokens.
+fn sp(input: &str) -> IResult<&str, ()> {
+    map(many0_count(alt((multispace1, parse_comment))), |_| ()).parse(input)
+}
+
+/// A token preceded by whitespace and comments. Trivia after the token is left for the
+/// next parser, so comments ending a statement reach `parse_trivia`.
+fn ws<'a, F, O>(inner: F) -> impl Parser<&'a str, Output = O, Error = ParseError<'a>>
 where
-    F: Parser<&'a str, Output = O, Error = E>,
+    F: Parser<&'a str, Output = O, Error = ParseError<'a>>,
 {
-    delimited(multispace0, inner, multispace0)
+    preceded(sp, inner)
 }
 
 fn parse_identifier(input: &str) -> IResult<&str, &str> {
@@ -32,166 +155,522 @@ fn parse_identifier(input: &str) -> IResult<&str, &str> {
 }
 
 fn parse_string(input: &str) -> IResult<&str, String> {
-    let (input, _) = tag("\"")(input)?;
-    let (input, content) = take_while(|c| c != '"')(input)?;
-    let (input, _) = tag("\"")(input)?;
+    alt((parse_quoted_string, parse_long_string)).parse(input)
+}
+
+fn parse_long_string(input: &str) -> IResult<&str, String> {
+    // A newline right after the opening bracket is not part of the string.
+    let (input, content) = parse_long_bracket(input)?;
+    let content = content
+        .strip_prefix("\r\n")
+        .or_else(|| content.strip_prefix('\n'))
+        .unwrap_or(content);
     Ok((input, content.to_string()))
 }
 
-fn parse_number(input: &str) -> IResult<&str, f64> {
+/// `"..."` or `'...'` with Luau escape sequences.
+fn parse_quoted_string(input: &str) -> IResult<&str, String> {
+    let quote = match input.chars().next() {
+        Some(q @ ('"' | '\'')) => q,
+        _ => return fail(input, ErrorKind::Char),
+    };
+    let body = &input[1..];
+    let mut out = String::new();
+    let mut chars = body.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c == quote => return Ok((&body[i + 1..], out)),
+            '\n' => return fail(input, ErrorKind::Char), // Unescaped newline
+            '\\' => {
+                let Some((_, e)) = chars.next() else { break };
+                match e {
+                    'n' => out.push('\n'),
+                    't' => out.push('\t'),
+                    'r' => out.push('\r'),
+                    'a' => out.push('\x07'),
+                    'b' => out.push('\x08'),
+                    'f' => out.push('\x0C'),
+                    'v' => out.push('\x0B'),
+                    '\\' | '"' | '\'' | '\n' => out.push(e),
+                    'z' => {
+                        // Skips the following whitespace, including line breaks.
+                        while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
+                    }
+                    'x' => {
+                        let hex: String = (0..2)
+                            .filter_map(|_| chars.next_if(|(_, c)| c.is_ascii_hexdigit()))
+                            .map(|(_, c)| c)
+                            .collect();
+                        match u8::from_str_radix(&hex, 16) {
+                            Ok(b) if hex.len() == 2 => out.push(b as char),
+                            _ => return fail(input, ErrorKind::Escaped),
+                        }
+                    }
+                    'u' => {
+                        if chars.next_if(|(_, c)| *c == '{').is_none() {
+                            return fail(input, ErrorKind::Escaped);
+                        }
+                        let hex: String =
+                            std::iter::from_fn(|| chars.next_if(|(_, c)| c.is_ascii_hexdigit()))
+                                .map(|(_, c)| c)
+                                .collect();
+                        let code = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32);
+                        match (code, chars.next()) {
+                            (Some(c), Some((_, '}'))) => out.push(c),
+                            _ => return fail(input, ErrorKind::Escaped),
+                        }
+                    }
+                    d if d.is_ascii_digit() => {
+                        // \ddd: up to three decimal digits
+                        let mut code = d.to_digit(10).unwrap_or(0);
+                        for _ in 0..2 {
+                            match chars.next_if(|(_, c)| c.is_ascii_digit()) {
+                                Some((_, c)) => code = code * 10 + c.to_digit(10).unwrap_or(0),
+                                None => break,
+                            }
+                        }
+                        match u8::try_from(code) {
+                            Ok(b) => out.push(b as char),
+                            Err(_) => return fail(input, ErrorKind::Escaped),
+                        }
+                    }
+                    _ => return fail(input, ErrorKind::Escaped),
+                }
+            }
+            c => out.push(c),
+        }
+    }
+
+    fail(input, ErrorKind::Char) // Unterminated string
+}
+
+// Digits with optional `_` separators (`1_000_000`).
+fn digits(input: &str) -> IResult<&str, &str> {
+    recognize(pair(digit1, take_while(|c: char| c.is_ascii_digit() || c == '_'))).parse(input)
+}
+
+fn parse_radix_number(input: &str) -> IResult<&str, f64> {
+    let (rest, radix) = alt((
+        map(alt((tag("0x"), tag("0X"))), |_| 16),
+        map(alt((tag("0b"), tag("0B"))), |_| 2),
+    ))
+    .parse(input)?;
+    let (rest, raw) = take_while1(|c: char| c.is_ascii_hexdigit() || c == '_').parse(rest)?;
+    match u64::from_str_radix(&raw.replace('_', ""), radix) {
+        Ok(n) => Ok((rest, n as f64)),
+        Err(_) => fail(input, ErrorKind::HexDigit),
+    }
+}
+
+fn parse_decimal_number(input: &str) -> IResult<&str, f64> {
     map_res(
         recognize((
-            opt(char('-')),
-            digit1,
-            opt(pair(char('.'), digit1)),
+            alt((
+                recognize(pair(digits, opt(pair(char('.'), opt(digits))))),
+                recognize(pair(char('.'), digits)),
+            )),
+            opt((one_of("eE"), opt(one_of("+-")), digits)),
         )),
-        |s: &str| s.parse::<f64>(),
+        |s: &str| s.replace('_', "").parse::<f64>(),
     )
     .parse(input)
 }
 
-fn parse_enum(input: &str) -> IResult<&str, PropertyValue> {
-    // Enum.PartType.Block
-    let (input, _) = tag("Enum.")(input)?;
-    let (input, enum_type) = parse_identifier(input)?;
-    let (input, _) = char('.')(input)?;
-    let (input, enum_item) = parse_identifier(input)?;
-    Ok((input, PropertyValue::Enum(format!("Enum.{}.{}", enum_type, enum_item))))
-}
-
-fn parse_bool(input: &str) -> IResult<&str, bool> {
-    alt((map(tag("true"), |_| true), map(tag("false"), |_| false))).parse(input)
-}
-
-// Constructors
-
-fn parse_vector3(input: &str) -> IResult<&str, PropertyValue> {
-    let (input, _) = tag("Vector3.new")(input)?;
-    let (input, _) = ws(char('(')).parse(input)?;
-    let (input, x) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(',')).parse(input)?;
-    let (input, y) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(',')).parse(input)?;
-    let (input, z) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(')')).parse(input)?;
-    Ok((
-        input,
-        PropertyValue::Vector3(Vec3Wrapper {
-            x: x as f32,
-            y: y as f32,
-            z: z as f32,
-        }),
-    ))
+/// Luau numeric literals (`1`, `.5`, `1e3`, `0xFF`, `0b101`, `1_000`). Signs and
+/// `math.huge` are handled by the expression grammar.
+fn parse_number(input: &str) -> IResult<&str, f64> {
+    alt((parse_radix_number, parse_decimal_number)).parse(input)
 }
 
-fn parse_cframe(input: &str) -> IResult<&str, PropertyValue> {
-    let (input, _) = tag("CFrame.new")(input)?;
-    let (input, _) = ws(char('(')).parse(input)?;
-    let (input, x) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(',')).parse(input)?;
-    let (input, y) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(',')).parse(input)?;
-    let (input, z) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(')')).parse(input)?;
-    Ok((
-        input,
-        PropertyValue::CFrame(CFrameWrapper::new(x as f32, y as f32, z as f32)),
+// --- Expressions ---
+// expr  := term (('+' | '-') term)*
+// term  := unary (('*' | '/') unary)*
+// unary := ('-' | '+') unary | primary
+
+fn parse_name(input: &str) -> IResult<&str, &str> {
+    recognize(pair(
+        parse_identifier,
+        many0_count(preceded(char('.'), parse_identifier)),
     ))
+    .parse(input)
 }
 
-fn parse_color3_from_rgb(input: &str) -> IResult<&str, PropertyValue> {
-    let (input, _) = tag("Color3.fromRGB")(input)?;
-    let (input, _) = ws(char('(')).parse(input)?;
-    let (input, r) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(',')).parse(input)?;
-    let (input, g) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(',')).parse(input)?;
-    let (input, b) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(')')).parse(input)?;
-    Ok((
-        input,
-        PropertyValue::Color3(Color3Wrapper::from_rgb(r as f32, g as f32, b as f32)),
-    ))
+fn parse_args(input: &str) -> IResult<&str, Vec<Expr>> {
+    delimited(
+        ws(char('(')),
+        separated_list0(ws(char(',')), parse_expr),
+        ws(char(')')),
+    )
+    .parse(input)
 }
 
-fn parse_color3_new(input: &str) -> IResult<&str, PropertyValue> {
-    let (input, _) = tag("Color3.new")(input)?;
-    let (input, _) = ws(char('(')).parse(input)?;
-    let (input, r) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(',')).parse(input)?;
-    let (input, g) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(',')).parse(input)?;
-    let (input, b) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(')')).parse(input)?;
-    Ok((
-        input,
-        PropertyValue::Color3(Color3Wrapper::new(r as f32, g as f32, b as f32)),
-    ))
+// `{ item, item, ... }` with an optional trailing separator, as in Luau tables.
+fn parse_table(input: &str) -> IResult<&str, Vec<Expr>> {
+    delimited(
+        ws(char('{')),
+        terminated(
+            separated_list0(ws(one_of(",;")), parse_expr),
+            opt(ws(one_of(",;"))),
+        ),
+        ws(char('}')),
+    )
+    .parse(input)
 }
 
-fn parse_udim2(input: &str) -> IResult<&str, PropertyValue> {
-    let (input, _) = tag("UDim2.new")(input)?;
-    let (input, _) = ws(char('(')).parse(input)?;
-    let (input, xs) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(',')).parse(input)?;
-    let (input, xo) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(',')).parse(input)?;
-    let (input, ys) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(',')).parse(input)?;
-    let (input, yo) = ws(parse_number).parse(input)?;
-    let (input, _) = ws(char(')')).parse(input)?;
-    Ok((
-        input,
-        PropertyValue::UDim2(UDim2Wrapper {
-            xs: xs as f32,
-            xo: xo as i32,
-            ys: ys as f32,
-            yo: yo as i32,
-        }),
+fn parse_name_or_call(input: &str) -> IResult<&str, Expr> {
+    let (input, name) = parse_name(input)?;
+    let (input, args) = opt(parse_args).parse(input)?;
+    let expr = match (name, args) {
+        (callee, Some(args)) => Expr::Call { callee: callee.to_string(), args },
+        ("true", None) => Expr::Literal(PropertyValue::Bool(true)),
+        ("false", None) => Expr::Literal(PropertyValue::Bool(false)),
+        (name, None) => Expr::Name(name.to_string()),
+    };
+    Ok((input, expr))
+}
+
+fn parse_primary(input: &str) -> IResult<&str, Expr> {
+    alt((
+        delimited(ws(char('(')), parse_expr, ws(char(')'))),
+        map(parse_table, Expr::Table),
+        map(ws(parse_number), |n| Expr::Literal(PropertyValue::Number(n))),
+        map(ws(parse_string), |s| Expr::Literal(PropertyValue::String(s))),
+        ws(parse_name_or_call),
     ))
+    .parse(input)
 }
 
-fn parse_value(input: &str) -> IResult<&str, PropertyValue> {
+fn parse_unary(input: &str) -> IResult<&str, Expr> {
     alt((
-        map(parse_bool, PropertyValue::Bool),
-        parse_vector3,
-        parse_cframe,
-        parse_color3_from_rgb,
-        parse_color3_new,
-        parse_udim2,
-        parse_enum,
-        map(parse_number, PropertyValue::Number),
-        map(parse_string, PropertyValue::String),
-        map(parse_identifier, |s| PropertyValue::String(s.to_string())),
+        map(preceded(ws(char('-')), parse_unary), |e| match e {
+            // Fold `-5` into a literal so it prints back the way it was written.
+            Expr::Literal(PropertyValue::Number(n)) => Expr::Literal(PropertyValue::Number(-n)),
+            e => Expr::Neg(Box::new(e)),
+        }),
+        preceded(ws(char('+')), parse_unary),
+        parse_primary,
     ))
     .parse(input)
 }
 
-fn parse_assignment(input: &str) -> IResult<&str, (String, PropertyValue)> {
+fn fold_binary(first: Expr, rest: Vec<(char, Expr)>) -> Expr {
+    rest.into_iter().fold(first, |lhs, (op, rhs)| {
+        let op = match op {
+            '+' => BinaryOp::Add,
+            '-' => BinaryOp::Sub,
+            '*' => BinaryOp::Mul,
+            _ => BinaryOp::Div,
+        };
+        Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+    })
+}
+
+fn parse_term(input: &str) -> IResult<&str, Expr> {
+    let (input, first) = parse_unary(input)?;
+    let (input, rest) = many0(pair(ws(one_of("*/")), parse_unary)).parse(input)?;
+    Ok((input, fold_binary(first, rest)))
+}
+
+fn parse_expr(input: &str) -> IResult<&str, Expr> {
+    let (input, first) = parse_term(input)?;
+    let (input, rest) = many0(pair(ws(one_of("+-")), parse_term)).parse(input)?;
+    Ok((input, fold_binary(first, rest)))
+}
+
+// --- Statements ---
+
+fn parse_assignment(input: &str) -> IResult<&str, (String, Expr)> {
     let (input, key) = ws(parse_identifier).parse(input)?;
     let (input, _) = ws(char('=')).parse(input)?;
-    let (input, value) = ws(parse_value).parse(input)?;
+    let (input, value) = parse_expr(input)?;
     Ok((input, (key.to_string(), value)))
 }
 
-pub fn parse_instance_dsl(input: &str) -> IResult<&str, HashMap<String, PropertyValue>> {
-    let (input, pairs) = many0(parse_assignment).parse(input)?;
-    let mut map = HashMap::new();
-    for (k, v) in pairs {
-        map.insert(k, v);
+fn parse_local(input: &str) -> IResult<&str, (String, Expr)> {
+    let (input, _) = ws(terminated(tag("local"), multispace1)).parse(input)?;
+    parse_assignment(input)
+}
+
+fn parse_child_header(input: &str) -> IResult<&str, String> {
+    // Child "Handle" { ClassName = Part ... }
+    let (input, _) = ws(tag("Child")).parse(input)?;
+    let (input, name) = ws(parse_string).parse(input)?;
+    let (input, _) = ws(char('{')).parse(input)?;
+    Ok((input, name))
+}
+
+/// Parses statements until the end of input (or the closing `}` of a nested block),
+/// recording a `DslError` for every statement that fails and resuming on the next line.
+fn parse_block<'a>(
+    source: &'a str,
+    mut input: &'a str,
+    nested: bool,
+    errors: &mut Vec<DslError>,
+) -> (&'a str, Vec<Statement>) {
+    let mut statements = Vec::new();
+
+    loop {
+        input = parse_trivia(source, input, &mut statements);
+        if input.is_empty() || (nested && input.starts_with('}')) {
+            break;
+        }
+        let offset = source.len() - input.len();
+
+        if let Ok((rest, name)) = parse_child_header(input) {
+            let (rest, body) = parse_block(source, rest, true, errors);
+            input = match rest.strip_prefix('}') {
+                Some(rest) => rest,
+                None => {
+                    errors.push(error_at(
+                        source,
+                        input,
+                        format!("Unclosed block for Child \"{}\": expected `}}`", name),
+                    ));
+                    rest
+                }
+            };
+            statements.push(Statement {
+                offset,
+                kind: StatementKind::Child { name, body },
+            });
+            continue;
+        }
+
+        if let Ok((rest, (name, value))) = parse_local(input) {
+            statements.push(Statement {
+                offset,
+                kind: StatementKind::Local { name, value },
+            });
+            input = rest;
+            continue;
+        }
+
+        match parse_assignment(input) {
+            Ok((rest, (key, value))) => {
+                statements.push(Statement {
+                    offset,
+                    kind: StatementKind::Assignment { key, value },
+                });
+                input = rest;
+            }
+            Err(_) => {
+                let local = opt(ws(terminated(tag::<&str, &str, ParseError>("local"), multispace1)));
+                let message = match pair(local, ws(parse_identifier)).parse(input) {
+                    Ok((rest, (local, key))) if ws(char::<&str, ParseError>('='))
+                        .parse(rest)
+                        .is_ok() =>
+                    {
+                        let kind = if local.is_some() { "local" } else { "property" };
+                        format!("Invalid value for {} `{}`", kind, key)
+                    }
+                    _ => "Expected `Key = Value` assignment or `Child \"Name\" { ... }` block"
+                        .to_string(),
+                };
+                errors.push(error_at(source, input, message));
+                input = skip_statement(input, nested);
+            }
+        }
     }
-    Ok((input, map))
+
+    (input, statements)
+}
+
+/// Skips whitespace and comments before a statement, recording each comment.
+fn parse_trivia<'a>(source: &'a str, mut input: &'a str, statements: &mut Vec<Statement>) -> &'a str {
+    let mut same_line = matches!(
+        statements.last(),
+        Some(Statement { kind, .. }) if !matches!(kind, StatementKind::Comment { .. })
+    );
+    loop {
+        if let Ok((rest, space)) = multispace1::<&str, ParseError>(input) {
+            same_line &= !space.contains('\n');
+            input = rest;
+        } else if let Ok((rest, _)) = parse_comment(input) {
+            let text = input[..input.len() - rest.len()].trim_end().to_string();
+            statements.push(Statement {
+                offset: source.len() - input.len(),
+                kind: StatementKind::Comment { text, trailing: same_line },
+            });
+            same_line = false;
+            input = rest;
+        } else {
+            return input;
+        }
+    }
+}
+
+/// Skips the rest of the current line. Inside a block, a `}` also ends the skip so
+/// `Child "A" { Bad = @ }` on one line still closes its block.
+fn skip_statement(input: &str, nested: bool) -> &str {
+    let end = input
+        .find(|c| c == '\n' || (nested && c == '}'))
+        .unwrap_or(input.len());
+    &input[end..]
+}
+
+fn span_at(source: &str, rest: &str) -> DslSpan {
+    let offset = source.len() - rest.len();
+    let before = &source[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let column = source[line_start..offset].chars().count() + 1;
+    let remaining = rest.split('\n').next().unwrap_or("").trim_end();
+    DslSpan {
+        line,
+        column,
+        end_column: column + remaining.chars().count(),
+    }
+}
+
+fn error_at(source: &str, rest: &str, message: String) -> DslError {
+    let span = span_at(source, rest);
+    DslError {
+        line: span.line,
+        column: span.column,
+        end_column: span.end_column,
+        message,
+    }
+}
+
+/// Parses a DSL file into statements without evaluating values. Unparseable statements
+/// are skipped and reported instead of aborting, so callers get every error in one pass.
+pub fn parse_statements(input: &str) -> (Vec<Statement>, Vec<DslError>) {
+    let mut errors = Vec::new();
+    let (rest, statements) = parse_block(input, input, false, &mut errors);
+    debug_assert!(rest.is_empty());
+    (statements, errors)
+}
+
+/// Like `evaluate`, but `ClassName` and `Name` also take a bare word (`ClassName = Part`).
+fn evaluate_property(key: &str, value: &Expr, scope: &Scope) -> Result<PropertyValue, String> {
+    match value {
+        Expr::Name(word)
+            if matches!(key, "ClassName" | "Name") && !word.contains('.') && !scope.contains_key(word) =>
+        {
+            Ok(PropertyValue::String(word.clone()))
+        }
+        _ => evaluate(value, scope),
+    }
+}
+
+fn evaluate_block(
+    source: &str,
+    statements: &[Statement],
+    scope: &Scope,
+    errors: &mut Vec<DslError>,
+) -> DslNode {
+    let mut node = DslNode::default();
+    // Locals shadow the enclosing scope until the end of the block.
+    let mut scope = scope.clone();
+    for statement in statements {
+        let at = &source[statement.offset..];
+        match &statement.kind {
+            StatementKind::Assignment { key, value } => match evaluate_property(key, value, &scope) {
+                Ok(PropertyValue::String(path)) if key == "Extends" => {
+                    node.extends = Some(DslExtends {
+                        path,
+                        span: span_at(source, at),
+                    });
+                }
+                Ok(_) if key == "Extends" => errors.push(error_at(
+                    source,
+                    at,
+                    "`Extends` expects a template path string".to_string(),
+                )),
+                Ok(value) => {
+                    node.properties.insert(key.clone(), value);
+                    node.spans.insert(key.clone(), span_at(source, at));
+                }
+                Err(e) => errors.push(error_at(
+                    source,
+                    at,
+                    format!("Invalid value for property `{}`: {}", key, e),
+                )),
+            },
+            StatementKind::Local { name, value } => match evaluate(value, &scope) {
+                Ok(value) => {
+                    scope.insert(name.clone(), value);
+                }
+                Err(e) => errors.push(error_at(
+                    source,
+                    at,
+                    format!("Invalid value for local `{}`: {}", name, e),
+                )),
+            },
+            StatementKind::Child { name, body } => node.children.push(DslChild {
+                name: name.clone(),
+                node: evaluate_block(source, body, &scope, errors),
+            }),
+            StatementKind::Comment { .. } => {}
+        }
+    }
+    node
+}
+
+/// Parses and evaluates a whole DSL file, with `defs` (project-wide variables) in scope.
+/// Errors are sorted by position.
+pub fn parse_instance_dsl(input: &str, defs: &Scope) -> (DslNode, Vec<DslError>) {
+    let (statements, mut errors) = parse_statements(input);
+    let node = evaluate_block(input, &statements, defs, &mut errors);
+    errors.sort_by_key(|e| (e.line, e.column));
+    (node, errors)
+}
+
+/// Parses and evaluates a single value expression with nothing in scope.
+pub fn parse_value(input: &str) -> Result<PropertyValue, String> {
+    match terminated(parse_expr, sp).parse(input) {
+        Ok(("", expr)) => evaluate(&expr, &Scope::new()),
+        _ => Err(format!("invalid value `{}`", input)),
+    }
+}
+
+/// Parses a project defs file. Both `local NAME = value` and `NAME = value` define a
+/// variable; later definitions can use earlier ones.
+pub fn parse_defs(input: &str) -> (Scope, Vec<DslError>) {
+    let (statements, mut errors) = parse_statements(input);
+    let mut defs = Scope::new();
+    for statement in &statements {
+        let at = &input[statement.offset..];
+        match &statement.kind {
+            StatementKind::Assignment { key: name, value }
+            | StatementKind::Local { name, value } => match evaluate(value, &defs) {
+                Ok(value) => {
+                    defs.insert(name.clone(), value);
+                }
+                Err(e) => errors.push(error_at(
+                    input,
+                    at,
+                    format!("Invalid value for `{}`: {}", name, e),
+                )),
+            },
+            StatementKind::Child { .. } => errors.push(error_at(
+                input,
+                at,
+                "Child blocks are not allowed in defs".to_string(),
+            )),
+            StatementKind::Comment { .. } => {}
+        }
+    }
+    errors.sort_by_key(|e| (e.line, e.column));
+    (defs, errors)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::core::datamodel::{AxesWrapper, FacesWrapper, InstanceRef, Vec3Wrapper, Vector2Wrapper};
+    use glam::Vec3;
+
+    fn value(input: &str) -> Result<PropertyValue, String> {
+        let (rest, expr) = parse_expr(input).expect("expression should parse");
+        assert!(rest.is_empty(), "unparsed input: {:?}", rest);
+        evaluate(&expr, &Scope::new())
+    }
 
     #[test]
     fn test_parse_vector3() {
-        let input = "Vector3.new(10, 20, 30.5)";
-        let (_, val) = parse_vector3(input).unwrap();
-        if let PropertyValue::Vector3(v) = val {
+        if let PropertyValue::Vector3(v) = value("Vector3.new(10, 20, 30.5)").unwrap() {
             assert_eq!(v.x, 10.0);
             assert_eq!(v.y, 20.0);
             assert_eq!(v.z, 30.5);
@@ -205,7 +684,7 @@ mod tests {
         let input = "Name = \"TestPart\"";
         let (_, (key, val)) = parse_assignment(input).unwrap();
         assert_eq!(key, "Name");
-        assert_eq!(val, PropertyValue::String("TestPart".to_string()));
+        assert_eq!(val, Expr::Literal(PropertyValue::String("TestPart".to_string())));
     }
 
     #[test]
@@ -217,7 +696,9 @@ mod tests {
             Size = Vector3.new(4, 1, 2)
             Color = Color3.fromRGB(255, 0, 0)
         "#;
-        let (_, props) = parse_instance_dsl(input).unwrap();
+        let (node, errors) = parse_instance_dsl(input, &Scope::new());
+        assert!(errors.is_empty());
+        let props = &node.properties;
 
         assert_eq!(
             props.get("ClassName"),
@@ -238,4 +719,279 @@ mod tests {
             panic!("Color wrong type");
         }
     }
+
+    #[test]
+    fn test_parse_nested_children() {
+        let input = r#"
+            ClassName = Model
+            Child "Handle" {
+                ClassName = Part
+                Size = Vector3.new(1, 4, 1)
+                Child "Grip" { ClassName = Attachment }
+            }
+            Child "Blade" {
+                ClassName = Part
+            }
+            Name = "Sword"
+        "#;
+        let (node, errors) = parse_instance_dsl(input, &Scope::new());
+        assert!(errors.is_empty());
+
+        assert_eq!(
+            node.properties.get("Name"),
+            Some(&PropertyValue::String("Sword".to_string()))
+        );
+        assert_eq!(node.children.len(), 2);
+        assert_eq!(node.children[0].name, "Handle");
+        assert_eq!(node.children[1].name, "Blade");
+
+        let handle = &node.children[0].node;
+        assert!(handle.properties.contains_key("Size"));
+        assert_eq!(handle.children.len(), 1);
+        assert_eq!(
+            handle.children[0].node.properties.get("ClassName"),
+            Some(&PropertyValue::String("Attachment".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors_are_recovered() {
+        let input = "Name = \"Wall\"\nSize = Vector3.new(1, 2)\n  ??? nonsense\nAnchored = true\nChild \"A\" {\n  Color = \n";
+        let (node, errors) = parse_instance_dsl(input, &Scope::new());
+
+        // Statements after the broken lines are still loaded.
+        assert_eq!(node.properties.get("Anchored"), Some(&PropertyValue::Bool(true)));
+        assert!(!node.properties.contains_key("Size"));
+
+        assert_eq!(errors.len(), 4);
+        assert_eq!((errors[0].line, errors[0].column), (2, 1));
+        assert_eq!(errors[0].end_column, 25);
+        assert!(errors[0].message.contains("`Size`"));
+        assert_eq!((errors[1].line, errors[1].column), (3, 3));
+        assert!(errors[2].message.contains("Unclosed block"));
+        assert_eq!(errors[2].line, 5);
+        assert_eq!((errors[3].line, errors[3].column), (6, 3));
+    }
+
+    #[test]
+    fn test_parse_cframe_constructors() {
+        let val = value("CFrame.new(1, 2, 3, 1, 0, 0, 0, 0, -1, 0, 1, 0)");
+        let Ok(PropertyValue::CFrame(cf)) = val else { panic!("Expected CFrame") };
+        assert_eq!(cf.components[5], 0.0);
+        assert_eq!(cf.components[7], 0.0);
+        assert_eq!(cf.components[8], -1.0);
+
+        // Translation followed by a 90 degree yaw: the rotation must not move the origin.
+        let input = "CFrame.new(0, 5, 0) * CFrame.Angles(0, 1.5707964, 0)";
+        let Ok(PropertyValue::CFrame(cf)) = value(input) else { panic!("Expected CFrame") };
+        assert_eq!(cf.translation(), Vec3::new(0.0, 5.0, 0.0));
+        let right = cf.rotation() * Vec3::X;
+        assert!((right - Vec3::new(0.0, 0.0, -1.0)).length() < 1e-5);
+
+        // lookAt faces -Z towards the target.
+        let val = value("CFrame.new(Vector3.new(0, 0, 0), Vector3.new(10, 0, 0))");
+        let Ok(PropertyValue::CFrame(cf)) = val else { panic!("Expected CFrame") };
+        let look = cf.rotation() * -Vec3::Z;
+        assert!((look - Vec3::X).length() < 1e-5);
+
+        assert!(value("CFrame.new(1, 2)").is_err());
+    }
+
+    #[test]
+    fn test_parse_expressions() {
+        assert_eq!(value("1 + 2 * 3"), Ok(PropertyValue::Number(7.0)));
+        assert_eq!(value("(1 + 2) * 3"), Ok(PropertyValue::Number(9.0)));
+        assert_eq!(value("10 - 4 - 3"), Ok(PropertyValue::Number(3.0)));
+        assert_eq!(value("-(2 + 3)"), Ok(PropertyValue::Number(-5.0)));
+        assert_eq!(value("math.rad(180)"), Ok(PropertyValue::Number(std::f64::consts::PI)));
+        assert_eq!(
+            value("Vector3.new(4, 1, 2) * 2 + Vector3.one"),
+            Ok(PropertyValue::Vector3(Vec3Wrapper { x: 9.0, y: 3.0, z: 5.0 }))
+        );
+        assert_eq!(
+            value("-Vector3.yAxis * 0.5"),
+            Ok(PropertyValue::Vector3(Vec3Wrapper { x: 0.0, y: -0.5, z: 0.0 }))
+        );
+
+        let Ok(PropertyValue::CFrame(cf)) = value("CFrame.new(0, 10 / 2, 0) + Vector3.xAxis") else {
+            panic!("Expected CFrame")
+        };
+        assert_eq!(cf.translation(), Vec3::new(1.0, 5.0, 0.0));
+
+        let err = value("Vector3.one + 1").unwrap_err();
+        assert_eq!(err, "cannot apply `+` to Vector3 and number");
+        assert!(value("Vector3.nope").is_err());
+        assert!(value("nope.new(1)").is_err());
+    }
+
+    #[test]
+    fn test_parse_datatypes() {
+        let input = r#"
+            Anchor = Vector2.new(0.5, 1)
+            Padding = UDim.new(0, 8)
+            Slice = Rect.new(Vector2.new(1, 2), Vector2.new(3, 4))
+            Lifetime = NumberRange.new(1, 2.5)
+            Transparency = NumberSequence.new({
+                NumberSequenceKeypoint.new(0, 0),
+                NumberSequenceKeypoint.new(0.5, 0.2, 0.1),
+                NumberSequenceKeypoint.new(1, 1),
+            })
+            Color = ColorSequence.new(Color3.new(1, 0, 0), Color3.new(0, 0, 1))
+            BrickColor = BrickColor.new("Bright red")
+            Team = BrickColor.new(23)
+            FontFace = Font.new("rbxasset://fonts/families/Arial.json", Enum.FontWeight.Bold)
+            Physics = PhysicalProperties.new(Enum.Material.Wood)
+            Faces = Faces.new(Enum.NormalId.Top, Enum.NormalId.Front)
+            Axes = Axes.new(Enum.Axis.X, Enum.NormalId.Top)
+            PrimaryPart = Ref("Handle")
+        "#;
+        let (node, errors) = parse_instance_dsl(input, &Scope::new());
+        assert!(errors.is_empty(), "{:?}", errors);
+        let props = &node.properties;
+
+        assert_eq!(
+            props.get("Anchor"),
+            Some(&PropertyValue::Vector2(Vector2Wrapper { x: 0.5, y: 1.0 }))
+        );
+        assert!(matches!(props.get("Padding"), Some(PropertyValue::UDim(u)) if u.offset == 8));
+        assert!(matches!(props.get("Slice"), Some(PropertyValue::Rect(r)) if r.max.y == 4.0));
+        assert!(matches!(props.get("Lifetime"), Some(PropertyValue::NumberRange(r)) if r.max == 2.5));
+        assert!(matches!(
+            props.get("Transparency"),
+            Some(PropertyValue::NumberSequence(s)) if s.keypoints.len() == 3 && s.keypoints[1].envelope == 0.1
+        ));
+        assert!(matches!(props.get("Color"), Some(PropertyValue::ColorSequence(s)) if s.keypoints[1].color.b == 1.0));
+        assert!(matches!(props.get("BrickColor"), Some(PropertyValue::BrickColor(b)) if b.number == 21));
+        assert!(matches!(props.get("Team"), Some(PropertyValue::BrickColor(b)) if b.name == "Bright blue"));
+        assert!(matches!(props.get("FontFace"), Some(PropertyValue::Font(f)) if f.weight == "Bold" && f.style == "Normal"));
+        assert!(matches!(props.get("Physics"), Some(PropertyValue::PhysicalProperties(p)) if p.density == 0.35));
+        assert_eq!(
+            props.get("Faces"),
+            Some(&PropertyValue::Faces(FacesWrapper { top: true, front: true, ..Default::default() }))
+        );
+        assert_eq!(
+            props.get("Axes"),
+            Some(&PropertyValue::Axes(AxesWrapper { x: true, y: true, z: false }))
+        );
+        assert_eq!(
+            props.get("PrimaryPart"),
+            Some(&PropertyValue::Ref(InstanceRef { instance: "Handle".to_string() }))
+        );
+
+        // Unknown palette entries and out-of-order keypoints are rejected.
+        let (_, errors) = parse_instance_dsl("BrickColor = BrickColor.new(\"Not a color\")", &Scope::new());
+        assert_eq!(errors.len(), 1);
+        let (_, errors) = parse_instance_dsl(
+            "T = NumberSequence.new({NumberSequenceKeypoint.new(1, 0), NumberSequenceKeypoint.new(0, 1)})",
+            &Scope::new(),
+        );
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn test_parse_comments_and_literals() {
+        let input = r#"
+            -- Header comment
+            --[==[
+                Block comment with ]] inside
+            ]==]
+            Name = 'It\'s \x41\u{1F600}\65' -- trailing comment
+            Source = [[
+line one
+line two]]
+            Text = "tab\tnew\
+line\z
+                   joined"
+            Size = Vector3.new(.5, 1e3, +2) --[[ inline ]] Transparency = 0x10
+            Mask = 0b1010
+            Big = 1_000_000
+            MaxForce = math.huge
+            Small = -2.5E-1
+        "#;
+        let (node, errors) = parse_instance_dsl(input, &Scope::new());
+        assert!(errors.is_empty(), "{:?}", errors);
+        let props = &node.properties;
+
+        assert_eq!(
+            props.get("Name"),
+            Some(&PropertyValue::String("It's A\u{1F600}A".to_string()))
+        );
+        assert_eq!(
+            props.get("Source"),
+            Some(&PropertyValue::String("line one\nline two".to_string()))
+        );
+        assert_eq!(
+            props.get("Text"),
+            Some(&PropertyValue::String("tab\tnew\nlinejoined".to_string()))
+        );
+        assert_eq!(
+            props.get("Size"),
+            Some(&PropertyValue::Vector3(Vec3Wrapper { x: 0.5, y: 1000.0, z: 2.0 }))
+        );
+        assert_eq!(props.get("Transparency"), Some(&PropertyValue::Number(16.0)));
+        assert_eq!(props.get("Mask"), Some(&PropertyValue::Number(10.0)));
+        assert_eq!(props.get("Big"), Some(&PropertyValue::Number(1_000_000.0)));
+        assert_eq!(props.get("MaxForce"), Some(&PropertyValue::Number(f64::INFINITY)));
+        assert_eq!(props.get("Small"), Some(&PropertyValue::Number(-0.25)));
+    }
+
+    #[test]
+    fn test_parse_locals_and_defs() {
+        let (defs, errors) = parse_defs("local WALL_HEIGHT = 12\nTEAM_RED = Color3.fromRGB(255, 0, 0)\nHALF = WALL_HEIGHT / 2");
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(defs.get("HALF"), Some(&PropertyValue::Number(6.0)));
+
+        let input = r#"
+            Extends = "templates/Wall.basepart"
+            local WIDTH = 4
+            Size = Vector3.new(WIDTH, WALL_HEIGHT, 1)
+            Color = TEAM_RED
+            Child "Trim" {
+                local WIDTH = WIDTH * 2
+                Size = Vector3.new(WIDTH, 1, 1)
+            }
+            local BAD = Vector3.one + 1
+        "#;
+        let (node, errors) = parse_instance_dsl(input, &defs);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("local `BAD`"));
+
+        let extends = node.extends.as_ref().expect("Extends should be captured");
+        assert_eq!(extends.path, "templates/Wall.basepart");
+        assert_eq!((extends.span.line, extends.span.column), (2, 13));
+        assert!(!node.properties.contains_key("Extends"));
+        assert_eq!(
+            node.properties.get("Size"),
+            Some(&PropertyValue::Vector3(Vec3Wrapper { x: 4.0, y: 12.0, z: 1.0 }))
+        );
+        assert!(matches!(node.properties.get("Color"), Some(PropertyValue::Color3(c)) if c.r == 1.0));
+        assert_eq!(
+            node.children[0].node.properties.get("Size"),
+            Some(&PropertyValue::Vector3(Vec3Wrapper { x: 8.0, y: 1.0, z: 1.0 }))
+        );
+    }
+
+    #[test]
+    fn test_unknown_identifiers_are_errors() {
+        let mut defs = Scope::new();
+        defs.insert("TEAM_RED".to_string(), parse_value("Color3.new(1, 0, 0)").unwrap());
+        let input = "ClassName = Part\nName = Wall\nMaterial = Wood\nColor = TEAM_RD\nSize = Vector3.new(WIDHT, 1, 1)";
+        let (node, errors) = parse_instance_dsl(input, &defs);
+
+        let messages: Vec<_> = errors.iter().map(|e| (e.line, e.message.as_str())).collect();
+        assert_eq!(
+            messages,
+            [
+                (4, "Invalid value for property `Color`: unknown identifier `TEAM_RD`"),
+                (5, "Invalid value for property `Size`: unknown identifier `WIDHT`"),
+            ]
+        );
+        // Class and instance names, and enum items, may still be written bare
+        assert_eq!(node.properties.get("ClassName"), Some(&PropertyValue::String("Part".to_string())));
+        assert_eq!(node.properties.get("Name"), Some(&PropertyValue::String("Wall".to_string())));
+        assert_eq!(node.properties.get("Material"), Some(&PropertyValue::String("Wood".to_string())));
+
+        let (_, errors) = parse_defs("local WALL = Vector3.new(4, 1, 1)\nDOOR = WAL * 2");
+        assert_eq!(errors[0].message, "Invalid value for `DOOR`: unknown identifier `WAL`");
+    }
 }
@@ -0,0 +1,559 @@
+use crate::core::datamodel::{Color3Wrapper, Instance, PropertyValue};
+use crate::core::parser::{BinaryOp, DslError, Expr, Statement, StatementKind, parse_statements};
+use std::fmt::{Display, LowerExp};
+
+const INDENT: &str = "    ";
+// Tables longer than this are written one item per line.
+const MAX_INLINE_TABLE: usize = 60;
+
+/// Rewrites a DSL file in canonical form: statements in a stable order, normalized
+/// numbers and strings, four-space indentation. Comments between statements are kept.
+/// Files with parse errors are left alone and their errors returned instead.
+pub fn format_dsl(source: &str) -> Result<String, Vec<DslError>> {
+    let (statements, errors) = parse_statements(source);
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let statements = canonicalize(statements);
+    let mut out = String::new();
+    write_block(&statements, 0, &mut out);
+
+    // The printed file must parse back to exactly the same statements.
+    let (reparsed, _) = parse_statements(&out);
+    if strip_offsets(reparsed) != strip_offsets(statements) {
+        return Err(vec![DslError {
+            line: 1,
+            column: 1,
+            end_column: 1,
+            message: "Formatting would change the meaning of this file; left unchanged".to_string(),
+        }]);
+    }
+    Ok(out)
+}
+
+/// DSL text for an instance and its descendants, as `ClassName`, sorted properties and
+/// `Child` blocks.
+pub fn print_instance(instance: &Instance) -> String {
+    let mut out = String::new();
+    write_instance(instance, 0, &mut out);
+    out
+}
+
+fn write_instance(instance: &Instance, depth: usize, out: &mut String) {
+    write_line(out, depth, &format!("ClassName = {}", instance.class_name));
+
+    let mut keys: Vec<&String> = instance
+        .properties
+        .keys()
+        .filter(|k| k.as_str() != "ClassName")
+        .collect();
+    keys.sort_by_key(|k| (k.as_str() != "Name", k.as_str()));
+    for key in keys {
+        let value = print_value(&instance.properties[key]);
+        write_line(out, depth, &format!("{} = {}", key, value));
+    }
+
+    for child in &instance.children {
+        write_line(out, depth, &format!("Child {} {{", print_string(&child.name)));
+        write_instance(child, depth + 1, out);
+        write_line(out, depth, "}");
+    }
+}
+
+// --- Statement order ---
+
+/// Sorts statements into canonical order. `local`s act as barriers (moving a statement
+/// across one could change what it refers to); between them, assignments come first
+/// (`Extends`, `ClassName`, `Name`, then alphabetical) followed by `Child` blocks in
+/// their original order. Comments travel with the statement they annotate.
+fn canonicalize(statements: Vec<Statement>) -> Vec<Statement> {
+    let mut groups: Vec<Vec<Statement>> = Vec::new();
+    let mut leading: Vec<Statement> = Vec::new();
+    for mut statement in statements {
+        match &mut statement.kind {
+            StatementKind::Comment { trailing: true, .. } if leading.is_empty() => {
+                match groups.last_mut() {
+                    Some(group) => group.push(statement),
+                    None => leading.push(statement),
+                }
+            }
+            StatementKind::Comment { .. } => leading.push(statement),
+            kind => {
+                if let StatementKind::Child { body, .. } = kind {
+                    *body = canonicalize(std::mem::take(body));
+                }
+                leading.push(statement);
+                groups.push(std::mem::take(&mut leading));
+            }
+        }
+    }
+
+    // Comments before the first statement are a header and stay at the top.
+    let mut out = match groups.first_mut() {
+        Some(first) => {
+            let header = first
+                .iter()
+                .take_while(|s| matches!(s.kind, StatementKind::Comment { .. }))
+                .count();
+            first.drain(..header).collect()
+        }
+        None => Vec::new(),
+    };
+    let mut segment: Vec<Vec<Statement>> = Vec::new();
+    for group in groups {
+        if group.iter().any(|s| matches!(s.kind, StatementKind::Local { .. })) {
+            flush_segment(&mut segment, &mut out);
+            out.extend(group);
+        } else {
+            segment.push(group);
+        }
+    }
+    flush_segment(&mut segment, &mut out);
+    // Comments after the last statement stay at the end of the block.
+    out.extend(leading);
+    out
+}
+
+fn flush_segment(segment: &mut Vec<Vec<Statement>>, out: &mut Vec<Statement>) {
+    segment.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+    out.extend(segment.drain(..).flatten());
+}
+
+fn sort_key(group: &[Statement]) -> (u8, &str) {
+    let statement = group
+        .iter()
+        .find(|s| !matches!(s.kind, StatementKind::Comment { .. }));
+    match statement.map(|s| &s.kind) {
+        Some(StatementKind::Assignment { key, .. }) => match key.as_str() {
+            "Extends" => (0, ""),
+            "ClassName" => (1, ""),
+            "Name" => (2, ""),
+            key => (3, key),
+        },
+        _ => (4, ""),
+    }
+}
+
+fn strip_offsets(statements: Vec<Statement>) -> Vec<Statement> {
+    statements
+        .into_iter()
+        .map(|mut statement| {
+            statement.offset = 0;
+            if let StatementKind::Child { body, .. } = &mut statement.kind {
+                *body = strip_offsets(std::mem::take(body));
+            }
+            statement
+        })
+        .collect()
+}
+
+// --- Statements ---
+
+fn write_line(out: &mut String, depth: usize, line: &str) {
+    out.push_str(&INDENT.repeat(depth));
+    out.push_str(line);
+    out.push('\n');
+}
+
+fn write_block(statements: &[Statement], depth: usize, out: &mut String) {
+    let mut iter = statements.iter().peekable();
+    while let Some(statement) = iter.next() {
+        let trailing = match iter.peek().map(|s| &s.kind) {
+            Some(StatementKind::Comment { text, trailing: true }) => {
+                iter.next();
+                format!(" {}", text)
+            }
+            _ => String::new(),
+        };
+
+        match &statement.kind {
+            StatementKind::Assignment { key, value } => {
+                let line = format!("{} = {}{}", key, print_expr_at(value, depth), trailing);
+                write_line(out, depth, &line);
+            }
+            StatementKind::Local { name, value } => {
+                let line = format!("local {} = {}{}", name, print_expr_at(value, depth), trailing);
+                write_line(out, depth, &line);
+            }
+            StatementKind::Child { name, body } if body.is_empty() => {
+                write_line(out, depth, &format!("Child {} {{}}{}", print_string(name), trailing));
+            }
+            StatementKind::Child { name, body } => {
+                write_line(out, depth, &format!("Child {} {{", print_string(name)));
+                write_block(body, depth + 1, out);
+                write_line(out, depth, &format!("}}{}", trailing));
+            }
+            // Comments are written verbatim; block comments keep their own line breaks.
+            StatementKind::Comment { text, .. } => write_line(out, depth, &format!("{}{}", text, trailing)),
+        }
+    }
+}
+
+// --- Expressions ---
+
+/// Canonical text for an expression, on one line.
+pub fn print_expr(expr: &Expr) -> String {
+    print_expr_at(expr, usize::MAX)
+}
+
+// `depth` is the indentation of the enclosing statement, used when a long table has to
+// be broken over several lines; `usize::MAX` keeps everything on one line.
+fn print_expr_at(expr: &Expr, depth: usize) -> String {
+    match expr {
+        Expr::Literal(value) => print_value(value),
+        Expr::Name(name) => name.clone(),
+        Expr::Call { callee, args } => {
+            let args: Vec<String> = args.iter().map(|a| print_expr_at(a, depth)).collect();
+            format!("{}({})", callee, args.join(", "))
+        }
+        Expr::Table(items) => {
+            let inline: Vec<String> = items.iter().map(print_expr).collect();
+            let inline = format!("{{{}}}", inline.join(", "));
+            if depth == usize::MAX || inline.len() <= MAX_INLINE_TABLE {
+                return inline;
+            }
+            let mut out = "{\n".to_string();
+            for item in items {
+                out.push_str(&INDENT.repeat(depth + 1));
+                out.push_str(&print_expr_at(item, depth + 1));
+                out.push_str(",\n");
+            }
+            out.push_str(&INDENT.repeat(depth));
+            out.push('}');
+            out
+        }
+        Expr::Neg(inner) => {
+            let inner_text = print_expr_at(inner, depth);
+            // Parenthesize binaries, and anything starting with `-` so it can't become `--`.
+            if matches!(**inner, Expr::Binary(..)) || inner_text.starts_with('-') {
+                format!("-({})", inner_text)
+            } else {
+                format!("-{}", inner_text)
+            }
+        }
+        Expr::Binary(op, lhs, rhs) => {
+            let lhs_text = print_operand(lhs, depth, precedence(lhs) < op_precedence(*op));
+            let rhs_text = print_operand(rhs, depth, precedence(rhs) <= op_precedence(*op));
+            format!("{} {} {}", lhs_text, op.symbol(), rhs_text)
+        }
+    }
+}
+
+fn print_operand(expr: &Expr, depth: usize, parenthesize: bool) -> String {
+    let text = print_expr_at(expr, depth);
+    if parenthesize { format!("({})", text) } else { text }
+}
+
+fn op_precedence(op: BinaryOp) -> u8 {
+    match op {
+        BinaryOp::Add | BinaryOp::Sub => 1,
+        BinaryOp::Mul | BinaryOp::Div => 2,
+    }
+}
+
+fn precedence(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Binary(op, ..) => op_precedence(*op),
+        _ => 3,
+    }
+}
+
+// --- Values ---
+
+/// Canonical DSL text for a value, as the constructor call that builds it.
+pub fn print_value(value: &PropertyValue) -> String {
+    match value {
+        PropertyValue::String(s) => print_string(s),
+        PropertyValue::Bool(b) => b.to_string(),
+        PropertyValue::Number(n) => format_number(*n),
+        PropertyValue::Vector3(v) => call("Vector3.new", [v.x, v.y, v.z]),
+        PropertyValue::Vector2(v) => call("Vector2.new", [v.x, v.y]),
+        PropertyValue::CFrame(cf) => {
+            let c = &cf.components;
+            if c[3..] == [1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0] {
+                call("CFrame.new", &c[..3])
+            } else {
+                call("CFrame.new", c)
+            }
+        }
+        PropertyValue::Color3(c) => match (rgb_byte(c.r), rgb_byte(c.g), rgb_byte(c.b)) {
+            (Some(r), Some(g), Some(b)) => call("Color3.fromRGB", [r, g, b]),
+            _ => call("Color3.new", [c.r, c.g, c.b]),
+        },
+        PropertyValue::UDim2(u) => format!(
+            "UDim2.new({}, {}, {}, {})",
+            format_number(u.xs),
+            u.xo,
+            format_number(u.ys),
+            u.yo
+        ),
+        PropertyValue::UDim(u) => format!("UDim.new({}, {})", format_number(u.scale), u.offset),
+        PropertyValue::Rect(r) => call("Rect.new", [r.min.x, r.min.y, r.max.x, r.max.y]),
+        PropertyValue::NumberRange(r) if r.min == r.max => call("NumberRange.new", [r.min]),
+        PropertyValue::NumberRange(r) => call("NumberRange.new", [r.min, r.max]),
+        PropertyValue::NumberSequence(s) => match s.keypoints.as_slice() {
+            [a, b] if a.time == 0.0 && b.time == 1.0 && a.envelope == 0.0 && b.envelope == 0.0 => {
+                if a.value == b.value {
+                    call("NumberSequence.new", [a.value])
+                } else {
+                    call("NumberSequence.new", [a.value, b.value])
+                }
+            }
+            keypoints => {
+                let items: Vec<String> = keypoints
+                    .iter()
+                    .map(|k| {
+                        if k.envelope == 0.0 {
+                            call("NumberSequenceKeypoint.new", [k.time, k.value])
+                        } else {
+                            call("NumberSequenceKeypoint.new", [k.time, k.value, k.envelope])
+                        }
+                    })
+                    .collect();
+                format!("NumberSequence.new({{{}}})", items.join(", "))
+            }
+        },
+        PropertyValue::ColorSequence(s) => match s.keypoints.as_slice() {
+            [a, b] if a.time == 0.0 && b.time == 1.0 => {
+                let (start, end) = (&a.color, &b.color);
+                if start == end {
+                    format!("ColorSequence.new({})", print_color(start))
+                } else {
+                    format!("ColorSequence.new({}, {})", print_color(start), print_color(end))
+                }
+            }
+            keypoints => {
+                let items: Vec<String> = keypoints
+                    .iter()
+                    .map(|k| {
+                        format!(
+                            "ColorSequenceKeypoint.new({}, {})",
+                            format_number(k.time),
+                            print_color(&k.color)
+                        )
+                    })
+                    .collect();
+                format!("ColorSequence.new({{{}}})", items.join(", "))
+            }
+        },
+        PropertyValue::BrickColor(b) => format!("BrickColor.new({})", print_string(&b.name)),
+        PropertyValue::Font(f) => {
+            let family = match f
+                .family
+                .strip_prefix("rbxasset://fonts/families/")
+                .and_then(|rest| rest.strip_suffix(".json"))
+            {
+                Some(name) if !name.contains('/') => format!("Font.fromName({}", print_string(name)),
+                _ => format!("Font.new({}", print_string(&f.family)),
+            };
+            match (f.weight.as_str(), f.style.as_str()) {
+                ("Regular", "Normal") => format!("{})", family),
+                (weight, "Normal") => format!("{}, Enum.FontWeight.{})", family, weight),
+                (weight, style) => {
+                    format!("{}, Enum.FontWeight.{}, Enum.FontStyle.{})", family, weight, style)
+                }
+            }
+        }
+        PropertyValue::PhysicalProperties(p) => {
+            if p.friction_weight == 1.0 && p.elasticity_weight == 1.0 {
+                call("PhysicalProperties.new", [p.density, p.friction, p.elasticity])
+            } else {
+                call(
+                    "PhysicalProperties.new",
+                    [p.density, p.friction, p.elasticity, p.friction_weight, p.elasticity_weight],
+                )
+            }
+        }
+        PropertyValue::Faces(f) => {
+            let faces = [
+                (f.right, "Right"),
+                (f.top, "Top"),
+                (f.back, "Back"),
+                (f.left, "Left"),
+                (f.bottom, "Bottom"),
+                (f.front, "Front"),
+            ];
+            enum_list("Faces.new", "NormalId", &faces)
+        }
+        PropertyValue::Axes(a) => enum_list("Axes.new", "Axis", &[(a.x, "X"), (a.y, "Y"), (a.z, "Z")]),
+        PropertyValue::Ref(r) => format!("Ref({})", print_string(&r.instance)),
+        PropertyValue::Enum(e) => e.clone(),
+    }
+}
+
+fn call<T: Into<f64> + Display + LowerExp + Copy>(callee: &str, args: impl AsRef<[T]>) -> String {
+    let args: Vec<String> = args.as_ref().iter().map(|a| format_number(*a)).collect();
+    format!("{}({})", callee, args.join(", "))
+}
+
+fn enum_list(callee: &str, enum_type: &str, items: &[(bool, &str)]) -> String {
+    let items: Vec<String> = items
+        .iter()
+        .filter(|(set, _)| *set)
+        .map(|(_, item)| format!("Enum.{}.{}", enum_type, item))
+        .collect();
+    format!("{}({})", callee, items.join(", "))
+}
+
+fn print_color(c: &Color3Wrapper) -> String {
+    print_value(&PropertyValue::Color3(c.clone()))
+}
+
+// The 0-255 channel value if `Color3.fromRGB` reproduces this component exactly.
+fn rgb_byte(component: f32) -> Option<f32> {
+    let byte = (component * 255.0).round();
+    ((0.0..=255.0).contains(&byte) && byte / 255.0 == component).then_some(byte)
+}
+
+/// Shortest text that parses back to the same number: no trailing `.0`, no `-0`,
+/// `math.huge` for infinities, and an exponent (`1e20`, `2.5e-7`) for very large or very
+/// small magnitudes. Works on `f32` components without widening noise.
+pub fn format_number<T: Into<f64> + Display + LowerExp + Copy>(n: T) -> String {
+    let wide: f64 = n.into();
+    if wide.is_nan() {
+        "0 / 0".to_string()
+    } else if wide == f64::INFINITY {
+        "math.huge".to_string()
+    } else if wide == f64::NEG_INFINITY {
+        "-math.huge".to_string()
+    } else if wide == 0.0 {
+        "0".to_string()
+    } else if (1e-5..1e16).contains(&wide.abs()) {
+        n.to_string()
+    } else {
+        format!("{:e}", n)
+    }
+}
+
+/// A double-quoted string, or a `[[long string]]` for multi-line text such as `Source`.
+pub fn print_string(s: &str) -> String {
+    if s.contains('\n') && !s.contains('\r') {
+        // The newline after the opening bracket is dropped by the parser.
+        let level = (0..)
+            .find(|&n| !format!("{}]", s).contains(&format!("]{}]", "=".repeat(n))))
+            .unwrap_or(0);
+        let eq = "=".repeat(level);
+        return format!("[{eq}[\n{s}]{eq}]");
+    }
+
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::eval::Scope;
+    use crate::core::parser::{DslNode, parse_instance_dsl};
+
+    #[test]
+    fn test_format_is_canonical_and_stable() {
+        let input = r#"-- Door
+Size=Vector3.new( 4.0,1,2 )  -- full size
+Anchored = true
+local W = 0x10
+Transparency = .5
+ClassName = Part
+Child 'Knob' {
+  Color = Color3.new(1, 0, 0)
+  ClassName = Part
+}
+CFrame = CFrame.new(0, (10 - 2) / -2, 0) * CFrame.Angles(0, -math.rad(90), 0)
+Child "Empty" {}
+Position = -(-Vector3.one) + Vector3.new(W, 0, 0)
+-- end
+"#;
+        let expected = r#"-- Door
+Anchored = true
+Size = Vector3.new(4, 1, 2) -- full size
+local W = 16
+ClassName = Part
+CFrame = CFrame.new(0, (10 - 2) / -2, 0) * CFrame.Angles(0, -math.rad(90), 0)
+Position = -(-Vector3.one) + Vector3.new(W, 0, 0)
+Transparency = 0.5
+Child "Knob" {
+    ClassName = Part
+    Color = Color3.new(1, 0, 0)
+}
+Child "Empty" {}
+-- end
+"#;
+        let formatted = format_dsl(input).unwrap();
+        assert_eq!(formatted, expected);
+        assert_eq!(format_dsl(&formatted).unwrap(), formatted);
+
+        // Same values; only the positions they were written at moved.
+        fn without_spans(mut node: DslNode) -> DslNode {
+            node.spans.clear();
+            for child in &mut node.children {
+                child.node = without_spans(std::mem::take(&mut child.node));
+            }
+            node
+        }
+        let (original, _) = parse_instance_dsl(input, &Scope::new());
+        let (reparsed, _) = parse_instance_dsl(&formatted, &Scope::new());
+        assert_eq!(without_spans(original), without_spans(reparsed));
+
+        assert!(format_dsl("Size = Vector3.new(1,").is_err());
+    }
+
+    #[test]
+    fn test_print_value_round_trips() {
+        let input = r#"
+            A = Vector3.new(0.1, -2.5, 1e20)
+            B = CFrame.new(1, 2, 3) * CFrame.Angles(0.3, 0.2, 0.1)
+            C = Color3.fromRGB(12, 34, 56)
+            D = Color3.new(0.3, 0.2, 0.1)
+            E = UDim2.new(0.5, -10, 1, 4)
+            F = NumberSequence.new({NumberSequenceKeypoint.new(0, 1), NumberSequenceKeypoint.new(0.5, 0, 0.2), NumberSequenceKeypoint.new(1, 1)})
+            G = ColorSequence.new(Color3.new(1, 0, 0), Color3.new(0, 0, 1))
+            H = Font.fromName("Arial", Enum.FontWeight.Bold, Enum.FontStyle.Italic)
+            I = PhysicalProperties.new(Enum.Material.Wood)
+            J = Faces.new(Enum.NormalId.Top, Enum.NormalId.Front)
+            K = "quote \" backslash \\ tab \t"
+            L = "line one\nline two]]"
+            M = -math.huge
+            N = NumberRange.new(2)
+            O = BrickColor.new(23)
+            P = Rect.new(1, 2, 3, 4)
+            Q = 0.00000025
+            R = 123456789012345678901
+            S = 0.00001
+            T = 1234567890123456
+        "#;
+        let (node, errors) = parse_instance_dsl(input, &Scope::new());
+        assert!(errors.is_empty(), "{:?}", errors);
+        for (key, value) in &node.properties {
+            let printed = print_value(value);
+            let (reparsed, errors) = parse_instance_dsl(&format!("{} = {}", key, printed), &Scope::new());
+            assert!(errors.is_empty(), "{} = {}: {:?}", key, printed, errors);
+            assert_eq!(reparsed.properties.get(key), Some(value), "{} = {}", key, printed);
+        }
+        assert_eq!(print_value(&node.properties["C"]), "Color3.fromRGB(12, 34, 56)");
+
+        let mut instance = Instance::new("Door", "Model", "door");
+        instance.properties = node.properties.clone();
+        instance.children.push(Instance::new("Knob", "Part", "door/Knob"));
+        let (printed, errors) = parse_instance_dsl(&print_instance(&instance), &Scope::new());
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(printed.properties.get("ClassName"), Some(&PropertyValue::String("Model".to_string())));
+        assert_eq!(printed.properties.get("B"), node.properties.get("B"));
+        assert_eq!(printed.children[0].name, "Knob");
+        assert_eq!(print_value(&node.properties["A"]), "Vector3.new(0.1, -2.5, 1e20)");
+        let numbers: Vec<_> = ["Q", "R", "S", "T"].iter().map(|k| print_value(&node.properties[*k])).collect();
+        assert_eq!(numbers, ["2.5e-7", "1.2345678901234568e20", "0.00001", "1234567890123456"]);
+    }
+}
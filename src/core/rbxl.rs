@@ -0,0 +1,853 @@
+use crate::core::datamodel::{
+    AxesWrapper, BrickColorWrapper, CFrameWrapper, Color3Wrapper, ColorSequenceKeypoint,
+    ColorSequenceWrapper, FacesWrapper, FontWrapper, Instance, InstanceRef, NumberRangeWrapper,
+    NumberSequenceKeypoint, NumberSequenceWrapper, PhysicalPropertiesWrapper, PropertyValue,
+    RectWrapper, UDim2Wrapper, UDimWrapper, Vec3Wrapper, Vector2Wrapper,
+};
+use crate::core::rbxlx::{
+    FONT_WEIGHTS, api_name, pack_bits, read_xml, resolve_referents, serialized_name, token_property,
+    token_value,
+};
+use crate::core::reflection::{TypeCategory, database};
+use std::collections::{BTreeMap, HashMap};
+
+// Roblox binary place (`.rbxl`) and model (`.rbxm`) files: a header followed by
+// LZ4-compressed chunks. `INST` lists the instances of one class, `PROP` holds one
+// property for all of them, `PRNT` links children to parents. Instances are numbered
+// by "referents" (here: depth-first order), which `Ref` properties point at.
+
+const MAGIC: &[u8] = b"<roblox!\x89\xff\r\n\x1a\n";
+
+// Property type ids.
+const STRING: u8 = 0x01;
+const BOOL: u8 = 0x02;
+const INT32: u8 = 0x03;
+const FLOAT32: u8 = 0x04;
+const FLOAT64: u8 = 0x05;
+const UDIM: u8 = 0x06;
+const UDIM2: u8 = 0x07;
+const FACES: u8 = 0x09;
+const AXES: u8 = 0x0A;
+const BRICK_COLOR: u8 = 0x0B;
+const COLOR3: u8 = 0x0C;
+const VECTOR2: u8 = 0x0D;
+const VECTOR3: u8 = 0x0E;
+const CFRAME: u8 = 0x10;
+const ENUM: u8 = 0x12;
+const REF: u8 = 0x13;
+const NUMBER_SEQUENCE: u8 = 0x15;
+const COLOR_SEQUENCE: u8 = 0x16;
+const NUMBER_RANGE: u8 = 0x17;
+const RECT: u8 = 0x18;
+const PHYSICAL_PROPERTIES: u8 = 0x19;
+const COLOR3_UINT8: u8 = 0x1A;
+const INT64: u8 = 0x1B;
+const FONT: u8 = 0x20;
+
+/// Serializes `items` and their descendants as a Roblox binary file. References to
+/// instances outside `items` are written as nil.
+pub fn write_binary(items: &[&Instance]) -> Vec<u8> {
+    let mut instances: Vec<(&Instance, i32)> = Vec::new();
+    fn collect<'a>(instance: &'a Instance, parent: i32, instances: &mut Vec<(&'a Instance, i32)>) {
+        let referent = instances.len() as i32;
+        instances.push((instance, parent));
+        for child in &instance.children {
+            collect(child, referent, instances);
+        }
+    }
+    for item in items {
+        collect(item, -1, &mut instances);
+    }
+    let referents: HashMap<&str, i32> =
+        instances.iter().enumerate().map(|(i, (instance, _))| (instance.full_path.as_str(), i as i32)).collect();
+
+    let mut classes: BTreeMap<&str, Vec<i32>> = BTreeMap::new();
+    for (i, (instance, _)) in instances.iter().enumerate() {
+        classes.entry(instance.class_name.as_str()).or_default().push(i as i32);
+    }
+
+    let mut out = MAGIC.to_vec();
+    out.extend(0u16.to_le_bytes());
+    out.extend((classes.len() as i32).to_le_bytes());
+    out.extend((instances.len() as i32).to_le_bytes());
+    out.extend([0; 8]);
+
+    let mut meta = Vec::new();
+    meta.extend(1u32.to_le_bytes());
+    write_string(&mut meta, "ExplicitAutoJoints");
+    write_string(&mut meta, "true");
+    write_chunk(&mut out, b"META", &meta);
+
+    let db = database();
+    for (class_id, (class_name, members)) in classes.iter().enumerate() {
+        let mut chunk = Vec::new();
+        chunk.extend((class_id as u32).to_le_bytes());
+        write_string(&mut chunk, class_name);
+        let is_service = db.class(class_name).is_some_and(|c| c.tags.iter().any(|t| t == "Service"));
+        chunk.push(is_service as u8);
+        chunk.extend((members.len() as u32).to_le_bytes());
+        write_referents(&mut chunk, members);
+        if is_service {
+            chunk.extend(std::iter::repeat_n(1u8, members.len()));
+        }
+        write_chunk(&mut out, b"INST", &chunk);
+    }
+
+    for (class_id, (class_name, members)) in classes.iter().enumerate() {
+        let members: Vec<&Instance> = members.iter().map(|r| instances[*r as usize].0).collect();
+        let mut chunk = Vec::new();
+        chunk.extend((class_id as u32).to_le_bytes());
+        write_string(&mut chunk, "Name");
+        chunk.push(STRING);
+        for instance in &members {
+            write_string(&mut chunk, &instance.name);
+        }
+        write_chunk(&mut out, b"PROP", &chunk);
+
+        let mut keys: Vec<&str> = members
+            .iter()
+            .flat_map(|i| i.properties.keys())
+            .map(String::as_str)
+            .filter(|k| *k != "Name" && *k != "ClassName")
+            .collect();
+        keys.sort();
+        keys.dedup();
+        for key in keys {
+            // The first instance that sets the property decides its type; instances
+            // that don't (or disagree) get the class default or a zero value.
+            let Some(ty) = members.iter().find_map(|i| i.properties.get(key).and_then(|v| value_type(class_name, key, v)))
+            else {
+                continue;
+            };
+            let default = db.default_value(class_name, key).filter(|v| value_type(class_name, key, v) == Some(ty));
+            let values: Vec<Option<&PropertyValue>> = members
+                .iter()
+                .map(|i| i.properties.get(key).filter(|v| value_type(class_name, key, v) == Some(ty)).or(default))
+                .collect();
+
+            let mut chunk = Vec::new();
+            chunk.extend((class_id as u32).to_le_bytes());
+            write_string(&mut chunk, serialized_name(class_name, key));
+            chunk.push(ty);
+            write_values(&mut chunk, ty, &values, &referents);
+            write_chunk(&mut out, b"PROP", &chunk);
+        }
+    }
+
+    let mut parents = vec![0u8];
+    parents.extend((instances.len() as u32).to_le_bytes());
+    let children: Vec<i32> = (0..instances.len() as i32).collect();
+    write_referents(&mut parents, &children);
+    write_referents(&mut parents, &instances.iter().map(|(_, p)| *p).collect::<Vec<_>>());
+    write_chunk(&mut out, b"PRNT", &parents);
+
+    // `END` is never compressed.
+    out.extend(b"END\0");
+    out.extend(0u32.to_le_bytes());
+    out.extend(9u32.to_le_bytes());
+    out.extend(0u32.to_le_bytes());
+    out.extend(b"</roblox>");
+    out
+}
+
+/// The binary type a value is stored as, or `None` for values the format can't hold.
+fn value_type(class_name: &str, key: &str, value: &PropertyValue) -> Option<u8> {
+    let declared = database().property(class_name, key).map(|p| &p.value_type);
+    Some(match value {
+        PropertyValue::String(_) => STRING,
+        PropertyValue::Bool(_) => BOOL,
+        PropertyValue::Number(_) => match declared.map(|t| (t.category, t.name.as_str())) {
+            Some((TypeCategory::Primitive, "int")) => INT32,
+            Some((TypeCategory::Primitive, "int64")) => INT64,
+            Some((TypeCategory::Primitive, "float")) => FLOAT32,
+            _ => FLOAT64,
+        },
+        PropertyValue::Vector3(_) => VECTOR3,
+        PropertyValue::Vector2(_) => VECTOR2,
+        PropertyValue::CFrame(_) => CFRAME,
+        PropertyValue::Color3(_) if serialized_name(class_name, key) == "Color3uint8" => COLOR3_UINT8,
+        PropertyValue::Color3(_) => COLOR3,
+        PropertyValue::UDim2(_) => UDIM2,
+        PropertyValue::UDim(_) => UDIM,
+        PropertyValue::Rect(_) => RECT,
+        PropertyValue::NumberRange(_) => NUMBER_RANGE,
+        PropertyValue::NumberSequence(_) => NUMBER_SEQUENCE,
+        PropertyValue::ColorSequence(_) => COLOR_SEQUENCE,
+        PropertyValue::BrickColor(_) => BRICK_COLOR,
+        PropertyValue::Font(_) => FONT,
+        PropertyValue::PhysicalProperties(_) => PHYSICAL_PROPERTIES,
+        PropertyValue::Faces(_) => FACES,
+        PropertyValue::Axes(_) => AXES,
+        PropertyValue::Ref(_) => REF,
+        PropertyValue::Enum(e) => {
+            token_value(e)?;
+            ENUM
+        }
+    })
+}
+
+/// Writes one value per instance; `None` (and mismatched values) become zero/empty.
+fn write_values(out: &mut Vec<u8>, ty: u8, values: &[Option<&PropertyValue>], referents: &HashMap<&str, i32>) {
+    macro_rules! each {
+        ($pattern:pat => $value:expr, $zero:expr) => {
+            values.iter().map(|v| match v {
+                Some($pattern) => $value,
+                _ => $zero,
+            })
+        };
+    }
+    let floats = |out: &mut Vec<u8>, values: Vec<f32>| write_interleaved(out, values.iter().map(|f| f.to_bits().rotate_left(1).to_be_bytes()));
+    let ints = |out: &mut Vec<u8>, values: Vec<i32>| write_interleaved(out, values.iter().map(|i| zigzag(*i).to_be_bytes()));
+
+    match ty {
+        STRING => {
+            for s in each!(PropertyValue::String(s) => s.as_str(), "") {
+                write_string(out, s);
+            }
+        }
+        BOOL => out.extend(each!(PropertyValue::Bool(b) => *b as u8, 0)),
+        INT32 => ints(out, each!(PropertyValue::Number(n) => *n as i32, 0).collect()),
+        INT64 => write_interleaved(
+            out,
+            each!(PropertyValue::Number(n) => *n as i64, 0).map(|i| ((i << 1) ^ (i >> 63)).to_be_bytes()),
+        ),
+        FLOAT32 => floats(out, each!(PropertyValue::Number(n) => *n as f32, 0.0).collect()),
+        FLOAT64 => {
+            for n in each!(PropertyValue::Number(n) => *n, 0.0) {
+                out.extend(n.to_le_bytes());
+            }
+        }
+        UDIM => {
+            let udims: Vec<(f32, i32)> = each!(PropertyValue::UDim(u) => (u.scale, u.offset), (0.0, 0)).collect();
+            floats(out, udims.iter().map(|u| u.0).collect());
+            ints(out, udims.iter().map(|u| u.1).collect());
+        }
+        UDIM2 => {
+            let udims: Vec<[f32; 4]> =
+                each!(PropertyValue::UDim2(u) => [u.xs, u.ys, u.xo as f32, u.yo as f32], [0.0; 4]).collect();
+            floats(out, udims.iter().map(|u| u[0]).collect());
+            floats(out, udims.iter().map(|u| u[1]).collect());
+            ints(out, udims.iter().map(|u| u[2] as i32).collect());
+            ints(out, udims.iter().map(|u| u[3] as i32).collect());
+        }
+        FACES => out.extend(each!(
+            PropertyValue::Faces(f) => pack_bits(&[f.right, f.top, f.back, f.left, f.bottom, f.front]) as u8,
+            0
+        )),
+        AXES => out.extend(each!(PropertyValue::Axes(a) => pack_bits(&[a.x, a.y, a.z]) as u8, 0)),
+        BRICK_COLOR => write_interleaved(out, each!(PropertyValue::BrickColor(b) => b.number, 194).map(u32::to_be_bytes)),
+        ENUM => write_interleaved(
+            out,
+            each!(PropertyValue::Enum(e) => token_value(e).unwrap_or(0), 0).map(u32::to_be_bytes),
+        ),
+        COLOR3 => {
+            let colors: Vec<[f32; 3]> = each!(PropertyValue::Color3(c) => [c.r, c.g, c.b], [0.0; 3]).collect();
+            for channel in 0..3 {
+                floats(out, colors.iter().map(|c| c[channel]).collect());
+            }
+        }
+        COLOR3_UINT8 => {
+            let colors: Vec<[f32; 3]> = each!(PropertyValue::Color3(c) => [c.r, c.g, c.b], [0.0; 3]).collect();
+            for channel in 0..3 {
+                out.extend(colors.iter().map(|c| (c[channel].clamp(0.0, 1.0) * 255.0).round() as u8));
+            }
+        }
+        VECTOR2 => {
+            let vectors: Vec<[f32; 2]> = each!(PropertyValue::Vector2(v) => [v.x, v.y], [0.0; 2]).collect();
+            for axis in 0..2 {
+                floats(out, vectors.iter().map(|v| v[axis]).collect());
+            }
+        }
+        VECTOR3 => {
+            let vectors: Vec<[f32; 3]> = each!(PropertyValue::Vector3(v) => [v.x, v.y, v.z], [0.0; 3]).collect();
+            for axis in 0..3 {
+                floats(out, vectors.iter().map(|v| v[axis]).collect());
+            }
+        }
+        CFRAME => {
+            let identity = CFrameWrapper::new(0.0, 0.0, 0.0).components;
+            let frames: Vec<[f32; 12]> = each!(PropertyValue::CFrame(cf) => cf.components, identity).collect();
+            // Rotations are always written in full (id 0) rather than as axis-aligned ids.
+            for frame in &frames {
+                out.push(0);
+                for r in &frame[3..] {
+                    out.extend(r.to_le_bytes());
+                }
+            }
+            for axis in 0..3 {
+                floats(out, frames.iter().map(|f| f[axis]).collect());
+            }
+        }
+        REF => {
+            let targets: Vec<i32> =
+                each!(PropertyValue::Ref(r) => referents.get(r.instance.as_str()).copied().unwrap_or(-1), -1).collect();
+            write_referents(out, &targets);
+        }
+        NUMBER_SEQUENCE => {
+            for keypoints in each!(PropertyValue::NumberSequence(s) => s.keypoints.as_slice(), &[]) {
+                out.extend((keypoints.len() as u32).to_le_bytes());
+                for k in keypoints {
+                    out.extend([k.time, k.value, k.envelope].iter().flat_map(|f| f.to_le_bytes()));
+                }
+            }
+        }
+        COLOR_SEQUENCE => {
+            for keypoints in each!(PropertyValue::ColorSequence(s) => s.keypoints.as_slice(), &[]) {
+                out.extend((keypoints.len() as u32).to_le_bytes());
+                for k in keypoints {
+                    let fields = [k.time, k.color.r, k.color.g, k.color.b, 0.0];
+                    out.extend(fields.iter().flat_map(|f| f.to_le_bytes()));
+                }
+            }
+        }
+        NUMBER_RANGE => {
+            for (min, max) in each!(PropertyValue::NumberRange(r) => (r.min, r.max), (0.0, 0.0)) {
+                out.extend(min.to_le_bytes());
+                out.extend(max.to_le_bytes());
+            }
+        }
+        RECT => {
+            let rects: Vec<[f32; 4]> =
+                each!(PropertyValue::Rect(r) => [r.min.x, r.min.y, r.max.x, r.max.y], [0.0; 4]).collect();
+            for field in 0..4 {
+                floats(out, rects.iter().map(|r| r[field]).collect());
+            }
+        }
+        PHYSICAL_PROPERTIES => {
+            for physics in each!(PropertyValue::PhysicalProperties(p) => Some(p), None) {
+                match physics {
+                    Some(p) => {
+                        out.push(1);
+                        let fields = [p.density, p.friction, p.elasticity, p.friction_weight, p.elasticity_weight];
+                        out.extend(fields.iter().flat_map(|f| f.to_le_bytes()));
+                    }
+                    None => out.push(0),
+                }
+            }
+        }
+        FONT => {
+            for font in each!(PropertyValue::Font(f) => Some(f), None) {
+                let (family, weight, style) = match font {
+                    Some(f) => {
+                        let weight = FONT_WEIGHTS.iter().find(|(n, _)| *n == f.weight).map_or(400, |(_, w)| *w);
+                        (f.family.as_str(), weight, (f.style == "Italic") as u8)
+                    }
+                    None => ("", 400, 0),
+                };
+                write_string(out, family);
+                out.extend((weight as u16).to_le_bytes());
+                out.push(style);
+                write_string(out, "");
+            }
+        }
+        _ => unreachable!("value_type returned an unknown type id {ty:#x}"),
+    }
+}
+
+fn write_chunk(out: &mut Vec<u8>, name: &[u8; 4], data: &[u8]) {
+    let compressed = lz4_flex::block::compress(data);
+    out.extend(name);
+    out.extend((compressed.len() as u32).to_le_bytes());
+    out.extend((data.len() as u32).to_le_bytes());
+    out.extend(0u32.to_le_bytes());
+    out.extend(compressed);
+}
+
+fn write_string(out: &mut Vec<u8>, s: &str) {
+    out.extend((s.len() as u32).to_le_bytes());
+    out.extend(s.as_bytes());
+}
+
+fn zigzag(i: i32) -> u32 {
+    ((i << 1) ^ (i >> 31)) as u32
+}
+
+fn unzigzag(u: u32) -> i32 {
+    ((u >> 1) as i32) ^ -((u & 1) as i32)
+}
+
+/// Big-endian values stored byte plane by byte plane: every first byte, then every
+/// second byte, and so on. This is what makes the chunks compress well.
+fn write_interleaved<const N: usize>(out: &mut Vec<u8>, values: impl Iterator<Item = [u8; N]>) {
+    let values: Vec<[u8; N]> = values.collect();
+    for byte in 0..N {
+        out.extend(values.iter().map(|v| v[byte]));
+    }
+}
+
+/// Referent lists are delta-encoded before interleaving.
+fn write_referents(out: &mut Vec<u8>, referents: &[i32]) {
+    let mut previous = 0;
+    let deltas = referents.iter().map(|r| {
+        let delta = r - previous;
+        previous = *r;
+        zigzag(delta).to_be_bytes()
+    });
+    write_interleaved(out, deltas);
+}
+
+// --- Reading ---
+
+struct Cursor<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Cursor { data, position: 0 }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.position.checked_add(len).filter(|end| *end <= self.data.len());
+        let end = end.ok_or("unexpected end of file")?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.bytes(N)?.try_into().unwrap())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        self.array().map(u32::from_le_bytes)
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        self.array().map(f32::from_le_bytes)
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.u32()? as usize;
+        Ok(String::from_utf8_lossy(self.bytes(len)?).into_owned())
+    }
+
+    fn interleaved<const N: usize>(&mut self, count: usize) -> Result<Vec<[u8; N]>, String> {
+        let bytes = self.bytes(count * N)?;
+        Ok((0..count).map(|i| std::array::from_fn(|byte| bytes[byte * count + i])).collect())
+    }
+
+    fn floats(&mut self, count: usize) -> Result<Vec<f32>, String> {
+        let values = self.interleaved(count)?;
+        Ok(values.into_iter().map(|v| f32::from_bits(u32::from_be_bytes(v).rotate_right(1))).collect())
+    }
+
+    fn ints(&mut self, count: usize) -> Result<Vec<i32>, String> {
+        Ok(self.interleaved(count)?.into_iter().map(|v| unzigzag(u32::from_be_bytes(v))).collect())
+    }
+
+    fn referents(&mut self, count: usize) -> Result<Vec<i32>, String> {
+        let mut current = 0;
+        Ok(self
+            .ints(count)?
+            .into_iter()
+            .map(|delta| {
+                current += delta;
+                current
+            })
+            .collect())
+    }
+}
+
+/// An instance before the tree is assembled.
+struct Node {
+    class_name: String,
+    name: String,
+    properties: HashMap<String, PropertyValue>,
+}
+
+/// Parses a `.rbxl`/`.rbxm` file into its top-level instances, placed under
+/// `parent_full_path`. Like `read_xml`, `Ref` properties become `full_path`s and
+/// unsupported property types are skipped.
+pub fn read_binary(bytes: &[u8], parent_full_path: &str) -> Result<Vec<Instance>, String> {
+    let mut file = Cursor::new(bytes);
+    if file.bytes(MAGIC.len())? != MAGIC {
+        return Err("not a Roblox binary file".to_string());
+    }
+    file.bytes(2 + 4 + 4 + 8)?;
+
+    let mut classes: HashMap<u32, (String, Vec<i32>)> = HashMap::new();
+    let mut nodes: BTreeMap<i32, Node> = BTreeMap::new();
+    let mut links: Vec<(i32, i32)> = Vec::new();
+    loop {
+        let name = file.array::<4>()?;
+        let compressed_len = file.u32()? as usize;
+        let len = file.u32()? as usize;
+        file.u32()?;
+        let data = if compressed_len == 0 {
+            file.bytes(len)?.to_vec()
+        } else {
+            let compressed = file.bytes(compressed_len)?;
+            if compressed.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+                return Err("zstd-compressed chunks are not supported".to_string());
+            }
+            lz4_flex::block::decompress(compressed, len).map_err(|e| format!("corrupt chunk: {}", e))?
+        };
+        let mut chunk = Cursor::new(&data);
+
+        match &name {
+            b"INST" => {
+                let class_id = chunk.u32()?;
+                let class_name = chunk.string()?;
+                chunk.u8()?;
+                let count = chunk.u32()? as usize;
+                let referents = chunk.referents(count)?;
+                for referent in &referents {
+                    let node = Node { class_name: class_name.clone(), name: class_name.clone(), properties: HashMap::new() };
+                    nodes.insert(*referent, node);
+                }
+                classes.insert(class_id, (class_name, referents));
+            }
+            b"PROP" => {
+                let class_id = chunk.u32()?;
+                let name = chunk.string()?;
+                let ty = chunk.u8()?;
+                let (class_name, referents) = classes.get(&class_id).ok_or("PROP chunk for an unknown class")?;
+                let name = api_name(class_name, &name).to_string();
+                let Some(values) = read_values(&mut chunk, ty, referents.len(), class_name, &name)? else {
+                    continue;
+                };
+                for (referent, value) in referents.iter().zip(values) {
+                    let node = nodes.get_mut(referent).unwrap();
+                    match value {
+                        Some(PropertyValue::String(s)) if name == "Name" => node.name = s,
+                        Some(value) => {
+                            node.properties.insert(name.clone(), value);
+                        }
+                        None => {}
+                    }
+                }
+            }
+            b"PRNT" => {
+                chunk.u8()?;
+                let count = chunk.u32()? as usize;
+                let children = chunk.referents(count)?;
+                let parents = chunk.referents(count)?;
+                links = children.into_iter().zip(parents).collect();
+            }
+            b"END\0" => break,
+            // META, SSTR, SIGN, ...
+            _ => {}
+        }
+    }
+
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    for (child, parent) in &links {
+        children.entry(*parent).or_default().push(*child);
+    }
+    let mut paths = HashMap::new();
+    let mut items: Vec<Instance> = children
+        .get(&-1)
+        .map(|roots| roots.iter().filter_map(|r| build(*r, parent_full_path, &mut nodes, &children, &mut paths)).collect())
+        .unwrap_or_default();
+    for item in &mut items {
+        resolve_referents(item, &paths);
+    }
+    Ok(items)
+}
+
+/// Reads a binary or XML Roblox file, whatever its extension says.
+pub fn read_roblox_file(content: &[u8], parent_full_path: &str) -> Result<Vec<Instance>, String> {
+    if content.starts_with(MAGIC) {
+        return read_binary(content, parent_full_path);
+    }
+    let xml = std::str::from_utf8(content).map_err(|_| "not a Roblox binary or XML file".to_string())?;
+    read_xml(xml, parent_full_path)
+}
+
+fn build(
+    referent: i32,
+    parent_full_path: &str,
+    nodes: &mut BTreeMap<i32, Node>,
+    children: &HashMap<i32, Vec<i32>>,
+    paths: &mut HashMap<String, String>,
+) -> Option<Instance> {
+    let node = nodes.remove(&referent)?;
+    let mut instance = Instance::new(&node.name, &node.class_name, &format!("{}/{}", parent_full_path, referent));
+    instance.properties = node.properties;
+    instance.full_path = format!("{}/{}", parent_full_path, instance.name);
+    paths.insert(referent.to_string(), instance.full_path.clone());
+    for child in children.get(&referent).into_iter().flatten() {
+        if let Some(child) = build(*child, &instance.full_path, nodes, children, paths) {
+            instance.children.push(child);
+        }
+    }
+    Some(instance)
+}
+
+/// One value per instance, or `None` for types LuDock doesn't represent.
+fn read_values(
+    chunk: &mut Cursor,
+    ty: u8,
+    count: usize,
+    class_name: &str,
+    name: &str,
+) -> Result<Option<Vec<Option<PropertyValue>>>, String> {
+    let values: Vec<Option<PropertyValue>> = match ty {
+        STRING => (0..count).map(|_| chunk.string().map(|s| Some(PropertyValue::String(s)))).collect::<Result<_, _>>()?,
+        BOOL => chunk.bytes(count)?.iter().map(|b| Some(PropertyValue::Bool(*b != 0))).collect(),
+        INT32 => chunk.ints(count)?.into_iter().map(|i| Some(PropertyValue::Number(i as f64))).collect(),
+        INT64 => chunk
+            .interleaved::<8>(count)?
+            .into_iter()
+            .map(|v| {
+                let u = u64::from_be_bytes(v);
+                let i = ((u >> 1) as i64) ^ -((u & 1) as i64);
+                Some(PropertyValue::Number(i as f64))
+            })
+            .collect(),
+        FLOAT32 => chunk.floats(count)?.into_iter().map(|f| Some(PropertyValue::Number(f as f64))).collect(),
+        FLOAT64 => (0..count)
+            .map(|_| chunk.array().map(|b| Some(PropertyValue::Number(f64::from_le_bytes(b)))))
+            .collect::<Result<_, _>>()?,
+        UDIM => {
+            let (scales, offsets) = (chunk.floats(count)?, chunk.ints(count)?);
+            scales
+                .into_iter()
+                .zip(offsets)
+                .map(|(scale, offset)| Some(PropertyValue::UDim(UDimWrapper { scale, offset })))
+                .collect()
+        }
+        UDIM2 => {
+            let (xs, ys) = (chunk.floats(count)?, chunk.floats(count)?);
+            let (xo, yo) = (chunk.ints(count)?, chunk.ints(count)?);
+            (0..count)
+                .map(|i| Some(PropertyValue::UDim2(UDim2Wrapper { xs: xs[i], xo: xo[i], ys: ys[i], yo: yo[i] })))
+                .collect()
+        }
+        FACES => chunk
+            .bytes(count)?
+            .iter()
+            .map(|bits| {
+                let set = |i: u32| bits & (1 << i) != 0;
+                Some(PropertyValue::Faces(FacesWrapper {
+                    right: set(0),
+                    top: set(1),
+                    back: set(2),
+                    left: set(3),
+                    bottom: set(4),
+                    front: set(5),
+                }))
+            })
+            .collect(),
+        AXES => chunk
+            .bytes(count)?
+            .iter()
+            .map(|bits| Some(PropertyValue::Axes(AxesWrapper { x: bits & 1 != 0, y: bits & 2 != 0, z: bits & 4 != 0 })))
+            .collect(),
+        BRICK_COLOR => chunk
+            .interleaved(count)?
+            .into_iter()
+            .map(|v| BrickColorWrapper::from_number(u32::from_be_bytes(v)).map(PropertyValue::BrickColor))
+            .collect(),
+        ENUM => chunk
+            .interleaved(count)?
+            .into_iter()
+            .map(|v| token_property(class_name, name, u32::from_be_bytes(v)))
+            .collect(),
+        COLOR3 => {
+            let (r, g, b) = (chunk.floats(count)?, chunk.floats(count)?, chunk.floats(count)?);
+            (0..count).map(|i| Some(PropertyValue::Color3(Color3Wrapper::new(r[i], g[i], b[i])))).collect()
+        }
+        COLOR3_UINT8 => {
+            let (r, g, b) = (chunk.bytes(count)?, chunk.bytes(count)?, chunk.bytes(count)?);
+            (0..count)
+                .map(|i| Some(PropertyValue::Color3(Color3Wrapper::from_rgb(r[i] as f32, g[i] as f32, b[i] as f32))))
+                .collect()
+        }
+        VECTOR2 => {
+            let (x, y) = (chunk.floats(count)?, chunk.floats(count)?);
+            (0..count).map(|i| Some(PropertyValue::Vector2(Vector2Wrapper { x: x[i], y: y[i] }))).collect()
+        }
+        VECTOR3 => {
+            let (x, y, z) = (chunk.floats(count)?, chunk.floats(count)?, chunk.floats(count)?);
+            (0..count).map(|i| Some(PropertyValue::Vector3(Vec3Wrapper { x: x[i], y: y[i], z: z[i] }))).collect()
+        }
+        CFRAME => {
+            let mut rotations = Vec::with_capacity(count);
+            for _ in 0..count {
+                let id = chunk.u8()?;
+                let rotation: [f32; 9] = if id == 0 {
+                    let mut r = [0.0; 9];
+                    for component in &mut r {
+                        *component = chunk.f32()?;
+                    }
+                    r
+                } else {
+                    basic_rotation(id).ok_or_else(|| format!("invalid CFrame rotation id {:#x}", id))?
+                };
+                rotations.push(rotation);
+            }
+            let (x, y, z) = (chunk.floats(count)?, chunk.floats(count)?, chunk.floats(count)?);
+            (0..count)
+                .map(|i| {
+                    let mut components = [x[i], y[i], z[i], 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0];
+                    components[3..].copy_from_slice(&rotations[i]);
+                    Some(PropertyValue::CFrame(CFrameWrapper::from_components(components)))
+                })
+                .collect()
+        }
+        REF => chunk
+            .referents(count)?
+            .into_iter()
+            .map(|r| (r >= 0).then(|| PropertyValue::Ref(InstanceRef { instance: r.to_string() })))
+            .collect(),
+        NUMBER_SEQUENCE => (0..count)
+            .map(|_| {
+                let len = chunk.u32()?;
+                let keypoints = (0..len)
+                    .map(|_| Ok(NumberSequenceKeypoint { time: chunk.f32()?, value: chunk.f32()?, envelope: chunk.f32()? }))
+                    .collect::<Result<_, String>>()?;
+                Ok(Some(PropertyValue::NumberSequence(NumberSequenceWrapper { keypoints })))
+            })
+            .collect::<Result<_, String>>()?,
+        COLOR_SEQUENCE => (0..count)
+            .map(|_| {
+                let len = chunk.u32()?;
+                let keypoints = (0..len)
+                    .map(|_| {
+                        let time = chunk.f32()?;
+                        let color = Color3Wrapper::new(chunk.f32()?, chunk.f32()?, chunk.f32()?);
+                        chunk.f32()?; // envelope, unused by ColorSequence
+                        Ok(ColorSequenceKeypoint { time, color })
+                    })
+                    .collect::<Result<_, String>>()?;
+                Ok(Some(PropertyValue::ColorSequence(ColorSequenceWrapper { keypoints })))
+            })
+            .collect::<Result<_, String>>()?,
+        NUMBER_RANGE => (0..count)
+            .map(|_| Ok(Some(PropertyValue::NumberRange(NumberRangeWrapper { min: chunk.f32()?, max: chunk.f32()? }))))
+            .collect::<Result<_, String>>()?,
+        RECT => {
+            let fields = [chunk.floats(count)?, chunk.floats(count)?, chunk.floats(count)?, chunk.floats(count)?];
+            (0..count)
+                .map(|i| {
+                    Some(PropertyValue::Rect(RectWrapper {
+                        min: Vector2Wrapper { x: fields[0][i], y: fields[1][i] },
+                        max: Vector2Wrapper { x: fields[2][i], y: fields[3][i] },
+                    }))
+                })
+                .collect()
+        }
+        PHYSICAL_PROPERTIES => (0..count)
+            .map(|_| {
+                let flag = chunk.u8()?;
+                if flag & 1 == 0 {
+                    return Ok(None);
+                }
+                let physics = PhysicalPropertiesWrapper {
+                    density: chunk.f32()?,
+                    friction: chunk.f32()?,
+                    elasticity: chunk.f32()?,
+                    friction_weight: chunk.f32()?,
+                    elasticity_weight: chunk.f32()?,
+                };
+                if flag & 2 != 0 {
+                    chunk.f32()?; // AcousticAbsorption
+                }
+                Ok(Some(PropertyValue::PhysicalProperties(physics)))
+            })
+            .collect::<Result<_, String>>()?,
+        FONT => (0..count)
+            .map(|_| {
+                let family = chunk.string()?;
+                let weight = u16::from_le_bytes(chunk.array()?) as u32;
+                let style = chunk.u8()?;
+                chunk.string()?; // cached face id
+                Ok(Some(PropertyValue::Font(FontWrapper {
+                    family,
+                    weight: FONT_WEIGHTS.iter().find(|(_, w)| *w == weight).map_or("Regular", |(n, _)| n).to_string(),
+                    style: if style == 1 { "Italic" } else { "Normal" }.to_string(),
+                })))
+            })
+            .collect::<Result<_, String>>()?,
+        _ => return Ok(None),
+    };
+    Ok(Some(values))
+}
+
+/// Axis-aligned rotations are stored as an id encoding two of the matrix columns,
+/// `RightVector` and `UpVector`, as normal ids (+X, +Y, +Z, -X, -Y, -Z); the third
+/// column is their cross product. Returns the matrix row by row.
+fn basic_rotation(id: u8) -> Option<[f32; 9]> {
+    let normal = |n: u8| {
+        let axis = [glam::Vec3::X, glam::Vec3::Y, glam::Vec3::Z][n as usize % 3];
+        if n < 3 { axis } else { -axis }
+    };
+    let id = id.checked_sub(1).filter(|id| *id < 36)?;
+    let (x, y) = (normal(id / 6), normal(id % 6));
+    if x.dot(y) != 0.0 {
+        return None;
+    }
+    let z = x.cross(y);
+    Some([x.x, y.x, z.x, x.y, y.y, z.y, x.z, y.z, z.z])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_value;
+
+    #[test]
+    fn test_binary_round_trip() {
+        let mut model = Instance::new("Door", "Model", "door");
+        model.full_path = "game/Workspace/Door".to_string();
+        let mut parts = Vec::new();
+        for (i, name) in ["Frame", "Panel"].iter().enumerate() {
+            let mut part = Instance::new(name, "Part", name);
+            part.full_path = format!("game/Workspace/Door/{}", name);
+            part.properties.insert("Size".to_string(), parse_value("Vector3.new(6, 8, 1)").unwrap());
+            if i == 0 {
+                for (key, value) in [
+                    ("CFrame", "CFrame.new(0, 4, 0) * CFrame.Angles(0, 0.5, 0)"),
+                    ("Color", "Color3.fromRGB(200, 30, 30)"),
+                    ("Material", "Enum.Material.Wood"),
+                    ("Transparency", "0.25"),
+                    ("CustomPhysicalProperties", "PhysicalProperties.new(0.7, 0.3, 0.5, 1, 1)"),
+                ] {
+                    part.properties.insert(key.to_string(), parse_value(value).unwrap());
+                }
+            }
+            parts.push(part);
+        }
+        model.properties.insert(
+            "PrimaryPart".to_string(),
+            PropertyValue::Ref(InstanceRef { instance: "game/Workspace/Door/Panel".to_string() }),
+        );
+        model.children = parts.clone();
+
+        let bytes = write_binary(&[&model]);
+        assert!(bytes.starts_with(MAGIC));
+        assert!(bytes.ends_with(b"</roblox>"));
+
+        let items = read_binary(&bytes, "game/Workspace").unwrap();
+        assert_eq!(items.len(), 1);
+        let door = &items[0];
+        assert_eq!((door.name.as_str(), door.class_name.as_str()), ("Door", "Model"));
+        assert_eq!(door.properties["PrimaryPart"], model.properties["PrimaryPart"]);
+        assert_eq!(door.children[0].properties, parts[0].properties);
+        // The panel doesn't set the frame's properties; it gets the class defaults.
+        let panel = &door.children[1];
+        assert_eq!(panel.properties["Size"], parts[1].properties["Size"]);
+        assert_eq!(panel.properties.get("Material"), database().default_value("Part", "Material"));
+        assert!(!panel.properties.contains_key("CustomPhysicalProperties"));
+
+        assert_eq!(basic_rotation(0x02), Some([1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]));
+    }
+
+    #[test]
+    fn test_basic_rotation_ids_are_columns() {
+        // 0x03: RightVector +X, UpVector +Z, a quarter turn around X
+        assert_eq!(basic_rotation(0x03), Some([1.0, 0.0, 0.0, 0.0, 0.0, -1.0, 0.0, 1.0, 0.0]));
+        let PropertyValue::CFrame(turn) = parse_value("CFrame.Angles(math.pi / 2, 0, 0)").unwrap() else { panic!() };
+        for (decoded, expected) in basic_rotation(0x03).unwrap().iter().zip(&turn.components[3..]) {
+            assert!((decoded - expected).abs() < 1e-6);
+        }
+        assert_eq!(basic_rotation(0x01), None); // +X twice
+    }
+}
@@ -0,0 +1,592 @@
+use crate::core::datamodel::{
+    AxesWrapper, BrickColorWrapper, CFrameWrapper, Color3Wrapper, ColorSequenceKeypoint,
+    ColorSequenceWrapper, FacesWrapper, FontWrapper, Instance, InstanceRef, NumberRangeWrapper,
+    NumberSequenceKeypoint, NumberSequenceWrapper, PhysicalPropertiesWrapper, PropertyValue,
+    RectWrapper, UDim2Wrapper, UDimWrapper, Vec3Wrapper, Vector2Wrapper,
+};
+use crate::core::reflection::{TypeCategory, database};
+use quick_xml::Reader;
+use quick_xml::escape::{escape, resolve_predefined_entity};
+use quick_xml::events::{BytesStart, Event};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+// Roblox XML place (`.rbxlx`) and model (`.rbxmx`) files. Each `<Item>` is an instance
+// with a `referent` id and typed `<Properties>`; `Ref` properties point at referents.
+
+/// Properties whose serialized name differs from the API name.
+const SERIALIZED_NAMES: &[(&str, &str, &str)] = &[
+    // (class, property, serialized name)
+    ("BasePart", "Size", "size"),
+    ("BasePart", "Color", "Color3uint8"),
+    ("Part", "Shape", "shape"),
+];
+
+// `FontWeight` items, which Roblox files store as numbers.
+pub const FONT_WEIGHTS: &[(&str, u32)] = &[
+    ("Thin", 100),
+    ("ExtraLight", 200),
+    ("Light", 300),
+    ("Regular", 400),
+    ("Medium", 500),
+    ("SemiBold", 600),
+    ("Bold", 700),
+    ("ExtraBold", 800),
+    ("Heavy", 900),
+];
+
+/// The referent written for an instance, derived from its deterministic UUID.
+pub fn referent(instance: &Instance) -> String {
+    format!("RBX{}", instance.id.simple()).to_uppercase()
+}
+
+/// Serializes `items` and their descendants as a Roblox XML document. References to
+/// instances outside `items` are written as `null`.
+pub fn write_xml(items: &[&Instance]) -> String {
+    let mut referents = HashMap::new();
+    fn collect<'a>(instance: &'a Instance, referents: &mut HashMap<&'a str, String>) {
+        referents.insert(instance.full_path.as_str(), referent(instance));
+        for child in &instance.children {
+            collect(child, referents);
+        }
+    }
+    for item in items {
+        collect(item, &mut referents);
+    }
+
+    let mut out = String::from(
+        "<roblox xmlns:xmime=\"http://www.w3.org/2005/05/xmlmime\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:noNamespaceSchemaLocation=\"http://www.roblox.com/roblox.xsd\" version=\"4\">\n",
+    );
+    out.push_str("\t<External>null</External>\n\t<External>nil</External>\n");
+    for item in items {
+        write_item(item, 1, &referents, &mut out);
+    }
+    out.push_str("</roblox>\n");
+    out
+}
+
+fn write_item(instance: &Instance, depth: usize, referents: &HashMap<&str, String>, out: &mut String) {
+    let indent = "\t".repeat(depth);
+    let _ = writeln!(
+        out,
+        "{}<Item class=\"{}\" referent=\"{}\">",
+        indent,
+        escape(instance.class_name.as_str()),
+        referent(instance)
+    );
+    let _ = writeln!(out, "{}\t<Properties>", indent);
+    let _ = writeln!(out, "{}\t\t<string name=\"Name\">{}</string>", indent, escape(instance.name.as_str()));
+
+    let mut keys: Vec<&String> = instance.properties.keys().collect();
+    keys.sort();
+    for key in keys {
+        if key == "Name" || key == "ClassName" {
+            continue;
+        }
+        let value = &instance.properties[key];
+        if let Some(element) = property_element(&instance.class_name, key, value, referents) {
+            let _ = writeln!(out, "{}\t\t{}", indent, element);
+        }
+    }
+    let _ = writeln!(out, "{}\t</Properties>", indent);
+    for child in &instance.children {
+        write_item(child, depth + 1, referents, out);
+    }
+    let _ = writeln!(out, "{}</Item>", indent);
+}
+
+/// One `<type name="...">...</type>` element, or `None` for values XML can't hold.
+fn property_element(
+    class_name: &str,
+    key: &str,
+    value: &PropertyValue,
+    referents: &HashMap<&str, String>,
+) -> Option<String> {
+    let db = database();
+    let declared = db.property(class_name, key).map(|p| &p.value_type);
+    let name = serialized_name(class_name, key);
+    let fields = |pairs: &[(&str, String)]| -> String {
+        pairs.iter().map(|(tag, v)| format!("<{tag}>{v}</{tag}>")).collect()
+    };
+
+    let (ty, body) = match value {
+        PropertyValue::String(s) => match declared {
+            Some(t) if t.category == TypeCategory::DataType && t.name == "Content" => {
+                let url = if s.is_empty() { "<null></null>".to_string() } else { format!("<url>{}</url>", escape(s.as_str())) };
+                ("Content", url)
+            }
+            _ if key == "Source" => ("ProtectedString", escape(s.as_str()).into_owned()),
+            _ => ("string", escape(s.as_str()).into_owned()),
+        },
+        PropertyValue::Bool(b) => ("bool", b.to_string()),
+        PropertyValue::Number(n) => match declared.map(|t| (t.category, t.name.as_str())) {
+            Some((TypeCategory::Primitive, "int")) => ("int", format!("{}", *n as i64)),
+            Some((TypeCategory::Primitive, "int64")) => ("int64", format!("{}", *n as i64)),
+            Some((TypeCategory::Primitive, "float")) => ("float", number(*n as f32)),
+            _ => ("double", number_f64(*n)),
+        },
+        PropertyValue::Vector3(v) => ("Vector3", fields(&[("X", number(v.x)), ("Y", number(v.y)), ("Z", number(v.z))])),
+        PropertyValue::Vector2(v) => ("Vector2", fields(&[("X", number(v.x)), ("Y", number(v.y))])),
+        PropertyValue::CFrame(cf) => {
+            const TAGS: [&str; 12] = ["X", "Y", "Z", "R00", "R01", "R02", "R10", "R11", "R12", "R20", "R21", "R22"];
+            let pairs: Vec<(&str, String)> = TAGS.iter().zip(cf.components).map(|(t, c)| (*t, number(c))).collect();
+            ("CoordinateFrame", fields(&pairs))
+        }
+        PropertyValue::Color3(c) if name == "Color3uint8" => {
+            let byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u32;
+            let packed = 0xFF00_0000 | (byte(c.r) << 16) | (byte(c.g) << 8) | byte(c.b);
+            ("Color3uint8", packed.to_string())
+        }
+        PropertyValue::Color3(c) => ("Color3", fields(&[("R", number(c.r)), ("G", number(c.g)), ("B", number(c.b))])),
+        PropertyValue::UDim2(u) => (
+            "UDim2",
+            fields(&[("XS", number(u.xs)), ("XO", u.xo.to_string()), ("YS", number(u.ys)), ("YO", u.yo.to_string())]),
+        ),
+        PropertyValue::UDim(u) => ("UDim", fields(&[("S", number(u.scale)), ("O", u.offset.to_string())])),
+        PropertyValue::Rect(r) => (
+            "Rect2D",
+            format!(
+                "<min>{}</min><max>{}</max>",
+                fields(&[("X", number(r.min.x)), ("Y", number(r.min.y))]),
+                fields(&[("X", number(r.max.x)), ("Y", number(r.max.y))])
+            ),
+        ),
+        PropertyValue::NumberRange(r) => ("NumberRange", format!("{} {} ", number(r.min), number(r.max))),
+        PropertyValue::NumberSequence(s) => (
+            "NumberSequence",
+            s.keypoints
+                .iter()
+                .map(|k| format!("{} {} {} ", number(k.time), number(k.value), number(k.envelope)))
+                .collect(),
+        ),
+        PropertyValue::ColorSequence(s) => (
+            "ColorSequence",
+            s.keypoints
+                .iter()
+                .map(|k| format!("{} {} {} {} 0 ", number(k.time), number(k.color.r), number(k.color.g), number(k.color.b)))
+                .collect(),
+        ),
+        PropertyValue::BrickColor(b) => ("BrickColor", b.number.to_string()),
+        PropertyValue::Font(f) => {
+            let weight = FONT_WEIGHTS.iter().find(|(n, _)| *n == f.weight).map_or(400, |(_, w)| *w);
+            (
+                "Font",
+                format!(
+                    "<Family><url>{}</url></Family><Weight>{}</Weight><Style>{}</Style>",
+                    escape(f.family.as_str()),
+                    weight,
+                    escape(f.style.as_str())
+                ),
+            )
+        }
+        PropertyValue::PhysicalProperties(p) => (
+            "PhysicalProperties",
+            fields(&[
+                ("CustomPhysics", "true".to_string()),
+                ("Density", number(p.density)),
+                ("Friction", number(p.friction)),
+                ("Elasticity", number(p.elasticity)),
+                ("FrictionWeight", number(p.friction_weight)),
+                ("ElasticityWeight", number(p.elasticity_weight)),
+            ]),
+        ),
+        PropertyValue::Faces(f) => {
+            let bits = [f.right, f.top, f.back, f.left, f.bottom, f.front];
+            ("Faces", format!("<faces>{}</faces>", pack_bits(&bits)))
+        }
+        PropertyValue::Axes(a) => ("Axes", format!("<axes>{}</axes>", pack_bits(&[a.x, a.y, a.z]))),
+        PropertyValue::Ref(r) => ("Ref", referents.get(r.instance.as_str()).cloned().unwrap_or_else(|| "null".to_string())),
+        PropertyValue::Enum(e) => ("token", token_value(e)?.to_string()),
+    };
+    Some(format!("<{ty} name=\"{}\">{body}</{ty}>", escape(name)))
+}
+
+pub fn serialized_name<'a>(class_name: &str, key: &'a str) -> &'a str {
+    let db = database();
+    SERIALIZED_NAMES
+        .iter()
+        .find(|(class, property, _)| *property == key && db.is_a(class_name, class))
+        .map_or(key, |(_, _, serialized)| serialized)
+}
+
+pub fn api_name<'a>(class_name: &str, serialized: &'a str) -> &'a str {
+    let db = database();
+    SERIALIZED_NAMES
+        .iter()
+        .find(|(class, _, name)| *name == serialized && db.is_a(class_name, class))
+        .map_or(serialized, |(_, property, _)| property)
+}
+
+/// The numeric value of an enum item such as `Enum.Material.Wood`.
+pub fn token_value(item: &str) -> Option<u32> {
+    let mut parts = item.split('.').skip(1);
+    let (enum_name, item) = (parts.next()?, parts.next()?);
+    let descriptor = database().enum_descriptor(enum_name)?;
+    descriptor.items.iter().find(|i| i.name == item).map(|i| i.value)
+}
+
+/// A token read from a file: the enum item if the property is enum-typed, otherwise
+/// the plain number.
+pub fn token_property(class_name: &str, name: &str, value: u32) -> Option<PropertyValue> {
+    let db = database();
+    let ty = db.property(class_name, name).map(|p| &p.value_type);
+    match ty.filter(|t| t.category == TypeCategory::Enum) {
+        Some(ty) => {
+            let descriptor = db.enum_descriptor(&ty.name)?;
+            let item = descriptor.items.iter().find(|i| i.value == value)?;
+            Some(PropertyValue::Enum(format!("Enum.{}.{}", descriptor.name, item.name)))
+        }
+        None => Some(PropertyValue::Number(value as f64)),
+    }
+}
+
+pub fn pack_bits(bits: &[bool]) -> u32 {
+    bits.iter().enumerate().filter(|(_, set)| **set).map(|(i, _)| 1 << i).sum()
+}
+
+/// `f32` components in their shortest round-trip form.
+fn number(n: f32) -> String {
+    if n.is_finite() { n.to_string() } else { number_f64(n as f64) }
+}
+
+fn number_f64(n: f64) -> String {
+    if n.is_nan() {
+        "NAN".to_string()
+    } else if n == f64::INFINITY {
+        "INF".to_string()
+    } else if n == f64::NEG_INFINITY {
+        "-INF".to_string()
+    } else {
+        n.to_string()
+    }
+}
+
+// --- Reading ---
+
+/// A parsed XML element; only what Roblox files use.
+#[derive(Debug, Default)]
+struct Element {
+    tag: String,
+    attributes: Vec<(String, String)>,
+    text: String,
+    children: Vec<Element>,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+    }
+
+    fn child(&self, tag: &str) -> Option<&Element> {
+        self.children.iter().find(|c| c.tag == tag)
+    }
+
+    fn number(&self, tag: &str) -> Option<f32> {
+        parse_number(&self.child(tag)?.text)
+    }
+}
+
+fn parse_number(text: &str) -> Option<f32> {
+    match text.trim() {
+        "INF" | "inf" => Some(f32::INFINITY),
+        "-INF" | "-inf" => Some(f32::NEG_INFINITY),
+        "NAN" | "nan" => Some(f32::NAN),
+        t => t.parse().ok(),
+    }
+}
+
+fn start_element(start: &BytesStart) -> Result<Element, String> {
+    let mut element = Element {
+        tag: String::from_utf8_lossy(start.name().as_ref()).into_owned(),
+        ..Element::default()
+    };
+    for attribute in start.attributes() {
+        let attribute = attribute.map_err(|e| e.to_string())?;
+        let key = String::from_utf8_lossy(attribute.key.as_ref()).into_owned();
+        let value = attribute.unescape_value().map_err(|e| e.to_string())?.into_owned();
+        element.attributes.push((key, value));
+    }
+    Ok(element)
+}
+
+fn parse_document(xml: &str) -> Result<Element, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut stack = vec![Element::default()];
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| format!("XML error at byte {}: {}", reader.error_position(), e))?;
+        match event {
+            Event::Start(start) => stack.push(start_element(&start)?),
+            Event::Empty(start) => {
+                let element = start_element(&start)?;
+                stack.last_mut().unwrap().children.push(element);
+            }
+            Event::End(_) => {
+                let element = stack.pop().unwrap();
+                stack.last_mut().ok_or("unbalanced XML")?.children.push(element);
+            }
+            Event::Text(text) => {
+                let text = text.xml_content().map_err(|e| e.to_string())?;
+                stack.last_mut().unwrap().text.push_str(&text);
+            }
+            Event::CData(data) => {
+                let data = data.into_inner();
+                stack.last_mut().unwrap().text.push_str(&String::from_utf8_lossy(&data));
+            }
+            Event::GeneralRef(reference) => {
+                let resolved = match reference.resolve_char_ref().map_err(|e| e.to_string())? {
+                    Some(c) => c.to_string(),
+                    None => {
+                        let name = reference.decode().map_err(|e| e.to_string())?;
+                        resolve_predefined_entity(&name)
+                            .ok_or_else(|| format!("unknown entity `&{};`", name))?
+                            .to_string()
+                    }
+                };
+                stack.last_mut().unwrap().text.push_str(&resolved);
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    if stack.len() != 1 {
+        return Err("unexpected end of XML".to_string());
+    }
+    let document = stack.pop().unwrap();
+    document
+        .children
+        .into_iter()
+        .find(|e| e.tag == "roblox")
+        .ok_or_else(|| "not a Roblox XML file (no <roblox> element)".to_string())
+}
+
+/// Parses a `.rbxlx`/`.rbxmx` document into its top-level instances, placed under
+/// `parent_full_path`. `Ref` properties become `full_path`s; unsupported property
+/// types (binary strings, attributes, ...) are skipped.
+pub fn read_xml(xml: &str, parent_full_path: &str) -> Result<Vec<Instance>, String> {
+    let document = parse_document(xml)?;
+    let mut referents = HashMap::new();
+    let mut items: Vec<Instance> = document
+        .children
+        .iter()
+        .filter(|e| e.tag == "Item")
+        .map(|e| read_item(e, parent_full_path, &mut referents))
+        .collect::<Result<_, _>>()?;
+    for item in &mut items {
+        resolve_referents(item, &referents);
+    }
+    Ok(items)
+}
+
+fn read_item(
+    element: &Element,
+    parent_full_path: &str,
+    referents: &mut HashMap<String, String>,
+) -> Result<Instance, String> {
+    let class_name = element.attribute("class").ok_or("<Item> without a class")?;
+    let referent = element.attribute("referent").unwrap_or("");
+
+    let mut instance = Instance::new(class_name, class_name, &format!("{}/{}", parent_full_path, referent));
+    if let Some(properties) = element.child("Properties") {
+        for property in &properties.children {
+            let Some(name) = property.attribute("name") else { continue };
+            if name == "Name" {
+                instance.name = property.text.clone();
+                continue;
+            }
+            let name = api_name(class_name, name);
+            if let Some(value) = property_value(class_name, name, property) {
+                instance.properties.insert(name.to_string(), value);
+            }
+        }
+    }
+    instance.full_path = format!("{}/{}", parent_full_path, instance.name);
+    if !referent.is_empty() {
+        referents.insert(referent.to_string(), instance.full_path.clone());
+    }
+
+    for child in element.children.iter().filter(|e| e.tag == "Item") {
+        let child = read_item(child, &instance.full_path, referents)?;
+        instance.children.push(child);
+    }
+    Ok(instance)
+}
+
+pub fn resolve_referents(instance: &mut Instance, referents: &HashMap<String, String>) {
+    instance.properties.retain(|_, value| match value {
+        PropertyValue::Ref(r) => match referents.get(&r.instance) {
+            Some(path) => {
+                r.instance = path.clone();
+                true
+            }
+            None => false,
+        },
+        _ => true,
+    });
+    for child in &mut instance.children {
+        resolve_referents(child, referents);
+    }
+}
+
+fn property_value(class_name: &str, name: &str, e: &Element) -> Option<PropertyValue> {
+    let vector3 = || Some(Vec3Wrapper { x: e.number("X")?, y: e.number("Y")?, z: e.number("Z")? });
+    let floats = || -> Vec<f32> { e.text.split_whitespace().filter_map(parse_number).collect() };
+
+    Some(match e.tag.as_str() {
+        "string" | "ProtectedString" => PropertyValue::String(e.text.clone()),
+        "Content" => PropertyValue::String(e.child("url").map(|u| u.text.clone()).unwrap_or_default()),
+        "bool" => PropertyValue::Bool(e.text.trim() == "true"),
+        "float" | "double" | "int" | "int64" => PropertyValue::Number(e.text.trim().parse().ok()?),
+        "token" => token_property(class_name, name, e.text.trim().parse().ok()?)?,
+        "Vector3" => PropertyValue::Vector3(vector3()?),
+        "Vector2" => PropertyValue::Vector2(Vector2Wrapper { x: e.number("X")?, y: e.number("Y")? }),
+        "CoordinateFrame" | "CFrame" => {
+            const TAGS: [&str; 12] = ["X", "Y", "Z", "R00", "R01", "R02", "R10", "R11", "R12", "R20", "R21", "R22"];
+            let mut components = [0.0; 12];
+            for (component, tag) in components.iter_mut().zip(TAGS) {
+                *component = e.number(tag)?;
+            }
+            PropertyValue::CFrame(CFrameWrapper::from_components(components))
+        }
+        "Color3" => match e.child("R") {
+            Some(_) => PropertyValue::Color3(Color3Wrapper::new(e.number("R")?, e.number("G")?, e.number("B")?)),
+            // Older files pack Color3 like Color3uint8.
+            None => packed_color(&e.text)?,
+        },
+        "Color3uint8" => packed_color(&e.text)?,
+        "UDim" => PropertyValue::UDim(UDimWrapper {
+            scale: e.number("S")?,
+            offset: e.number("O")? as i32,
+        }),
+        "UDim2" => PropertyValue::UDim2(UDim2Wrapper {
+            xs: e.number("XS")?,
+            xo: e.number("XO")? as i32,
+            ys: e.number("YS")?,
+            yo: e.number("YO")? as i32,
+        }),
+        "Rect2D" => {
+            let (min, max) = (e.child("min")?, e.child("max")?);
+            PropertyValue::Rect(RectWrapper {
+                min: Vector2Wrapper { x: min.number("X")?, y: min.number("Y")? },
+                max: Vector2Wrapper { x: max.number("X")?, y: max.number("Y")? },
+            })
+        }
+        "NumberRange" => match floats()[..] {
+            [min, max, ..] => PropertyValue::NumberRange(NumberRangeWrapper { min, max }),
+            _ => return None,
+        },
+        "NumberSequence" => PropertyValue::NumberSequence(NumberSequenceWrapper {
+            keypoints: floats()
+                .chunks_exact(3)
+                .map(|k| NumberSequenceKeypoint { time: k[0], value: k[1], envelope: k[2] })
+                .collect(),
+        }),
+        "ColorSequence" => PropertyValue::ColorSequence(ColorSequenceWrapper {
+            keypoints: floats()
+                .chunks_exact(5)
+                .map(|k| ColorSequenceKeypoint { time: k[0], color: Color3Wrapper::new(k[1], k[2], k[3]) })
+                .collect(),
+        }),
+        "BrickColor" => PropertyValue::BrickColor(BrickColorWrapper::from_number(e.text.trim().parse().ok()?)?),
+        "Font" => {
+            let weight: u32 = e.child("Weight").and_then(|w| w.text.trim().parse().ok()).unwrap_or(400);
+            PropertyValue::Font(FontWrapper {
+                family: e.child("Family")?.child("url")?.text.clone(),
+                weight: FONT_WEIGHTS.iter().find(|(_, w)| *w == weight).map_or("Regular", |(n, _)| n).to_string(),
+                style: e.child("Style").map_or("Normal".to_string(), |s| s.text.trim().to_string()),
+            })
+        }
+        "PhysicalProperties" => {
+            if e.child("CustomPhysics")?.text.trim() != "true" {
+                return None;
+            }
+            PropertyValue::PhysicalProperties(PhysicalPropertiesWrapper {
+                density: e.number("Density")?,
+                friction: e.number("Friction")?,
+                elasticity: e.number("Elasticity")?,
+                friction_weight: e.number("FrictionWeight")?,
+                elasticity_weight: e.number("ElasticityWeight")?,
+            })
+        }
+        "Faces" => {
+            let bits: u32 = e.child("faces")?.text.trim().parse().ok()?;
+            let set = |i: u32| bits & (1 << i) != 0;
+            PropertyValue::Faces(FacesWrapper {
+                right: set(0),
+                top: set(1),
+                back: set(2),
+                left: set(3),
+                bottom: set(4),
+                front: set(5),
+            })
+        }
+        "Axes" => {
+            let bits: u32 = e.child("axes")?.text.trim().parse().ok()?;
+            PropertyValue::Axes(AxesWrapper { x: bits & 1 != 0, y: bits & 2 != 0, z: bits & 4 != 0 })
+        }
+        "Ref" => {
+            let referent = e.text.trim();
+            if referent == "null" || referent.is_empty() {
+                return None;
+            }
+            PropertyValue::Ref(InstanceRef { instance: referent.to_string() })
+        }
+        _ => return None,
+    })
+}
+
+fn packed_color(text: &str) -> Option<PropertyValue> {
+    let packed: u32 = text.trim().parse().ok()?;
+    let channel = |shift: u32| ((packed >> shift) & 0xFF) as f32;
+    Some(PropertyValue::Color3(Color3Wrapper::from_rgb(channel(16), channel(8), channel(0))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_value;
+
+    #[test]
+    fn test_xml_round_trip() {
+        let mut model = Instance::new("Door", "Model", "door");
+        model.full_path = "game/Workspace/Door".to_string();
+        let mut frame = Instance::new("Frame", "Part", "door/Frame");
+        frame.full_path = "game/Workspace/Door/Frame".to_string();
+        for (key, value) in [
+            ("Size", "Vector3.new(6, 8, 1)"),
+            ("CFrame", "CFrame.new(0, 4, 0) * CFrame.Angles(0, 0.5, 0)"),
+            ("Color", "Color3.fromRGB(200, 30, 30)"),
+            ("Material", "Enum.Material.Wood"),
+            ("Shape", "Enum.PartType.Ball"),
+            ("Transparency", "0.25"),
+            ("Anchored", "true"),
+            ("BrickColor", "BrickColor.new(\"Bright red\")"),
+            ("CustomPhysicalProperties", "PhysicalProperties.new(0.7, 0.3, 0.5, 1, 1)"),
+        ] {
+            frame.properties.insert(key.to_string(), parse_value(value).unwrap());
+        }
+        let mut script = Instance::new("Open", "Script", "door/Open");
+        script.full_path = "game/Workspace/Door/Open".to_string();
+        script.properties.insert("Source".to_string(), PropertyValue::String("if a < b then\n\tprint(\"&\")\nend".to_string()));
+        model.properties.insert(
+            "PrimaryPart".to_string(),
+            PropertyValue::Ref(InstanceRef { instance: "game/Workspace/Door/Frame".to_string() }),
+        );
+        model.children = vec![frame.clone(), script.clone()];
+
+        let xml = write_xml(&[&model]);
+        assert!(xml.contains(&format!("referent=\"{}\"", referent(&model))));
+        assert!(xml.contains("<Vector3 name=\"size\">"));
+
+        let items = read_xml(&xml, "game/Workspace").unwrap();
+        assert_eq!(items.len(), 1);
+        let door = &items[0];
+        assert_eq!((door.name.as_str(), door.class_name.as_str()), ("Door", "Model"));
+        assert_eq!(door.properties["PrimaryPart"], model.properties["PrimaryPart"]);
+        assert_eq!(door.children[0].properties, frame.properties);
+        assert_eq!(door.children[1].properties, script.properties);
+    }
+}
@@ -0,0 +1,232 @@
+use crate::core::datamodel::PropertyValue;
+use crate::core::parser::parse_value;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+// API-dump-style class metadata shipped with the binary. Defaults are DSL expressions,
+// evaluated once when the database is first used.
+const REFLECTION_JSON: &str = include_str!("reflection.json");
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ApiDump {
+    classes: Vec<ClassEntry>,
+    enums: Vec<EnumDescriptor>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ClassEntry {
+    name: String,
+    superclass: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    members: Vec<Member>,
+    #[serde(default)]
+    default_properties: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "MemberType")]
+enum Member {
+    Property(PropertyDescriptor),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PropertyDescriptor {
+    pub name: String,
+    pub value_type: ValueType,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl PropertyDescriptor {
+    pub fn is_read_only(&self) -> bool {
+        self.tags.iter().any(|t| t == "ReadOnly")
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ValueType {
+    pub category: TypeCategory,
+    /// `float`, `Vector3`, the enum name (`Material`) or the class name (`BasePart`).
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum TypeCategory {
+    Primitive,
+    DataType,
+    Enum,
+    Class,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EnumDescriptor {
+    pub name: String,
+    pub items: Vec<EnumItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct EnumItem {
+    pub name: String,
+    pub value: u32,
+}
+
+#[derive(Debug)]
+pub struct ClassDescriptor {
+    pub name: String,
+    pub superclass: Option<String>,
+    pub tags: Vec<String>,
+    pub properties: Vec<PropertyDescriptor>,
+    pub defaults: HashMap<String, PropertyValue>,
+}
+
+/// Classes, properties, defaults and enums known to LuDock.
+#[derive(Debug)]
+pub struct ReflectionDatabase {
+    classes: HashMap<String, ClassDescriptor>,
+    enums: HashMap<String, EnumDescriptor>,
+}
+
+/// The embedded database, parsed on first use.
+pub fn database() -> &'static ReflectionDatabase {
+    static DATABASE: OnceLock<ReflectionDatabase> = OnceLock::new();
+    DATABASE.get_or_init(|| {
+        ReflectionDatabase::from_json(REFLECTION_JSON).expect("embedded reflection.json is invalid")
+    })
+}
+
+impl ReflectionDatabase {
+    fn from_json(json: &str) -> Result<Self, String> {
+        let dump: ApiDump = serde_json::from_str(json).map_err(|e| e.to_string())?;
+
+        let mut classes = HashMap::new();
+        for entry in dump.classes {
+            let mut defaults = HashMap::new();
+            for (property, expr) in entry.default_properties {
+                let value = parse_value(&expr)
+                    .map_err(|e| format!("default {}.{}: {}", entry.name, property, e))?;
+                defaults.insert(property, value);
+            }
+            let class = ClassDescriptor {
+                superclass: (entry.superclass != "<<<ROOT>>>").then_some(entry.superclass),
+                tags: entry.tags,
+                properties: entry
+                    .members
+                    .into_iter()
+                    .map(|Member::Property(p)| p)
+                    .collect(),
+                defaults,
+                name: entry.name,
+            };
+            classes.insert(class.name.clone(), class);
+        }
+        let enums = dump.enums.into_iter().map(|e| (e.name.clone(), e)).collect();
+
+        Ok(ReflectionDatabase { classes, enums })
+    }
+
+    pub fn class(&self, name: &str) -> Option<&ClassDescriptor> {
+        self.classes.get(name)
+    }
+
+    /// `name` followed by each of its superclasses, up to `Instance`.
+    pub fn ancestry<'a>(&'a self, name: &str) -> impl Iterator<Item = &'a ClassDescriptor> {
+        std::iter::successors(self.class(name), |class| {
+            class.superclass.as_deref().and_then(|s| self.class(s))
+        })
+    }
+
+    /// Roblox `IsA`: true if `class` is `ancestor` or inherits from it. Unknown classes
+    /// are only themselves.
+    pub fn is_a(&self, class: &str, ancestor: &str) -> bool {
+        class == ancestor || self.ancestry(class).any(|c| c.name == ancestor)
+    }
+
+    /// Property metadata, including properties inherited from superclasses.
+    pub fn property(&self, class: &str, property: &str) -> Option<&PropertyDescriptor> {
+        self.ancestry(class)
+            .flat_map(|c| &c.properties)
+            .find(|p| p.name == property)
+    }
+
+    /// Every property of `class`, own properties first.
+    pub fn properties<'a>(&'a self, class: &str) -> impl Iterator<Item = &'a PropertyDescriptor> {
+        self.ancestry(class).flat_map(|c| &c.properties)
+    }
+
+    /// The value a property has when a file doesn't set it. Subclasses can override
+    /// inherited defaults (`SpawnLocation` is larger than a `Part`).
+    pub fn default_value(&self, class: &str, property: &str) -> Option<&PropertyValue> {
+        self.ancestry(class).find_map(|c| c.defaults.get(property))
+    }
+
+    pub fn enum_descriptor(&self, name: &str) -> Option<&EnumDescriptor> {
+        self.enums.get(name)
+    }
+
+    /// Whether `name` is an item of any enum, so it can be written bare (`Material = Wood`).
+    pub fn is_enum_item(&self, name: &str) -> bool {
+        self.enums.values().any(|e| e.items.iter().any(|i| i.name == name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_embedded_database() {
+        // Parses, and every default evaluates (checked in from_json).
+        let db = database();
+
+        assert!(db.is_a("Part", "BasePart"));
+        assert!(db.is_a("SpawnLocation", "Part"));
+        assert!(db.is_a("MeshPart", "BasePart"));
+        assert!(db.is_a("Workspace", "Model"));
+        assert!(!db.is_a("Model", "BasePart"));
+        assert!(db.is_a("Unknown", "Unknown"));
+
+        assert_eq!(
+            db.default_value("SpawnLocation", "Size"),
+            Some(&parse_value("Vector3.new(12, 1, 12)").unwrap())
+        );
+        assert_eq!(
+            db.default_value("WedgePart", "Size"),
+            Some(&parse_value("Vector3.new(4, 1, 2)").unwrap())
+        );
+        assert!(db.property("Part", "Name").is_some());
+        assert!(db.property("BasePart", "Mass").unwrap().is_read_only());
+
+        // Every class reference resolves, and every default has a declared property
+        // whose enum exists.
+        for class in db.classes.values() {
+            if let Some(superclass) = &class.superclass {
+                assert!(db.class(superclass).is_some(), "{} extends {}", class.name, superclass);
+            }
+            for property in &class.properties {
+                let ty = &property.value_type;
+                match ty.category {
+                    TypeCategory::Enum => assert!(db.enum_descriptor(&ty.name).is_some(), "{}", ty.name),
+                    TypeCategory::Class => assert!(db.class(&ty.name).is_some(), "{}", ty.name),
+                    _ => {}
+                }
+            }
+            for (property, value) in &class.defaults {
+                assert!(db.property(&class.name, property).is_some(), "{}.{}", class.name, property);
+                if let PropertyValue::Enum(e) = value {
+                    let mut parts = e.split('.').skip(1);
+                    let (enum_name, item) = (parts.next().unwrap(), parts.next().unwrap());
+                    let descriptor = db.enum_descriptor(enum_name).unwrap();
+                    assert!(descriptor.items.iter().any(|i| i.name == item), "{}", e);
+                }
+            }
+        }
+    }
+}
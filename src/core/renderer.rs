@@ -1,9 +1,11 @@
 use crate::core::datamodel::{Instance, PropertyValue};
+use crate::core::errors::LuDockError;
+use crate::core::geometry::{box_corners, Mesh, MeshFiles, PartShape};
 use anyhow::Result;
 use glam::{Mat4, Vec3, Vec4};
 use image::{ImageBuffer, Rgb, RgbImage};
 use imageproc::drawing::draw_line_segment_mut;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 // Constants for render
 const WIDTH: u32 = 800;
@@ -11,6 +13,8 @@ const HEIGHT: u32 = 600;
 
 pub struct RenderContext {
     buffer: RgbImage,
+    /// Reverse-Z depth: 1 at the near plane, 0 at the far plane and for the background.
+    /// Float precision is densest near 0, which evens it out over distance.
     z_buffer: Vec<f32>,
     width: u32,
     height: u32,
@@ -20,7 +24,7 @@ impl RenderContext {
     pub fn new(width: u32, height: u32) -> Self {
         RenderContext {
             buffer: ImageBuffer::new(width, height),
-            z_buffer: vec![f32::INFINITY; (width * height) as usize],
+            z_buffer: vec![0.0; (width * height) as usize],
             width,
             height,
         }
@@ -31,20 +35,50 @@ impl RenderContext {
             *pixel = color;
         }
         for z in self.z_buffer.iter_mut() {
-            *z = f32::INFINITY;
+            *z = 0.0;
         }
     }
 
+    /// The depth buffer as a greyscale image, white nearest and black for the
+    /// background, stretched over the depths actually drawn.
+    pub fn depth_image(&self) -> RgbImage {
+        let drawn = self.z_buffer.iter().filter(|z| **z > 0.0);
+        let min = drawn.clone().fold(f32::INFINITY, |a, b| a.min(*b));
+        let max = drawn.fold(0.0, |a: f32, b| a.max(*b));
+        ImageBuffer::from_fn(self.width, self.height, |x, y| {
+            let z = self.z_buffer[(y * self.width + x) as usize];
+            if z <= 0.0 {
+                return Rgb([0, 0, 0]);
+            }
+            let t = if max > min { (z - min) / (max - min) } else { 1.0 };
+            let v = (32.0 + t * 223.0).round() as u8;
+            Rgb([v, v, v])
+        })
+    }
+
     pub fn draw_pixel(&mut self, x: u32, y: u32, z: f32, color: Rgb<u8>) {
         if x >= self.width || y >= self.height {
             return;
         }
         let idx = (y * self.width + x) as usize;
-        if z < self.z_buffer[idx] {
+        if z > self.z_buffer[idx] {
             self.z_buffer[idx] = z;
             self.buffer.put_pixel(x, y, color);
         }
     }
+
+    /// Blends `color` over what is already drawn, without writing depth, so surfaces
+    /// behind a transparent one still show.
+    pub fn blend_pixel(&mut self, x: u32, y: u32, z: f32, color: Rgb<u8>, alpha: f32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let idx = (y * self.width + x) as usize;
+        if z > self.z_buffer[idx] {
+            let below = *self.buffer.get_pixel(x, y);
+            self.buffer.put_pixel(x, y, mix(below, color, alpha));
+        }
+    }
 }
 
 // Simple bounding box for auto-framing
@@ -70,108 +104,595 @@ impl Aabb {
     fn size(&self) -> Vec3 {
         self.max - self.min
     }
+    fn is_empty(&self) -> bool {
+        self.min.x > self.max.x
+    }
+}
+
+/// Where the camera looks from (`--camera`).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum CameraView {
+    /// The Workspace's camera (`CurrentCamera`, or a `Camera` child) when there is one
+    /// and no `--target`; `Iso` otherwise.
+    #[default]
+    Auto,
+    /// Straight down, +X to the right.
+    Top,
+    /// Facing the scene's front (-Z) side.
+    Front,
+    /// Facing the scene's right (+X) side.
+    Side,
+    /// The diagonal overview.
+    Iso,
+    /// A `Camera` instance, by full or dotted path.
+    Instance(String),
+}
+
+impl std::str::FromStr for CameraView {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "top" => CameraView::Top,
+            "front" => CameraView::Front,
+            "side" => CameraView::Side,
+            "iso" => CameraView::Iso,
+            path => CameraView::Instance(path.to_string()),
+        })
+    }
+}
+
+/// A cell of the `--views` contact sheet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SheetView {
+    Iso,
+    Top,
+    Front,
+    Right,
+}
+
+impl SheetView {
+    /// The default sheet, in reading order.
+    pub const ALL: [SheetView; 4] = [SheetView::Iso, SheetView::Top, SheetView::Front, SheetView::Right];
+
+    fn name(self) -> &'static str {
+        match self {
+            SheetView::Iso => "iso",
+            SheetView::Top => "top",
+            SheetView::Front => "front",
+            SheetView::Right => "right",
+        }
+    }
+
+    fn camera(self) -> CameraView {
+        match self {
+            SheetView::Iso => CameraView::Iso,
+            SheetView::Top => CameraView::Top,
+            SheetView::Front => CameraView::Front,
+            SheetView::Right => CameraView::Side,
+        }
+    }
+}
+
+impl std::str::FromStr for SheetView {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SheetView::ALL
+            .into_iter()
+            .find(|view| view.name() == s)
+            .ok_or_else(|| format!("unknown view `{}`, expected iso, top, front or right", s))
+    }
 }
 
+#[derive(Default)]
 pub struct RenderOptions {
     pub debug_bounds: bool,
     pub debug_origin: bool,
     pub debug_axes: bool,
+    /// `full_path` of the subtree to frame; everything else is dimmed.
+    pub target: Option<String>,
+    /// Hide everything outside `target` instead of dimming it.
+    pub isolate: bool,
+    pub camera: CameraView,
+    /// Orthographic projection, sized to what the perspective view would show at the
+    /// point looked at.
+    pub orthographic: bool,
+    /// Overrides the camera position.
+    pub eye: Option<Vec3>,
+    /// Overrides the point the camera looks at.
+    pub look_at: Option<Vec3>,
+    /// Shadow-map pass from the sun (skipped when `Lighting.GlobalShadows` is false).
+    pub shadows: bool,
+    /// Draw `Transparency = 1` parts as ghosts and apply `LocalTransparencyModifier`.
+    pub show_invisible: bool,
+    /// Near clipping distance; fitted to the scene when unset.
+    pub near: Option<f32>,
+    /// Far clipping distance; fitted to the scene when unset.
+    pub far: Option<f32>,
+    /// Also save the depth buffer next to the render, as `<name>_depth.png`.
+    pub depth: bool,
+    /// Where `MeshId = "file://..."` paths start from (the current directory when empty).
+    pub project_root: PathBuf,
+}
+
+const DEFAULT_FOV: f32 = 70.0;
+
+/// A resolved viewpoint.
+struct Camera {
+    eye: Vec3,
+    look_at: Vec3,
+    up: Vec3,
+    /// Vertical field of view, in radians.
+    fov_y: f32,
+    near: f32,
+    far: f32,
+}
+
+impl Camera {
+    /// Fits the near and far planes around `bounds` (the whole scene), unless
+    /// `--near`/`--far` are given.
+    fn fit_depth(mut self, bounds: &Aabb, options: &RenderOptions) -> Camera {
+        let forward = (self.look_at - self.eye).normalize_or(Vec3::NEG_Z);
+        let (nearest, farthest) = if bounds.is_empty() {
+            (0.1, 1000.0)
+        } else {
+            box_corners(bounds.size())
+                .map(|c| (bounds.center() + c - self.eye).dot(forward))
+                .iter()
+                .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), d| (lo.min(*d), hi.max(*d)))
+        };
+        // A little slack so surfaces on the box itself aren't clipped
+        self.far = options.far.unwrap_or(farthest * 1.01 + 1.0);
+        self.near = options.near.unwrap_or((nearest * 0.99).max(self.far * 1e-5).max(0.05));
+        self.far = self.far.max(self.near * 2.0);
+        self
+    }
+
+    /// Applies `--eye`/`--look-at`.
+    fn with_overrides(mut self, options: &RenderOptions) -> Camera {
+        if let Some(eye) = options.eye {
+            self.eye = eye;
+        }
+        if let Some(look_at) = options.look_at {
+            self.look_at = look_at;
+        }
+        self
+    }
+
+    fn view_proj(&self, orthographic: bool) -> Mat4 {
+        let forward = (self.look_at - self.eye).normalize_or(Vec3::NEG_Z);
+        // Looking straight up or down: keep -Z at the top of the image
+        let up = if forward.cross(self.up).length_squared() < 1e-6 { Vec3::NEG_Z } else { self.up };
+        let view = Mat4::look_at_rh(self.eye, self.eye + forward, up);
+
+        let aspect = WIDTH as f32 / HEIGHT as f32;
+        let projection = if orthographic {
+            let half_height = self.eye.distance(self.look_at) * (self.fov_y / 2.0).tan();
+            let half_width = half_height * aspect;
+            // Near and far swapped: reverse-Z
+            Mat4::orthographic_rh(-half_width, half_width, -half_height, half_height, self.far, self.near)
+        } else {
+            Mat4::perspective_rh(self.fov_y, aspect, self.far, self.near)
+        };
+        projection * view
+    }
+}
+
+/// Resolves `view` against the scene: a Camera instance keeps its `CFrame` and
+/// `FieldOfView`, presets frame `aabb`.
+fn resolve_camera(root: &Instance, aabb: &Aabb, view: &CameraView, options: &RenderOptions) -> Result<Camera> {
+    let instance = match view {
+        CameraView::Instance(path) => Some(
+            root.resolve_path(path)
+                .filter(|i| i.is_a("Camera"))
+                .ok_or_else(|| LuDockError::RendererError(format!("No Camera at `{}`", path)))?,
+        ),
+        CameraView::Auto if options.target.is_none() => workspace_camera(root),
+        _ => None,
+    };
+
+    let camera = match instance {
+        Some(instance) => {
+            let transform = root.space_of(&instance.full_path).unwrap_or(Mat4::IDENTITY) * instance.part_transform();
+            let fov = match instance.property_or_default("FieldOfView") {
+                Some(PropertyValue::Number(n)) => n as f32,
+                _ => DEFAULT_FOV,
+            };
+            let eye = transform.transform_point3(Vec3::ZERO);
+            Camera {
+                eye,
+                look_at: eye + transform.transform_vector3(Vec3::NEG_Z),
+                up: transform.transform_vector3(Vec3::Y),
+                fov_y: fov.to_radians(),
+                near: 0.1,
+                far: 1000.0,
+            }
+        }
+        None => {
+            let dir = match view {
+                CameraView::Top => Vec3::Y,
+                CameraView::Front => Vec3::NEG_Z,
+                CameraView::Side => Vec3::X,
+                _ => Vec3::new(1.0, 0.8, 1.0).normalize(),
+            };
+            // Distance needed to fit the scene
+            let fov_y = DEFAULT_FOV.to_radians();
+            let distance = (aabb.size().max_element() / 2.0) / (fov_y / 2.0).tan();
+            let center = aabb.center();
+            Camera { eye: center + dir * (distance * 1.5 + 5.0), look_at: center, up: Vec3::Y, fov_y, near: 0.1, far: 1000.0 } // Add margin
+        }
+    };
+
+    Ok(camera)
 }
 
-pub fn render_scene(root: &Instance, output_path: &Path, options: RenderOptions) -> Result<()> {
+/// `Workspace.CurrentCamera`, or else the first `Camera` directly in Workspace.
+fn workspace_camera(root: &Instance) -> Option<&Instance> {
+    let workspace = root.children.iter().find(|c| c.is_a("Workspace"))?;
+    match workspace.properties.get("CurrentCamera") {
+        Some(PropertyValue::Ref(r)) => root.find(&r.instance).filter(|i| i.is_a("Camera")),
+        _ => None,
+    }
+    .or_else(|| workspace.children.iter().find(|c| c.is_a("Camera")))
+}
+
+const SKY: Rgb<u8> = Rgb([200, 230, 255]);
+const NIGHT_SKY: Rgb<u8> = Rgb([20, 24, 40]);
+
+/// Sun and ambient light from the `Lighting` service.
+struct Lighting {
+    /// Unit vector towards the sun.
+    sun: Vec3,
+    /// Light added to a face looking straight at the sun; zero at night.
+    sun_strength: f32,
+    /// `Ambient + OutdoorAmbient`, per channel.
+    ambient: Vec3,
+    sky: Rgb<u8>,
+    /// `Lighting.GlobalShadows`.
+    global_shadows: bool,
+    /// Set when shadows are rendered.
+    shadow_map: Option<ShadowMap>,
+}
+
+impl Lighting {
+    /// Reads `game/Lighting`, falling back to the class defaults (2 PM, equator).
+    fn from_root(root: &Instance) -> Self {
+        let fallback = Instance::new("Lighting", "Lighting", "game/Lighting");
+        let lighting = root.children.iter().find(|c| c.is_a("Lighting")).unwrap_or(&fallback);
+        let number = |name: &str, default: f32| match lighting.property_or_default(name) {
+            Some(PropertyValue::Number(n)) => n as f32,
+            _ => default,
+        };
+        let color = |name: &str| match lighting.property_or_default(name) {
+            Some(PropertyValue::Color3(c)) => Vec3::new(c.r, c.g, c.b),
+            _ => Vec3::ZERO,
+        };
+
+        // An explicit TimeOfDay ("HH:MM:SS") stands in for ClockTime
+        let clock_time = match (lighting.properties.get("ClockTime"), lighting.properties.get("TimeOfDay")) {
+            (None, Some(PropertyValue::String(time))) => time
+                .split(':')
+                .zip([1.0, 60.0, 3600.0])
+                .map(|(part, unit)| part.parse::<f32>().unwrap_or(0.0) / unit)
+                .sum(),
+            _ => number("ClockTime", 14.0),
+        };
+        let sun = sun_direction(clock_time, number("GeographicLatitude", 0.0));
+
+        // Fade the sun out around the horizon and the sky to night below it
+        let daylight = ((sun.y + 0.1) / 0.3).clamp(0.0, 1.0);
+        Lighting {
+            sun,
+            sun_strength: number("Brightness", 2.0) / 4.0 * daylight,
+            ambient: color("Ambient") + color("OutdoorAmbient"),
+            sky: mix(NIGHT_SKY, SKY, daylight),
+            global_shadows: !matches!(lighting.property_or_default("GlobalShadows"), Some(PropertyValue::Bool(false))),
+            shadow_map: None,
+        }
+    }
+
+    /// Builds the shadow map for `parts` when `--shadows` is on, `GlobalShadows` allows
+    /// it and the sun is up.
+    fn cast_shadows(&mut self, parts: &[RenderPart], options: &RenderOptions) {
+        if options.shadows && self.global_shadows && self.sun_strength > 0.0 {
+            self.shadow_map = Some(ShadowMap::build(parts, self.sun));
+        }
+    }
+
+    /// Lambert shading of `color` on a face with unit `normal`.
+    fn shade(&self, color: Rgb<u8>, normal: Vec3) -> Rgb<u8> {
+        let light = self.ambient + Vec3::splat(self.sun_strength * normal.dot(self.sun).max(0.0));
+        Rgb(std::array::from_fn(|i| (color[i] as f32 * light[i].min(1.0)).round() as u8))
+    }
+}
+
+const SHADOW_MAP_SIZE: u32 = 1024;
+
+/// Depth of the scene seen from the sun, like `RenderContext.z_buffer` for a camera
+/// looking along the sunlight.
+struct ShadowMap {
+    view_proj: Mat4,
+    depth: Vec<f32>,
+    /// Depth tolerance against self-shadowing, in NDC.
+    bias: f32,
+}
+
+impl ShadowMap {
+    /// Rasterizes every opaque part, seen through an orthographic box fitted around them.
+    fn build(parts: &[RenderPart], sun: Vec3) -> ShadowMap {
+        let meshes: Vec<(Vec<Vec3>, &Vec<u32>)> = parts
+            .iter()
+            .filter(|part| part.transparency <= 0.0) // Transparent parts let the light through
+            .map(|part| {
+                let mesh = &part.mesh;
+                (mesh.vertices.iter().map(|v| part.transform.transform_point3(*v)).collect(), &mesh.indices)
+            })
+            .collect();
+
+        let mut aabb = Aabb::empty();
+        for point in meshes.iter().flat_map(|(vertices, _)| vertices) {
+            aabb.extend(*point);
+        }
+        let center = if aabb.is_empty() { Vec3::ZERO } else { aabb.center() };
+        let radius = (aabb.size().length() / 2.0).max(1.0);
+
+        let up = if sun.cross(Vec3::Y).length_squared() < 1e-6 { Vec3::NEG_Z } else { Vec3::Y };
+        let view = Mat4::look_at_rh(center + sun * radius * 2.0, center, up);
+        let projection = Mat4::orthographic_rh(-radius, radius, -radius, radius, radius * 3.0, radius); // Reverse-Z
+        let view_proj = projection * view;
+
+        let mut depth = vec![0.0_f32; (SHADOW_MAP_SIZE * SHADOW_MAP_SIZE) as usize];
+        for (vertices, indices) in &meshes {
+            let clip: Vec<Vec4> = vertices.iter().map(|p| view_proj * Vec4::new(p.x, p.y, p.z, 1.0)).collect();
+            for tri in indices.chunks_exact(3) {
+                let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| &clip[i as usize]);
+                rasterize(SHADOW_MAP_SIZE, SHADOW_MAP_SIZE, a, b, c, |x, y, z, _| {
+                    let idx = (y * SHADOW_MAP_SIZE + x) as usize;
+                    depth[idx] = depth[idx].max(z);
+                });
+            }
+        }
+
+        // About two texels of slack, over a depth range of 2 * radius
+        let texel = 2.0 * radius / SHADOW_MAP_SIZE as f32;
+        ShadowMap { view_proj, depth, bias: (2.0 * texel).max(0.05) / (2.0 * radius) }
+    }
+
+    /// Whether something between `point` and the sun blocks the light.
+    fn occludes(&self, point: Vec3) -> bool {
+        let ndc = self.view_proj.project_point3(point);
+        let (x, y, _) = ndc_to_screen(&ndc.extend(1.0), SHADOW_MAP_SIZE, SHADOW_MAP_SIZE);
+        if x < 0.0 || y < 0.0 || x >= SHADOW_MAP_SIZE as f32 || y >= SHADOW_MAP_SIZE as f32 {
+            return false;
+        }
+        self.depth[(y as u32 * SHADOW_MAP_SIZE + x as u32) as usize] > ndc.z + self.bias
+    }
+}
+
+/// Direction towards the sun at `clock_time` hours: it rises in the east (+X) at 6,
+/// peaks at noon and sets in the west at 18. `latitude` (degrees north) tilts its path
+/// towards the south (+Z).
+fn sun_direction(clock_time: f32, latitude: f32) -> Vec3 {
+    let angle = (clock_time - 6.0) / 12.0 * std::f32::consts::PI;
+    let tilt = latitude.to_radians();
+    Vec3::new(angle.cos(), angle.sin() * tilt.cos(), angle.sin() * tilt.sin()).normalize()
+}
+
+/// A BasePart ready to draw, in world space.
+struct RenderPart {
+    transform: Mat4,
+    size: Vec3,
+    color: Rgb<u8>,
+    /// Triangles in part space.
+    mesh: Mesh,
+    /// Outside the `--target` subtree.
+    context: bool,
+    /// 0 (opaque) to 1 (invisible).
+    transparency: f32,
+}
+
+pub fn render_scene(root: &Instance, output_path: &Path, options: &RenderOptions) -> Result<()> {
+    let (parts, aabb, bounds) = collect_scene(root, options);
+    let mut lighting = Lighting::from_root(root);
+    lighting.cast_shadows(&parts, options);
+    let camera = resolve_camera(root, &aabb, &options.camera, options)?
+        .with_overrides(options)
+        .fit_depth(&bounds, options);
+
+    let mut ctx = RenderContext::new(WIDTH, HEIGHT);
+    draw_view(&mut ctx, &parts, &camera.view_proj(options.orthographic), &lighting, options);
+    if options.depth {
+        let stem = output_path.file_stem().and_then(|s| s.to_str()).unwrap_or("render");
+        ctx.depth_image().save(output_path.with_file_name(format!("{}_depth.png", stem)))?;
+    }
+    // UI Overlay (a target outside StarterGui is a 3D subtree: no UI)
+    draw_ui_overlay(&mut ctx, root, options.target.as_deref());
+
+    ctx.buffer.save(output_path)?;
+    Ok(())
+}
+
+/// Renders each view into `render_<view>.png` and tiles them, labelled and two to a
+/// row, into `render_views.png` in `output_dir`; no views means all four (a 2x2 sheet).
+/// `--camera`, `--eye` and `--look-at` only apply to the main render, and the UI
+/// overlay is left out.
+pub fn render_views(root: &Instance, output_dir: &Path, views: &[SheetView], options: &RenderOptions) -> Result<()> {
+    let mut views = if views.is_empty() { SheetView::ALL.to_vec() } else { views.to_vec() };
+    let mut seen = Vec::new();
+    views.retain(|view| !seen.contains(view) && { seen.push(*view); true });
+
+    let (parts, aabb, bounds) = collect_scene(root, options);
+    let mut lighting = Lighting::from_root(root);
+    lighting.cast_shadows(&parts, options);
+    let columns = views.len().min(2) as u32;
+    let rows = views.len().div_ceil(2) as u32;
+    let mut sheet: RgbImage = ImageBuffer::from_pixel(WIDTH * columns, HEIGHT * rows, Rgb([40, 40, 40]));
+
     let mut ctx = RenderContext::new(WIDTH, HEIGHT);
-    ctx.clear(Rgb([200, 230, 255])); // Sky blue background
+    for (i, view) in views.iter().enumerate() {
+        let camera = resolve_camera(root, &aabb, &view.camera(), options)?.fit_depth(&bounds, options);
+        draw_view(&mut ctx, &parts, &camera.view_proj(options.orthographic), &lighting, options);
+        ctx.buffer.save(output_dir.join(format!("render_{}.png", view.name())))?;
+
+        let (x, y) = ((i as u32 % 2) * WIDTH, (i as u32 / 2) * HEIGHT);
+        image::imageops::replace(&mut sheet, &ctx.buffer, x as i64, y as i64);
+        draw_label(&mut sheet, x + 8, y + 8, &view.name().to_uppercase());
+    }
+
+    // 1px separators between the cells
+    for row in 1..rows {
+        for x in 0..sheet.width() {
+            sheet.put_pixel(x, row * HEIGHT, Rgb([40, 40, 40]));
+        }
+    }
+    for column in 1..columns {
+        for y in 0..sheet.height() {
+            sheet.put_pixel(column * WIDTH, y, Rgb([40, 40, 40]));
+        }
+    }
+
+    sheet.save(output_dir.join("render_views.png"))?;
+    Ok(())
+}
 
+/// The parts to draw, the box the camera frames (the target's bounds, or every part)
+/// and the box around every part.
+fn collect_scene(root: &Instance, options: &RenderOptions) -> (Vec<RenderPart>, Aabb, Aabb) {
     // 1. Collect all renderable parts (BaseParts)
     let mut parts = Vec::new();
-    collect_parts(root, &mut parts, Mat4::IDENTITY);
-
-    if parts.is_empty() {
-        // println!("No 3D parts to render."); // Silence this to avoid user confusion if they only have UI
-        // Or make it debug only?
-        // User complained it says "No parts" but they exist. This implies they DO exist but weren't picked up.
-        // I will keep it but add info.
-        // println!("Info: No 3D parts found in DataModel.");
+    let mut meshes = MeshFiles::new(&options.project_root);
+    collect_parts(root, &mut parts, Mat4::IDENTITY, &mut meshes, options);
+    if options.isolate {
+        parts.retain(|p| !p.context);
     }
 
     // 2. Calculate Scene AABB for Auto-Framing
-    let mut aabb = Aabb::empty();
-    for (transform, size, _, _) in &parts {
-        // Compute the 8 corners of the OBB
-        let half_size = *size * 0.5;
-        let corners = [
-            Vec3::new(-half_size.x, -half_size.y, -half_size.z),
-            Vec3::new(half_size.x, -half_size.y, -half_size.z),
-            Vec3::new(-half_size.x, half_size.y, -half_size.z),
-            Vec3::new(half_size.x, half_size.y, -half_size.z),
-            Vec3::new(-half_size.x, -half_size.y, half_size.z),
-            Vec3::new(half_size.x, -half_size.y, half_size.z),
-            Vec3::new(-half_size.x, half_size.y, half_size.z),
-            Vec3::new(half_size.x, half_size.y, half_size.z),
-        ];
-
-        for corner in corners {
-            let world_pos = transform.transform_point3(corner);
-            aabb.extend(world_pos);
-        }
-    }
-
-    // 3. Setup Camera
-    let center = aabb.center();
-    let size = aabb.size();
-    let max_dim = size.max_element();
-    // Distance needed to fit the object
-    // FOV 70 deg
-    let fov_y = 70.0_f32.to_radians();
-    let distance = (max_dim / 2.0) / (fov_y / 2.0).tan();
-    // Position camera diagonally
-    let dir = Vec3::new(1.0, 0.8, 1.0).normalize();
-    let eye = center + dir * (distance * 1.5 + 5.0); // Add margin
-    let target = center;
-    let up = Vec3::Y;
-
-    let view = Mat4::look_at_rh(eye, target, up);
-    let projection = Mat4::perspective_rh(fov_y, WIDTH as f32 / HEIGHT as f32, 0.1, 1000.0);
-    let view_proj = projection * view;
-
-    // 4. Rasterize Parts
-    for (transform, size, color, shape) in &parts {
-        match shape.as_str() {
-            "Ball" => draw_sphere(&mut ctx, &view_proj, *transform, *size, *color),
-            "Cylinder" => draw_cylinder(&mut ctx, &view_proj, *transform, *size, *color),
-            _ => draw_cube(&mut ctx, &view_proj, *transform, *size, *color), // Default to Block/Cube
-        }
-    }
-
-    // 5. Debug Visuals
+    let mut bounds = Aabb::empty();
+    for part in &parts {
+        for vertex in &part.mesh.vertices {
+            bounds.extend(part.transform.transform_point3(*vertex));
+        }
+    }
+    let target_bounds = options.target.as_deref().and_then(|t| root.find(t)).and_then(|t| t.world_bounds.as_ref());
+    let framing = match target_bounds {
+        Some(target) => {
+            let mut aabb = Aabb::empty();
+            aabb.extend(target.min.clone().into());
+            aabb.extend(target.max.clone().into());
+            aabb
+        }
+        None => Aabb { min: bounds.min, max: bounds.max },
+    };
+    // The depth range must also reach the debug axes at the origin
+    if options.debug_axes || options.debug_origin {
+        bounds.extend(Vec3::splat(-5.0));
+        bounds.extend(Vec3::splat(5.0));
+    }
+    (parts, framing, bounds)
+}
+
+/// Clears `ctx` to the sky and draws the lit parts and debug overlays seen through
+/// `view_proj`.
+fn draw_view(ctx: &mut RenderContext, parts: &[RenderPart], view_proj: &Mat4, lighting: &Lighting, options: &RenderOptions) {
+    ctx.clear(lighting.sky);
+
+    // Rasterize Parts: opaque ones first, then transparent ones back-to-front so each
+    // blends over everything behind it. Context around a target fades into the sky.
+    let (opaque, mut transparent): (Vec<&RenderPart>, Vec<&RenderPart>) = parts.iter().partition(|p| p.transparency <= 0.0);
+    // Reverse-Z: the farthest part has the smallest depth
+    let depth = |part: &RenderPart| view_proj.project_point3(part.transform.w_axis.truncate()).z;
+    transparent.sort_by(|a, b| depth(a).total_cmp(&depth(b)));
+    for part in opaque.into_iter().chain(transparent) {
+        let color = if part.context { mix(part.color, lighting.sky, 0.7) } else { part.color };
+        draw_mesh(ctx, view_proj, part.transform, &part.mesh, color, 1.0 - part.transparency, lighting);
+    }
+
+    // Debug Visuals
     if options.debug_axes {
-         draw_axes(&mut ctx, &view_proj, Mat4::IDENTITY, 5.0);
+         draw_axes(ctx, view_proj, Mat4::IDENTITY, 5.0);
     }
     
     if options.debug_bounds {
-        for (transform, size, _, _) in &parts {
-             draw_wireframe_box(&mut ctx, &view_proj, *transform, *size, Rgb([255, 255, 0]));
+        for part in parts.iter().filter(|p| !p.context) {
+             draw_wireframe_box(ctx, view_proj, part.transform, part.size, Rgb([255, 255, 0]));
         }
     }
     
     if options.debug_origin {
          // Draw a small cross at 0,0,0
-         draw_wireframe_box(&mut ctx, &view_proj, Mat4::IDENTITY, Vec3::splat(0.5), Rgb([0, 0, 0]));
+         draw_wireframe_box(ctx, view_proj, Mat4::IDENTITY, Vec3::splat(0.5), Rgb([0, 0, 0]));
     }
+}
 
-    // 6. UI Overlay
-    draw_ui_overlay(&mut ctx, root);
-
-    ctx.buffer.save(output_path)?;
-    Ok(())
+/// 5x7 bitmap glyphs for `A`-`Z`, one byte per row, low 5 bits used.
+const GLYPHS: [[u8; 7]; 26] = [
+    [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // A
+    [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E], // B
+    [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E], // C
+    [0x1E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1E], // D
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F], // E
+    [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10], // F
+    [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F], // G
+    [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11], // H
+    [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E], // I
+    [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C], // J
+    [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11], // K
+    [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F], // L
+    [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11], // M
+    [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11], // N
+    [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // O
+    [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10], // P
+    [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D], // Q
+    [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11], // R
+    [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E], // S
+    [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04], // T
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E], // U
+    [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04], // V
+    [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A], // W
+    [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11], // X
+    [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04], // Y
+    [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F], // Z
+];
+
+/// Writes `text` (uppercase letters; anything else is a blank) in white on a dark
+/// box with its top-left corner at `(x, y)`.
+fn draw_label(image: &mut RgbImage, x: u32, y: u32, text: &str) {
+    const SCALE: u32 = 3;
+    const PAD: u32 = 4;
+    let width = text.len() as u32 * 6 * SCALE - SCALE + PAD * 2;
+    let height = 7 * SCALE + PAD * 2;
+    let rect = imageproc::rect::Rect::at(x as i32, y as i32).of_size(width, height);
+    imageproc::drawing::draw_filled_rect_mut(image, rect, Rgb([40, 40, 40]));
+
+    for (i, c) in text.chars().enumerate() {
+        if !c.is_ascii_uppercase() {
+            continue;
+        }
+        let glyph = &GLYPHS[(c as u8 - b'A') as usize];
+        let left = x + PAD + i as u32 * 6 * SCALE;
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..5 {
+                if bits & (0x10 >> col) != 0 {
+                    let rect = imageproc::rect::Rect::at((left + col * SCALE) as i32, (y + PAD + row as u32 * SCALE) as i32)
+                        .of_size(SCALE, SCALE);
+                    imageproc::drawing::draw_filled_rect_mut(image, rect, Rgb([255, 255, 255]));
+                }
+            }
+        }
+    }
 }
 
-fn draw_ui_overlay(ctx: &mut RenderContext, root: &Instance) {
+fn draw_ui_overlay(ctx: &mut RenderContext, root: &Instance, target: Option<&str>) {
     // Traverse for StarterGui -> ScreenGui -> Frame
     for child in &root.children {
-        if child.class_name == "StarterGui" {
+        if child.is_a("StarterGui") {
             for screen_gui in &child.children {
-                if screen_gui.class_name == "ScreenGui" {
+                // With a target, only the ScreenGui containing it (or inside it) is drawn.
+                let targeted = target.is_none_or(|t| {
+                    screen_gui.is_in(t) || root.find(t).is_some_and(|i| i.is_in(&screen_gui.full_path))
+                });
+                if screen_gui.is_a("ScreenGui") && targeted {
                     draw_gui_recursive(ctx, screen_gui, 0.0, 0.0, ctx.width as f32, ctx.height as f32);
                 }
             }
@@ -185,7 +706,7 @@ fn draw_gui_recursive(ctx: &mut RenderContext, instance: &Instance, parent_x: f3
     let mut my_w = parent_w;
     let mut my_h = parent_h;
 
-    if instance.class_name == "Frame" {
+    if instance.is_a("Frame") {
         // Position
         if let Some(PropertyValue::UDim2(pos)) = instance.properties.get("Position") {
             my_x = parent_x + (pos.xs * parent_w) + (pos.xo as f32);
@@ -200,10 +721,9 @@ fn draw_gui_recursive(ctx: &mut RenderContext, instance: &Instance, parent_x: f3
 
         // AnchorPoint (Optional, assume 0,0 for now if missing)
         // BackgroundColor3
-        let color = if let Some(PropertyValue::Color3(c)) = instance.properties.get("BackgroundColor3") {
-            Rgb([(c.r * 255.0) as u8, (c.g * 255.0) as u8, (c.b * 255.0) as u8])
-        } else {
-            Rgb([255, 255, 255]) // Default white
+        let color = match instance.property_or_default("BackgroundColor3") {
+            Some(PropertyValue::Color3(c)) => Rgb([(c.r * 255.0) as u8, (c.g * 255.0) as u8, (c.b * 255.0) as u8]),
+            _ => Rgb([255, 255, 255]),
         };
 
         // Draw Rect using imageproc
@@ -216,77 +736,98 @@ fn draw_gui_recursive(ctx: &mut RenderContext, instance: &Instance, parent_x: f3
     }
 }
 
+/// How see-through invisible parts are drawn with `--show-invisible`.
+const GHOST_TRANSPARENCY: f32 = 0.8;
+
 fn collect_parts(
     instance: &Instance,
-    parts: &mut Vec<(Mat4, Vec3, Rgb<u8>, String)>,
-    _parent_transform: Mat4,
+    parts: &mut Vec<RenderPart>,
+    parent_transform: Mat4,
+    meshes: &mut MeshFiles,
+    options: &RenderOptions,
 ) {
-    let mut current_transform = Mat4::IDENTITY; // Placeholder if we don't find CFrame
-    let mut found_cframe = false;
-
-    if instance.class_name == "Part" || instance.class_name == "BasePart" {
+    if instance.is_a("BasePart") {
         // Extract Size
-        let size = if let Some(PropertyValue::Vector3(v)) = instance.properties.get("Size") {
-            v.clone().into()
-        } else {
-            Vec3::new(4.0, 1.0, 2.0) // Default part size
+        let size = match instance.property_or_default("Size") {
+            Some(PropertyValue::Vector3(v)) => v.into(),
+            _ => Vec3::ZERO,
         };
 
-        // Extract Color
-        let color = if let Some(PropertyValue::Color3(c)) = instance.properties.get("Color") {
+        // Extract Color: an explicit Color wins over BrickColor, then the class default
+        let color = match (instance.properties.get("Color"), instance.properties.get("BrickColor")) {
+            (Some(PropertyValue::Color3(c)), _) => Some(c.clone()),
+            (_, Some(PropertyValue::BrickColor(b))) => Some(b.color()),
+            _ => match instance.property_or_default("Color") {
+                Some(PropertyValue::Color3(c)) => Some(c),
+                _ => None,
+            },
+        };
+        let color = color.map_or(Rgb([163, 162, 165]), |c| {
             Rgb([
                 (c.r * 255.0) as u8,
                 (c.g * 255.0) as u8,
                 (c.b * 255.0) as u8,
             ])
-        } else {
-            Rgb([163, 162, 165]) // Medium stone grey
-        };
+        });
 
-        // Extract Shape
-        let shape = if let Some(PropertyValue::Enum(s)) = instance.properties.get("Shape") {
-            // "Enum.PartType.Ball" -> "Ball"
-            s.split('.').last().unwrap_or("Block").to_string()
-        } else {
-            "Block".to_string()
+        // Extract the mesh: a file mesh, or the shape (from the class for wedges and
+        // trusses). Files that fail to load were reported by the loader.
+        let mesh = match meshes.part_mesh(instance, size) {
+            Ok(Some(mesh)) => mesh,
+            _ => PartShape::of(instance).mesh(size),
         };
 
-        // Extract CFrame
-        if let Some(PropertyValue::CFrame(cf)) = instance.properties.get("CFrame") {
-            let c = &cf.components;
-            let col0 = Vec4::new(c[3], c[6], c[9], 0.0);
-            let col1 = Vec4::new(c[4], c[7], c[10], 0.0);
-            let col2 = Vec4::new(c[5], c[8], c[11], 0.0);
-            let col3 = Vec4::new(c[0], c[1], c[2], 1.0); // Translation
-
-            current_transform = Mat4::from_cols(col0, col1, col2, col3);
-            found_cframe = true;
-        } else if let Some(PropertyValue::Vector3(pos)) = instance.properties.get("Position") {
-            current_transform = Mat4::from_translation(pos.clone().into());
-            found_cframe = true;
-        } else {
-            // Default to Identity (0,0,0) if missing, consistent with Roblox defaults and Loader logic
-            current_transform = Mat4::IDENTITY;
-            found_cframe = true;
+        // Extract CFrame (or Position/Orientation), placed in the enclosing models' space
+        let transform = parent_transform * instance.part_transform();
+
+        // Transparency, combined with LocalTransparencyModifier like the client does.
+        // Invisible parts are skipped, or drawn as ghosts with --show-invisible.
+        let number = |name: &str| match instance.property_or_default(name) {
+            Some(PropertyValue::Number(n)) => (n as f32).clamp(0.0, 1.0),
+            _ => 0.0,
+        };
+        let mut transparency = number("Transparency");
+        if options.show_invisible {
+            transparency = 1.0 - (1.0 - transparency) * (1.0 - number("LocalTransparencyModifier"));
+        }
+        if transparency >= 1.0 && options.show_invisible {
+            transparency = GHOST_TRANSPARENCY;
         }
 
-        if found_cframe {
-            parts.push((current_transform, size, color, shape));
+        if transparency < 1.0 {
+            let context = options.target.as_deref().is_some_and(|t| !instance.is_in(t));
+            parts.push(RenderPart { transform, size, color, mesh, context, transparency });
         }
     }
 
     // Recurse
+    let children_transform = instance.children_transform(parent_transform);
     for child in &instance.children {
-        collect_parts(child, parts, Mat4::IDENTITY);
+        collect_parts(child, parts, children_transform, meshes, options);
     }
 }
 
-// Helper to draw a mesh
-fn draw_mesh(ctx: &mut RenderContext, view_proj: &Mat4, model: Mat4, vertices: &[Vec3], indices: &[u32], color: Rgb<u8>) {
-    let mvp = *view_proj * model;
-    let clip_coords: Vec<Vec4> = vertices
+/// `a` blended towards `b` by `t` (0 = `a`, 1 = `b`).
+fn mix(a: Rgb<u8>, b: Rgb<u8>, t: f32) -> Rgb<u8> {
+    Rgb(std::array::from_fn(|i| (a[i] as f32 + (b[i] as f32 - a[i] as f32) * t).round() as u8))
+}
+
+// Helper to draw a mesh, each triangle shaded with its own normal. Below an `alpha`
+// of 1 it is blended over the scene.
+fn draw_mesh(
+    ctx: &mut RenderContext,
+    view_proj: &Mat4,
+    model: Mat4,
+    mesh: &Mesh,
+    color: Rgb<u8>,
+    alpha: f32,
+    lighting: &Lighting,
+) {
+    let indices = &mesh.indices;
+    let world: Vec<Vec3> = mesh.vertices.iter().map(|p| model.transform_point3(*p)).collect();
+    let clip_coords: Vec<Vec4> = world
         .iter()
-        .map(|p| mvp * Vec4::new(p.x, p.y, p.z, 1.0))
+        .map(|p| *view_proj * Vec4::new(p.x, p.y, p.z, 1.0))
         .collect();
 
     for i in 0..(indices.len() / 3) {
@@ -294,165 +835,148 @@ fn draw_mesh(ctx: &mut RenderContext, view_proj: &Mat4, model: Mat4, vertices: &
         let idx1 = indices[i * 3 + 1] as usize;
         let idx2 = indices[i * 3 + 2] as usize;
 
+        let normal = (world[idx1] - world[idx0]).cross(world[idx2] - world[idx0]).normalize_or_zero();
+        let lit = lighting.shade(color, normal);
+        let shadowed = lighting.shade(color, Vec3::ZERO); // Ambient only
+        let shadow_map = lighting.shadow_map.as_ref().filter(|_| lit != shadowed);
         rasterize_triangle(
             ctx,
             &clip_coords[idx0],
             &clip_coords[idx1],
             &clip_coords[idx2],
-            color,
+            alpha,
+            |weights| match shadow_map {
+                Some(map) if map.occludes(world[idx0] * weights.x + world[idx1] * weights.y + world[idx2] * weights.z) => shadowed,
+                _ => lit,
+            },
         );
     }
 }
 
-fn draw_cube(ctx: &mut RenderContext, view_proj: &Mat4, model: Mat4, size: Vec3, color: Rgb<u8>) {
-    let half = size * 0.5;
-    let corners = [
-        Vec3::new(-half.x, -half.y, -half.z), // 0
-        Vec3::new(half.x, -half.y, -half.z),  // 1
-        Vec3::new(-half.x, half.y, -half.z),  // 2
-        Vec3::new(half.x, half.y, -half.z),   // 3
-        Vec3::new(-half.x, -half.y, half.z),  // 4
-        Vec3::new(half.x, -half.y, half.z),   // 5
-        Vec3::new(-half.x, half.y, half.z),   // 6
-        Vec3::new(half.x, half.y, half.z),    // 7
-    ];
-    let indices = [
-        4, 5, 7, 4, 7, 6, // Front
-        1, 0, 2, 1, 2, 3, // Back
-        0, 4, 6, 0, 6, 2, // Left
-        5, 1, 3, 5, 3, 7, // Right
-        6, 7, 3, 6, 3, 2, // Top
-        0, 1, 5, 0, 5, 4, // Bottom
-    ];
-    draw_mesh(ctx, view_proj, model, &corners, &indices, color);
+/// Fills a triangle, colouring each covered pixel with `shade` of its
+/// perspective-correct barycentric weights, blended by `alpha`.
+fn rasterize_triangle(ctx: &mut RenderContext, v0: &Vec4, v1: &Vec4, v2: &Vec4, alpha: f32, shade: impl Fn(Vec3) -> Rgb<u8>) {
+    let (width, height) = (ctx.width, ctx.height);
+    rasterize(width, height, v0, v1, v2, |x, y, z, weights| {
+        let idx = (y * width + x) as usize;
+        if z <= ctx.z_buffer[idx] {
+            return;
+        }
+        if alpha >= 1.0 {
+            ctx.draw_pixel(x, y, z, shade(weights));
+        } else {
+            ctx.blend_pixel(x, y, z, shade(weights), alpha);
+        }
+    });
 }
 
-fn draw_sphere(ctx: &mut RenderContext, view_proj: &Mat4, model: Mat4, size: Vec3, color: Rgb<u8>) {
-    // Generate sphere mesh (icosphere or UV sphere). Using simple UV sphere.
-    let lat_segments = 12;
-    let lon_segments = 12;
-    let radius = size.min_element() * 0.5; // Approximation: use min dimension as radius
-
-    let mut vertices = Vec::new();
-    let mut indices = Vec::new();
-
-    for lat in 0..=lat_segments {
-        let theta = lat as f32 * std::f32::consts::PI / lat_segments as f32;
-        let sin_theta = theta.sin();
-        let cos_theta = theta.cos();
-
-        for lon in 0..=lon_segments {
-            let phi = lon as f32 * 2.0 * std::f32::consts::PI / lon_segments as f32;
-            let sin_phi = phi.sin();
-            let cos_phi = phi.cos();
-
-            let x = cos_phi * sin_theta;
-            let y = cos_theta;
-            let z = sin_phi * sin_theta;
-
-            vertices.push(Vec3::new(x * radius, y * radius, z * radius));
-        }
+/// Calls `plot(x, y, depth, weights)` for every pixel centre of a `width` x `height`
+/// target inside the triangle, with the vertices' perspective-correct barycentric weights.
+/// The triangle is clipped first, so parts crossing the near plane still draw.
+fn rasterize(width: u32, height: u32, v0: &Vec4, v1: &Vec4, v2: &Vec4, mut plot: impl FnMut(u32, u32, f32, Vec3)) {
+    let polygon = clip_triangle([*v0, *v1, *v2]);
+    // Fan out the convex polygon; clipped vertices carry their weights in the original
+    for i in 1..polygon.len().saturating_sub(1) {
+        let [a, b, c] = [polygon[0], polygon[i], polygon[i + 1]];
+        rasterize_clipped(width, height, &a.position, &b.position, &c.position, |x, y, z, w| {
+            plot(x, y, z, a.weights * w.x + b.weights * w.y + c.weights * w.z)
+        });
     }
+}
 
-    for lat in 0..lat_segments {
-        for lon in 0..lon_segments {
-            let first = (lat * (lon_segments + 1)) + lon;
-            let second = first + lon_segments + 1;
+/// How far past the viewport edges, in half-viewports, triangles may reach before they
+/// are clipped. Inside the band, clamping the screen-space bounding box is cheaper and
+/// keeps coordinates small enough for the edge functions.
+const GUARD_BAND: f32 = 8.0;
+
+/// Signed distances to the clipping planes, positive inside: the near (depth 1) and far
+/// (depth 0) planes of the reverse-Z projections, and the four guard-band planes.
+const CLIP_PLANES: [fn(Vec4) -> f32; 6] = [
+    |v| v.w - v.z,
+    |v| v.z,
+    |v| GUARD_BAND * v.w - v.x,
+    |v| GUARD_BAND * v.w + v.x,
+    |v| GUARD_BAND * v.w - v.y,
+    |v| GUARD_BAND * v.w + v.y,
+];
+
+/// A clip-space vertex with its barycentric weights in the unclipped triangle.
+#[derive(Clone, Copy)]
+struct ClipVertex {
+    position: Vec4,
+    weights: Vec3,
+}
 
-            indices.push(first);
-            indices.push(second);
-            indices.push(first + 1);
+/// Sutherland–Hodgman clipping of a clip-space triangle against `CLIP_PLANES`. Returns
+/// the convex polygon left (empty when nothing is), in the triangle's winding.
+fn clip_triangle(triangle: [Vec4; 3]) -> Vec<ClipVertex> {
+    let mut polygon: Vec<ClipVertex> = triangle
+        .into_iter()
+        .zip([Vec3::X, Vec3::Y, Vec3::Z])
+        .map(|(position, weights)| ClipVertex { position, weights })
+        .collect();
 
-            indices.push(second);
-            indices.push(second + 1);
-            indices.push(first + 1);
+    for plane in CLIP_PLANES {
+        if polygon.iter().all(|v| plane(v.position) >= 0.0) {
+            continue;
+        }
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for (i, &a) in polygon.iter().enumerate() {
+            let b = polygon[(i + 1) % polygon.len()];
+            let (da, db) = (plane(a.position), plane(b.position));
+            if da >= 0.0 {
+                clipped.push(a);
+            }
+            if (da >= 0.0) != (db >= 0.0) {
+                let t = da / (da - db);
+                clipped.push(ClipVertex {
+                    position: a.position.lerp(b.position, t),
+                    weights: a.weights.lerp(b.weights, t),
+                });
+            }
         }
+        polygon = clipped;
     }
-    
-    draw_mesh(ctx, view_proj, model, &vertices, &indices, color);
+    polygon
 }
 
-fn draw_cylinder(ctx: &mut RenderContext, view_proj: &Mat4, model: Mat4, size: Vec3, color: Rgb<u8>) {
-    let segments = 16;
-    let radius = size.x.min(size.z) * 0.5; // X/Z determines radius usually
-    let half_height = size.y * 0.5;
-
-    let mut vertices = Vec::new();
-    let mut indices = Vec::new();
-
-    // Side vertices
-    for i in 0..=segments {
-        let theta = i as f32 * 2.0 * std::f32::consts::PI / segments as f32;
-        let x = theta.cos() * radius;
-        let z = theta.sin() * radius;
-        
-        vertices.push(Vec3::new(x, -half_height, z)); // Bottom ring
-        vertices.push(Vec3::new(x, half_height, z));  // Top ring
+/// Clips a clip-space segment against `CLIP_PLANES`; `None` when nothing is left.
+fn clip_line(mut a: Vec4, mut b: Vec4) -> Option<(Vec4, Vec4)> {
+    for plane in CLIP_PLANES {
+        let (da, db) = (plane(a), plane(b));
+        match (da >= 0.0, db >= 0.0) {
+            (true, true) => {}
+            (false, false) => return None,
+            (true, false) => b = a.lerp(b, da / (da - db)),
+            (false, true) => a = a.lerp(b, da / (da - db)),
+        }
     }
-    
-    // Center points for caps
-    let bottom_center_idx = vertices.len() as u32;
-    vertices.push(Vec3::new(0.0, -half_height, 0.0));
-    let top_center_idx = vertices.len() as u32;
-    vertices.push(Vec3::new(0.0, half_height, 0.0));
-
-    for i in 0..segments {
-        let base = i * 2;
-        let next_base = (i + 1) * 2;
-        
-        // Side quads (2 tris)
-        indices.push(base);
-        indices.push(base + 1);
-        indices.push(next_base);
-        
-        indices.push(next_base);
-        indices.push(base + 1);
-        indices.push(next_base + 1);
-        
-        // Bottom Cap
-        indices.push(bottom_center_idx);
-        indices.push(next_base);
-        indices.push(base);
-        
-        // Top Cap
-        indices.push(top_center_idx);
-        indices.push(base + 1);
-        indices.push(next_base + 1);
-    }
-
-    // Reorient to align with Roblox Cylinder (which lies on X axis by default? No, usually Y up).
-    // Actually Roblox Cylinder is usually along X axis. 
-    // Wait, Roblox BasePart Cylinder: "The cylinder is aligned with the X-axis."
-    // My generation above is along Y axis.
-    // I need to rotate it 90 deg around Z.
-    let rotation = Mat4::from_rotation_z(90.0_f32.to_radians());
-    
-    // We can apply this rotation to the vertices or the model matrix. 
-    // Applying to vertices here for simplicity.
-    let vertices: Vec<Vec3> = vertices.iter().map(|v| rotation.transform_point3(*v)).collect();
-
-    draw_mesh(ctx, view_proj, model, &vertices, &indices, color);
+    Some((a, b))
 }
 
-fn rasterize_triangle(ctx: &mut RenderContext, v0: &Vec4, v1: &Vec4, v2: &Vec4, color: Rgb<u8>) {
+/// `rasterize` for a triangle already inside the near plane and the guard band.
+fn rasterize_clipped(width: u32, height: u32, v0: &Vec4, v1: &Vec4, v2: &Vec4, mut plot: impl FnMut(u32, u32, f32, Vec3)) {
     // Homogeneous divide
-    if v0.w <= 0.0 || v1.w <= 0.0 || v2.w <= 0.0 {
-        return;
-    } // Very basic near plane clipping (discard)
-
-    let p0 = ndc_to_screen(v0, ctx.width, ctx.height);
-    let p1 = ndc_to_screen(v1, ctx.width, ctx.height);
-    let p2 = ndc_to_screen(v2, ctx.width, ctx.height);
+    let p0 = ndc_to_screen(v0, width, height);
+    let p1 = ndc_to_screen(v1, width, height);
+    let p2 = ndc_to_screen(v2, width, height);
 
     // Bounding box of triangle
     let min_x = p0.0.min(p1.0).min(p2.0).max(0.0) as u32;
-    let max_x = p0.0.max(p1.0).max(p2.0).min((ctx.width - 1) as f32) as u32;
+    let max_x = p0.0.max(p1.0).max(p2.0).min((width - 1) as f32) as u32;
     let min_y = p0.1.min(p1.1).min(p2.1).max(0.0) as u32;
-    let max_y = p0.1.max(p1.1).max(p2.1).min((ctx.height - 1) as f32) as u32;
+    let max_y = p0.1.max(p1.1).max(p2.1).min((height - 1) as f32) as u32;
 
-    // Edge functions
+    // Edge functions, evaluated from the lower endpoint so both triangles sharing an
+    // edge get exactly opposite values
     let edge = |a: (f32, f32), b: (f32, f32), c: (f32, f32)| {
-        (c.0 - a.0) * (b.1 - a.1) - (c.1 - a.1) * (b.0 - a.0)
+        let raw = |a: (f32, f32), b: (f32, f32)| (c.0 - a.0) * (b.1 - a.1) - (c.1 - a.1) * (b.0 - a.0);
+        if a > b { -raw(b, a) } else { raw(a, b) }
+    };
+    // Top-left rule: a pixel centre right on an edge belongs to the triangle whose top or
+    // left edge it is, so pixels on shared edges are drawn once (and blended once)
+    let covers = |w: f32, a: (f32, f32), b: (f32, f32)| {
+        w > 0.0 || (w == 0.0 && (b.1 > a.1 || (b.1 == a.1 && b.0 < a.0)))
     };
 
     let p0_2d = (p0.0, p0.1);
@@ -473,19 +997,16 @@ fn rasterize_triangle(ctx: &mut RenderContext, v0: &Vec4, v1: &Vec4, v2: &Vec4,
             let w1 = edge(p2_2d, p0_2d, p);
             let w2 = edge(p0_2d, p1_2d, p);
 
-            // Check if inside
-            if w0 >= 0.0 && w1 >= 0.0 && w2 >= 0.0 {
+            // Check if inside (back faces wind the other way and are skipped)
+            if covers(w0, p1_2d, p2_2d) && covers(w1, p2_2d, p0_2d) && covers(w2, p0_2d, p1_2d) {
                 let w0 = w0 / area;
                 let w1 = w1 / area;
                 let w2 = w2 / area;
 
-                // Interpolate Z
-                let _z = 1.0 / (w0 / v0.w + w1 / v1.w + w2 / v2.w);
-                // Actually, standard perspective correct interpolation for Z buffer is just Z in NDC?
-                // Z in screen space:
+                // Z in screen space is linear; attributes need dividing by w
                 let z_depth = w0 * p0.2 + w1 * p1.2 + w2 * p2.2;
-
-                ctx.draw_pixel(x, y, z_depth, color);
+                let weights = Vec3::new(w0 / v0.w, w1 / v1.w, w2 / v2.w);
+                plot(x, y, z_depth, weights / weights.element_sum());
             }
         }
     }
@@ -495,7 +1016,7 @@ fn ndc_to_screen(v: &Vec4, width: u32, height: u32) -> (f32, f32, f32) {
     let ndc = *v / v.w;
     let x = (ndc.x + 1.0) * 0.5 * width as f32;
     let y = (1.0 - ndc.y) * 0.5 * height as f32; // Flip Y
-    let z = ndc.z; // -1 to 1 usually, or 0 to 1 depending on API (WebGPU/DX vs GL). Glam uses GL conventions (-1 to 1)
+    let z = ndc.z; // Reverse-Z: 1 at the near plane, 0 at the far plane
     (x, y, z)
 }
 
@@ -503,18 +1024,17 @@ fn draw_line(ctx: &mut RenderContext, p0: (f32, f32), p1: (f32, f32), color: Rgb
     draw_line_segment_mut(&mut ctx.buffer, p0, p1, color);
 }
 
+/// Draws the visible part of a clip-space segment.
+fn draw_clip_line(ctx: &mut RenderContext, v0: Vec4, v1: Vec4, color: Rgb<u8>) {
+    if let Some((v0, v1)) = clip_line(v0, v1) {
+        let s0 = ndc_to_screen(&v0, ctx.width, ctx.height);
+        let s1 = ndc_to_screen(&v1, ctx.width, ctx.height);
+        draw_line(ctx, (s0.0, s0.1), (s1.0, s1.1), color);
+    }
+}
+
 fn draw_wireframe_box(ctx: &mut RenderContext, view_proj: &Mat4, model: Mat4, size: Vec3, color: Rgb<u8>) {
-    let half = size * 0.5;
-    let corners = [
-        Vec3::new(-half.x, -half.y, -half.z), // 0
-        Vec3::new(half.x, -half.y, -half.z),  // 1
-        Vec3::new(-half.x, half.y, -half.z),  // 2
-        Vec3::new(half.x, half.y, -half.z),   // 3
-        Vec3::new(-half.x, -half.y, half.z),  // 4
-        Vec3::new(half.x, -half.y, half.z),   // 5
-        Vec3::new(-half.x, half.y, half.z),   // 6
-        Vec3::new(half.x, half.y, half.z),    // 7
-    ];
+    let corners = box_corners(size);
     
     let mvp = *view_proj * model;
     let clip: Vec<Vec4> = corners.iter().map(|p| mvp * Vec4::new(p.x, p.y, p.z, 1.0)).collect();
@@ -527,14 +1047,7 @@ fn draw_wireframe_box(ctx: &mut RenderContext, view_proj: &Mat4, model: Mat4, si
     ];
 
     for (i, j) in edges {
-        let v0 = clip[i];
-        let v1 = clip[j];
-        // Clip check logic (naive)
-        if v0.w > 0.0 && v1.w > 0.0 {
-            let s0 = ndc_to_screen(&v0, ctx.width, ctx.height);
-            let s1 = ndc_to_screen(&v1, ctx.width, ctx.height);
-            draw_line(ctx, (s0.0, s0.1), (s1.0, s1.1), color);
-        }
+        draw_clip_line(ctx, clip[i], clip[j], color);
     }
 }
 
@@ -548,23 +1061,253 @@ fn draw_axes(ctx: &mut RenderContext, view_proj: &Mat4, model: Mat4, length: f32
     let mvp = *view_proj * model;
     let clip: Vec<Vec4> = pts.iter().map(|p| mvp * Vec4::new(p.x, p.y, p.z, 1.0)).collect();
 
-    let o_s = ndc_to_screen(&clip[0], ctx.width, ctx.height);
-    
-    if clip[0].w > 0.0 {
-         // X: Red
-        if clip[1].w > 0.0 {
-            let x_s = ndc_to_screen(&clip[1], ctx.width, ctx.height);
-            draw_line(ctx, (o_s.0, o_s.1), (x_s.0, x_s.1), Rgb([255, 0, 0]));
-        }
-        // Y: Green
-        if clip[2].w > 0.0 {
-            let y_s = ndc_to_screen(&clip[2], ctx.width, ctx.height);
-            draw_line(ctx, (o_s.0, o_s.1), (y_s.0, y_s.1), Rgb([0, 255, 0]));
-        }
-        // Z: Blue
-        if clip[3].w > 0.0 {
-            let z_s = ndc_to_screen(&clip[3], ctx.width, ctx.height);
-            draw_line(ctx, (o_s.0, o_s.1), (z_s.0, z_s.1), Rgb([0, 0, 255]));
+    // X: Red, Y: Green, Z: Blue
+    draw_clip_line(ctx, clip[0], clip[1], Rgb([255, 0, 0]));
+    draw_clip_line(ctx, clip[0], clip[2], Rgb([0, 255, 0]));
+    draw_clip_line(ctx, clip[0], clip[3], Rgb([0, 0, 255]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::datamodel::CFrameWrapper;
+    use crate::core::geometry::{cube_mesh, cylinder_mesh};
+    use crate::core::test_support::TempProject;
+
+    #[test]
+    fn test_camera_presets_and_instances() {
+        let mut root = Instance::new("game", "DataModel", "game");
+        root.full_path = "game".to_string();
+        let mut workspace = Instance::new("Workspace", "Workspace", "game/Workspace");
+        workspace.full_path = "game/Workspace".to_string();
+        let mut camera = Instance::new("Camera", "Camera", "game/Workspace/Camera");
+        camera.full_path = "game/Workspace/Camera".to_string();
+        camera.properties.insert("CFrame".to_string(), PropertyValue::CFrame(CFrameWrapper::new(0.0, 10.0, 20.0)));
+        camera.properties.insert("FieldOfView".to_string(), PropertyValue::Number(40.0));
+        workspace.children.push(camera);
+        root.children.push(workspace);
+
+        let mut aabb = Aabb::empty();
+        aabb.extend(Vec3::splat(-2.0));
+        aabb.extend(Vec3::splat(2.0));
+        let options = RenderOptions::default();
+
+        // Top: above the scene, +X to the right of the image
+        let top = resolve_camera(&root, &aabb, &CameraView::Top, &options).unwrap();
+        assert!(top.eye.y > 2.0 && top.eye.x == 0.0 && top.eye.z == 0.0);
+        let view_proj = top.view_proj(true);
+        let right = view_proj.project_point3(Vec3::X);
+        let left = view_proj.project_point3(Vec3::NEG_X);
+        assert!(right.x > left.x);
+
+        // Auto picks the Workspace camera, Iso ignores it, and overrides win
+        let auto = resolve_camera(&root, &aabb, &CameraView::Auto, &options).unwrap();
+        assert_eq!(auto.eye, Vec3::new(0.0, 10.0, 20.0));
+        assert_eq!(auto.look_at, Vec3::new(0.0, 10.0, 19.0));
+        assert!((auto.fov_y - 40.0_f32.to_radians()).abs() < 1e-6);
+        let iso = resolve_camera(&root, &aabb, &CameraView::Iso, &options).unwrap();
+        assert_eq!(iso.look_at, Vec3::ZERO);
+        let options = RenderOptions { look_at: Some(Vec3::ZERO), ..Default::default() };
+        let named = resolve_camera(&root, &aabb, &"Workspace.Camera".parse().unwrap(), &options)
+            .unwrap()
+            .with_overrides(&options);
+        assert_eq!(named.look_at, Vec3::ZERO);
+        assert!(resolve_camera(&root, &aabb, &"Workspace.Nope".parse().unwrap(), &options).is_err());
+    }
+
+    #[test]
+    fn test_lighting_follows_clock_time() {
+        let mut root = Instance::new("game", "DataModel", "game");
+        let mut lighting = Instance::new("Lighting", "Lighting", "game/Lighting");
+        lighting.properties.insert("ClockTime".to_string(), PropertyValue::Number(12.0));
+        root.children.push(lighting);
+
+        // Noon at the equator: the sun is overhead, so tops are brighter than sides
+        let noon = Lighting::from_root(&root);
+        assert!(noon.sun.abs_diff_eq(Vec3::Y, 1e-5));
+        let grey = Rgb([128, 128, 128]);
+        assert!(noon.shade(grey, Vec3::Y)[0] > noon.shade(grey, Vec3::X)[0]);
+        assert_eq!(noon.sky, SKY);
+
+        // Midnight: no sun, ambient only, night sky
+        root.children[0].properties.insert("ClockTime".to_string(), PropertyValue::Number(0.0));
+        let night = Lighting::from_root(&root);
+        assert_eq!(night.sun_strength, 0.0);
+        assert_eq!(night.shade(grey, Vec3::Y), night.shade(grey, Vec3::X));
+        assert_eq!(night.sky, NIGHT_SKY);
+
+        // Sphere and cylinder triangles face outwards
+        let sun_below = Lighting {
+            sun: Vec3::NEG_Y,
+            sun_strength: 1.0,
+            ambient: Vec3::ZERO,
+            sky: SKY,
+            global_shadows: true,
+            shadow_map: None,
+        };
+        let mut ctx = RenderContext::new(WIDTH, HEIGHT);
+        ctx.clear(SKY);
+        let view_proj = Camera { eye: Vec3::new(0.0, -10.0, 0.0), look_at: Vec3::ZERO, up: Vec3::Y, fov_y: 1.0, near: 0.1, far: 100.0 }.view_proj(false);
+        draw_mesh(&mut ctx, &view_proj, Mat4::from_rotation_z(90.0_f32.to_radians()), &cylinder_mesh(Vec3::splat(4.0)), grey, 1.0, &sun_below);
+        assert_eq!(*ctx.buffer.get_pixel(WIDTH / 2, HEIGHT / 2), grey);
+    }
+
+    #[test]
+    fn test_floor_crossing_the_near_plane_still_draws() {
+        // Standing on a huge baseplate: most of its corners are behind the camera
+        let camera = Camera {
+            eye: Vec3::new(0.0, 2.0, 0.0),
+            look_at: Vec3::new(0.0, 2.0, -10.0),
+            up: Vec3::Y,
+            fov_y: 1.2,
+            near: 0.1,
+            far: 2000.0,
+        };
+        let view_proj = camera.view_proj(false);
+        let lighting = Lighting::from_root(&Instance::new("game", "DataModel", "game"));
+        let mut ctx = RenderContext::new(WIDTH, HEIGHT);
+        ctx.clear(SKY);
+        let floor = Mat4::from_translation(Vec3::new(0.0, -0.5, 0.0));
+        draw_mesh(&mut ctx, &view_proj, floor, &cube_mesh(Vec3::new(2048.0, 1.0, 2048.0)), Rgb([90, 160, 90]), 1.0, &lighting);
+        draw_wireframe_box(&mut ctx, &view_proj, floor, Vec3::new(4.0, 1.0, 4.0), Rgb([255, 255, 0]));
+
+        // Below the horizon is floor, right down to the bottom edge
+        assert_ne!(*ctx.buffer.get_pixel(WIDTH / 2, HEIGHT - 1), SKY);
+        assert_ne!(*ctx.buffer.get_pixel(10, HEIGHT * 3 / 4), SKY);
+        assert_eq!(*ctx.buffer.get_pixel(WIDTH / 2, 10), SKY);
+
+        // A segment from behind the camera to in front of it keeps its visible part
+        let (a, b) = clip_line(Vec4::new(0.0, 0.0, 1.0, -1.0), Vec4::new(0.0, 0.0, 0.5, 2.0)).unwrap();
+        assert!((a.w - a.z).abs() < 1e-6 && b == Vec4::new(0.0, 0.0, 0.5, 2.0));
+    }
+
+    #[test]
+    fn test_invisible_parts_are_skipped_or_ghosted() {
+        let mut root = Instance::new("game", "DataModel", "game");
+        for (name, transparency, modifier) in [("Glass", 0.5, 0.0), ("Trigger", 1.0, 0.0), ("Hidden", 0.0, 1.0)] {
+            let mut part = Instance::new(name, "Part", name);
+            part.properties.insert("Transparency".to_string(), PropertyValue::Number(transparency));
+            part.properties.insert("LocalTransparencyModifier".to_string(), PropertyValue::Number(modifier));
+            root.children.push(part);
+        }
+
+        let transparencies = |options: &RenderOptions| {
+            let mut parts = Vec::new();
+            collect_parts(&root, &mut parts, Mat4::IDENTITY, &mut MeshFiles::new(Path::new("")), options);
+            parts.iter().map(|p| p.transparency).collect::<Vec<_>>()
+        };
+        assert_eq!(transparencies(&RenderOptions::default()), vec![0.5, 0.0]);
+        let debug = RenderOptions { show_invisible: true, ..Default::default() };
+        assert_eq!(transparencies(&debug), vec![0.5, GHOST_TRANSPARENCY, GHOST_TRANSPARENCY]);
+    }
+
+    #[test]
+    fn test_depth_range_and_transparent_order() {
+        let pane = |position: Vec3, color: Rgb<u8>, transparency: f32| RenderPart {
+            transform: Mat4::from_translation(position),
+            size: Vec3::new(10.0, 10.0, 0.2),
+            color,
+            mesh: cube_mesh(Vec3::new(10.0, 10.0, 0.2)),
+            context: false,
+            transparency,
+        };
+        let (red, blue) = (Rgb([255, 0, 0]), Rgb([0, 0, 255]));
+        let parts = [pane(Vec3::ZERO, red, 0.3), pane(Vec3::new(0.0, 0.0, -20.0), blue, 0.3)];
+        let mut bounds = Aabb::empty();
+        for part in &parts {
+            for v in &part.mesh.vertices {
+                bounds.extend(part.transform.transform_point3(*v));
+            }
         }
+
+        // Near and far hug the panes (29.9 and 50.1 studs away), unless overridden
+        let camera = Camera { eye: Vec3::new(0.0, 0.0, 30.0), look_at: Vec3::ZERO, up: Vec3::Y, fov_y: 1.2, near: 0.0, far: 0.0 };
+        let fitted = Camera { ..camera }.fit_depth(&bounds, &RenderOptions::default());
+        assert!(fitted.near > 29.0 && fitted.near < 29.9, "near {}", fitted.near);
+        assert!(fitted.far > 50.1 && fitted.far < 60.0, "far {}", fitted.far);
+        let options = RenderOptions { near: Some(1.0), far: Some(500.0), ..Default::default() };
+        let overridden = Camera { ..fitted }.fit_depth(&bounds, &options);
+        assert_eq!((overridden.near, overridden.far), (1.0, 500.0));
+
+        // The far blue pane is drawn first, so the near red one is blended over it
+        let view_proj = fitted.view_proj(false);
+        let lighting = Lighting::from_root(&Instance::new("game", "DataModel", "game"));
+        let mut ctx = RenderContext::new(WIDTH, HEIGHT);
+        draw_view(&mut ctx, &parts, &view_proj, &lighting, &RenderOptions::default());
+        let center = *ctx.buffer.get_pixel(WIDTH / 2, HEIGHT / 2);
+        assert!(center[0] > center[2], "{:?}", center);
+
+        // Opaque panes side by side: the depth image is brighter for the nearer one and
+        // black where nothing was drawn
+        let parts = [pane(Vec3::new(-6.0, 0.0, 0.0), red, 0.0), pane(Vec3::new(6.0, 0.0, -20.0), blue, 0.0)];
+        draw_view(&mut ctx, &parts, &view_proj, &lighting, &RenderOptions::default());
+        let depth = ctx.depth_image();
+        let pixel = |p: Vec3| {
+            let (x, y, _) = ndc_to_screen(&(view_proj * p.extend(1.0)), WIDTH, HEIGHT);
+            depth.get_pixel(x as u32, y as u32)[0]
+        };
+        assert!(pixel(Vec3::new(-6.0, 0.0, 0.1)) > pixel(Vec3::new(6.0, 0.0, -19.9)));
+        assert!(pixel(Vec3::new(6.0, 0.0, -19.9)) > 0);
+        assert_eq!(depth.get_pixel(0, 0)[0], 0);
+    }
+
+    #[test]
+    fn test_shared_edges_are_drawn_once() {
+        // Two quads split along a diagonal, given in pixels: a square whose diagonal runs
+        // through pixel centres, and a skewed one
+        let clip = |x: f32, y: f32| Vec4::new(x / 8.0 - 1.0, 1.0 - y / 8.0, 0.5, 1.0);
+        for [a, b, c, d] in [[(0.0, 0.0), (16.0, 0.0), (16.0, 16.0), (0.0, 16.0)], [(1.3, 0.7), (15.1, 2.9), (13.7, 15.2), (0.4, 12.6)]] {
+            let mut coverage = [[0; 16]; 16];
+            for [p0, p1, p2] in [[a, c, b], [a, d, c]] {
+                rasterize_clipped(16, 16, &clip(p0.0, p0.1), &clip(p1.0, p1.1), &clip(p2.0, p2.1), |x, y, _, _| {
+                    coverage[y as usize][x as usize] += 1;
+                });
+            }
+            assert!(coverage.iter().flatten().all(|n| *n <= 1), "{:?}", coverage);
+            assert_eq!(coverage[8][8], 1);
+        }
+    }
+
+    #[test]
+    fn test_views_sheet_layout() {
+        let dir = TempProject::new("views", &[]);
+        let root = Instance::new("game", "DataModel", "game");
+        let sheet_size = |views: &[SheetView]| {
+            render_views(&root, &dir, views, &RenderOptions::default()).unwrap();
+            image::open(dir.join("render_views.png")).unwrap().to_rgb8().dimensions()
+        };
+
+        // All four by default, two to a row; three leave the last cell empty
+        assert_eq!(sheet_size(&[]), (WIDTH * 2, HEIGHT * 2));
+        assert_eq!(sheet_size(&[SheetView::Right]), (WIDTH, HEIGHT));
+        let views: Vec<SheetView> = "top,front,iso".split(',').map(|v| v.parse().unwrap()).collect();
+        assert_eq!(sheet_size(&views), (WIDTH * 2, HEIGHT * 2));
+        let sheet = image::open(dir.join("render_views.png")).unwrap().to_rgb8();
+        let front = image::open(dir.join("render_front.png")).unwrap().to_rgb8();
+        assert_eq!(sheet.get_pixel(WIDTH + WIDTH / 2, HEIGHT / 2), front.get_pixel(WIDTH / 2, HEIGHT / 2));
+        assert_eq!(*sheet.get_pixel(WIDTH + WIDTH / 2, HEIGHT + HEIGHT / 2), Rgb([40, 40, 40]));
+        assert_ne!(*sheet.get_pixel(WIDTH / 2, HEIGHT + HEIGHT / 2), Rgb([40, 40, 40]));
+
+        assert!("left".parse::<SheetView>().is_err());
+    }
+
+    #[test]
+    fn test_shadow_map_darkens_floor_under_part() {
+        let part = |y: f32, size: Vec3| RenderPart {
+            transform: Mat4::from_translation(Vec3::new(0.0, y, 0.0)),
+            size,
+            color: Rgb([128, 128, 128]),
+            mesh: cube_mesh(size),
+            context: false,
+            transparency: 0.0,
+        };
+        let parts = [part(0.0, Vec3::new(40.0, 1.0, 40.0)), part(6.0, Vec3::splat(4.0))];
+        let map = ShadowMap::build(&parts, Vec3::Y);
+
+        // The floor's top face is shadowed under the block, lit beside it; the block's
+        // own top is lit
+        assert!(map.occludes(Vec3::new(0.0, 0.5, 0.0)));
+        assert!(!map.occludes(Vec3::new(10.0, 0.5, 10.0)));
+        assert!(!map.occludes(Vec3::new(0.0, 8.0, 0.0)));
     }
 }
@@ -0,0 +1,311 @@
+use crate::core::datamodel::PropertyValue;
+use crate::core::parser::parse_value;
+use crate::core::printer::{format_number, print_string};
+use crate::core::reflection::{TypeCategory, database};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+// Rojo's on-disk formats: `*.project.json` trees, `*.meta.json` and `*.model.json`.
+// Property values are translated to DSL expressions and evaluated like a DSL file, so
+// both formats end up with identical `PropertyValue`s.
+
+/// A node of a project file `tree`.
+#[derive(Debug, Default)]
+pub struct ProjectNode {
+    pub class_name: Option<String>,
+    /// `$path`, relative to the project file. Optional paths may be missing on disk.
+    pub path: Option<ProjectPath>,
+    pub properties: Vec<(String, Value)>,
+    pub children: Vec<(String, ProjectNode)>,
+}
+
+#[derive(Debug)]
+pub struct ProjectPath {
+    pub path: String,
+    pub optional: bool,
+}
+
+/// A parsed `default.project.json`: the project name and its instance tree.
+#[derive(Debug)]
+pub struct Project {
+    pub name: String,
+    pub tree: ProjectNode,
+}
+
+pub fn parse_project(json: &str) -> Result<Project, String> {
+    let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let name = value.get("name").and_then(Value::as_str).unwrap_or("").to_string();
+    let tree = value
+        .get("tree")
+        .and_then(Value::as_object)
+        .ok_or("project file has no `tree` object")?;
+    Ok(Project {
+        name,
+        tree: parse_node(tree)?,
+    })
+}
+
+fn parse_node(object: &Map<String, Value>) -> Result<ProjectNode, String> {
+    let mut node = ProjectNode::default();
+    for (key, value) in object {
+        match key.as_str() {
+            "$className" => {
+                let class_name = value.as_str().ok_or("`$className` must be a string")?;
+                node.class_name = Some(class_name.to_string());
+            }
+            "$path" => {
+                node.path = Some(match value {
+                    Value::String(path) => ProjectPath { path: path.clone(), optional: false },
+                    Value::Object(o) => match o.get("optional").and_then(Value::as_str) {
+                        Some(path) => ProjectPath { path: path.to_string(), optional: true },
+                        None => return Err("`$path` object needs an `optional` string".to_string()),
+                    },
+                    _ => return Err("`$path` must be a string".to_string()),
+                });
+            }
+            "$properties" => {
+                let properties = value.as_object().ok_or("`$properties` must be an object")?;
+                node.properties = properties.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+            }
+            // `$ignoreUnknownInstances`, `$attributes`, ...: only relevant when syncing.
+            key if key.starts_with('$') => {}
+            name => {
+                let child = value
+                    .as_object()
+                    .ok_or_else(|| format!("`{}` must be an object", name))?;
+                let child = parse_node(child).map_err(|e| format!("{}: {}", name, e))?;
+                node.children.push((name.to_string(), child));
+            }
+        }
+    }
+    Ok(node)
+}
+
+/// `init.meta.json` / `Name.meta.json`: extra properties for the instance a directory or
+/// script file becomes.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetaFile {
+    pub class_name: Option<String>,
+    #[serde(default)]
+    pub properties: Map<String, Value>,
+}
+
+/// `Name.model.json`: an instance tree written out as JSON. Rojo accepts both the old
+/// PascalCase and the current camelCase keys.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JsonModel {
+    #[serde(alias = "Name")]
+    pub name: Option<String>,
+    #[serde(alias = "ClassName")]
+    pub class_name: String,
+    #[serde(default, alias = "Properties")]
+    pub properties: Map<String, Value>,
+    #[serde(default, alias = "Children")]
+    pub children: Vec<JsonModel>,
+}
+
+/// Converts a Rojo property value, either explicit (`{"Vector3": [1, 2, 3]}`) or
+/// implicit (`[1, 2, 3]`, typed by the class database).
+pub fn property_value(class_name: &str, property: &str, value: &Value) -> Result<PropertyValue, String> {
+    let declared = database().property(class_name, property).map(|p| &p.value_type);
+
+    let expr = match value {
+        Value::Object(map) if map.len() == 1 => {
+            let (ty, inner) = map.iter().next().unwrap();
+            let enum_name = declared
+                .filter(|t| t.category == TypeCategory::Enum)
+                .map(|t| t.name.as_str());
+            match explicit_type(ty, enum_name) {
+                Some((category, name)) => value_expr(category, name, inner)?,
+                None => return Err(format!("unsupported value type `{}`", ty)),
+            }
+        }
+        _ => match declared {
+            Some(ty) => value_expr(ty.category, &ty.name, value)?,
+            None => match value {
+                Value::Bool(b) => b.to_string(),
+                Value::Number(_) => value_expr(TypeCategory::Primitive, "double", value)?,
+                Value::String(s) => print_string(s),
+                _ => {
+                    return Err(format!(
+                        "cannot infer the type of `{}`; write it explicitly, e.g. {{\"Vector3\": [0, 0, 0]}}",
+                        property
+                    ));
+                }
+            },
+        },
+    };
+    parse_value(&expr)
+}
+
+/// Maps Rojo's explicit type names onto the database's type categories.
+fn explicit_type<'a>(ty: &'a str, enum_name: Option<&'a str>) -> Option<(TypeCategory, &'a str)> {
+    Some(match ty {
+        "Bool" => (TypeCategory::Primitive, "bool"),
+        "String" => (TypeCategory::Primitive, "string"),
+        "Float32" | "Float64" | "Int32" | "Int64" => (TypeCategory::Primitive, "double"),
+        "Enum" => (TypeCategory::Enum, enum_name?),
+        "Content" | "Vector2" | "Vector3" | "Color3" | "Color3uint8" | "CFrame" | "UDim" | "UDim2"
+        | "NumberRange" | "Rect" | "BrickColor" => (TypeCategory::DataType, ty),
+        _ => return None,
+    })
+}
+
+fn value_expr(category: TypeCategory, ty: &str, value: &Value) -> Result<String, String> {
+    let mismatch = || format!("expected {}, got {}", ty, value);
+    let numbers = |n: usize| -> Result<String, String> {
+        let items = value.as_array().filter(|a| a.len() == n).ok_or_else(mismatch)?;
+        let items: Option<Vec<String>> = items.iter().map(|v| v.as_f64().map(format_number)).collect();
+        Ok(items.ok_or_else(mismatch)?.join(", "))
+    };
+    // `[[a, b], [c, d]]`, as used by UDim2 and Rect.
+    let pairs = || -> Result<String, String> {
+        let items = value.as_array().filter(|a| a.len() == 2).ok_or_else(mismatch)?;
+        let mut flat = Vec::new();
+        for pair in items {
+            let pair = pair.as_array().filter(|a| a.len() == 2).ok_or_else(mismatch)?;
+            for v in pair {
+                flat.push(format_number(v.as_f64().ok_or_else(mismatch)?));
+            }
+        }
+        Ok(flat.join(", "))
+    };
+
+    Ok(match (category, ty) {
+        (TypeCategory::Primitive, "bool") => value.as_bool().ok_or_else(mismatch)?.to_string(),
+        (TypeCategory::Primitive, "string") | (TypeCategory::DataType, "Content") => {
+            print_string(value.as_str().ok_or_else(mismatch)?)
+        }
+        (TypeCategory::Primitive, _) => format_number(value.as_f64().ok_or_else(mismatch)?),
+        (TypeCategory::Enum, enum_name) => {
+            let descriptor = database().enum_descriptor(enum_name).ok_or_else(mismatch)?;
+            let item = match value {
+                Value::String(name) => descriptor.items.iter().find(|i| i.name == *name),
+                Value::Number(n) => descriptor
+                    .items
+                    .iter()
+                    .find(|i| n.as_u64() == Some(u64::from(i.value))),
+                _ => None,
+            };
+            let item = item.ok_or_else(|| format!("{} is not an item of Enum.{}", value, enum_name))?;
+            format!("Enum.{}.{}", enum_name, item.name)
+        }
+        (TypeCategory::DataType, "Vector2") => format!("Vector2.new({})", numbers(2)?),
+        (TypeCategory::DataType, "Vector3") => format!("Vector3.new({})", numbers(3)?),
+        (TypeCategory::DataType, "Color3") => format!("Color3.new({})", numbers(3)?),
+        (TypeCategory::DataType, "Color3uint8") => format!("Color3.fromRGB({})", numbers(3)?),
+        (TypeCategory::DataType, "UDim") => format!("UDim.new({})", numbers(2)?),
+        (TypeCategory::DataType, "NumberRange") => format!("NumberRange.new({})", numbers(2)?),
+        (TypeCategory::DataType, "UDim2") => format!("UDim2.new({})", pairs()?),
+        (TypeCategory::DataType, "Rect") => format!("Rect.new({})", pairs()?),
+        (TypeCategory::DataType, "BrickColor") => match value {
+            Value::String(name) => format!("BrickColor.new({})", print_string(name)),
+            _ => format!("BrickColor.new({})", format_number(value.as_f64().ok_or_else(mismatch)?)),
+        },
+        (TypeCategory::DataType, "CFrame") => match value {
+            // `{"position": [x, y, z], "orientation": [[r00, r01, r02], ...]}`
+            Value::Object(o) => {
+                let position = o.get("position").ok_or_else(mismatch)?;
+                let position = value_expr(category, "Vector3", position)?;
+                let rows = o.get("orientation").and_then(Value::as_array).ok_or_else(mismatch)?;
+                let mut components = Vec::new();
+                for row in rows {
+                    let row = row.as_array().filter(|r| r.len() == 3).ok_or_else(mismatch)?;
+                    for v in row {
+                        components.push(format_number(v.as_f64().ok_or_else(mismatch)?));
+                    }
+                }
+                if components.len() != 9 {
+                    return Err(mismatch());
+                }
+                let position = position.trim_start_matches("Vector3.new(").trim_end_matches(')');
+                format!("CFrame.new({}, {})", position, components.join(", "))
+            }
+            _ => format!("CFrame.new({})", numbers(12)?),
+        },
+        _ => return Err(format!("{} values are not supported in Rojo files", ty)),
+    })
+}
+
+/// The Rojo JSON form of a value, the inverse of `property_value`: primitives and enum
+/// items are implicit, data types explicit. `None` for types Rojo files can't hold here.
+pub fn json_value(value: &PropertyValue) -> Option<Value> {
+    // Via the shortest `f32` text, so 0.3 is written as 0.3 rather than 0.30000001192092896.
+    let widen = |n: f32| n.to_string().parse::<f64>().unwrap_or(f64::from(n));
+    let numbers = |ns: &[f32]| Value::from(ns.iter().map(|n| widen(*n)).collect::<Vec<_>>());
+    let explicit = |ty: &str, inner: Value| Value::Object(Map::from_iter([(ty.to_string(), inner)]));
+    Some(match value {
+        PropertyValue::String(s) => Value::from(s.as_str()),
+        PropertyValue::Bool(b) => Value::from(*b),
+        PropertyValue::Number(n) => Value::from(*n),
+        PropertyValue::Enum(e) => Value::from(e.rsplit('.').next()?),
+        PropertyValue::Vector3(v) => explicit("Vector3", numbers(&[v.x, v.y, v.z])),
+        PropertyValue::Vector2(v) => explicit("Vector2", numbers(&[v.x, v.y])),
+        PropertyValue::Color3(c) => explicit("Color3", numbers(&[c.r, c.g, c.b])),
+        PropertyValue::UDim(u) => explicit("UDim", numbers(&[u.scale, u.offset as f32])),
+        PropertyValue::UDim2(u) => explicit(
+            "UDim2",
+            Value::from(vec![numbers(&[u.xs, u.xo as f32]), numbers(&[u.ys, u.yo as f32])]),
+        ),
+        PropertyValue::NumberRange(r) => explicit("NumberRange", numbers(&[r.min, r.max])),
+        PropertyValue::Rect(r) => explicit(
+            "Rect",
+            Value::from(vec![numbers(&[r.min.x, r.min.y]), numbers(&[r.max.x, r.max.y])]),
+        ),
+        PropertyValue::BrickColor(b) => explicit("BrickColor", Value::from(b.number)),
+        PropertyValue::CFrame(cf) => {
+            let c = &cf.components;
+            let orientation = vec![numbers(&c[3..6]), numbers(&c[6..9]), numbers(&c[9..12])];
+            let mut inner = Map::new();
+            inner.insert("position".to_string(), numbers(&c[0..3]));
+            inner.insert("orientation".to_string(), Value::from(orientation));
+            explicit("CFrame", Value::Object(inner))
+        }
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rojo_property_values() {
+        let value = |class: &str, property: &str, json: &str| {
+            property_value(class, property, &serde_json::from_str(json).unwrap())
+        };
+        let dsl = |expr: &str| Ok(parse_value(expr).unwrap());
+
+        assert_eq!(value("Part", "Size", "[4, 1, 2]"), dsl("Vector3.new(4, 1, 2)"));
+        assert_eq!(value("Part", "Color", "[1, 0, 0]"), dsl("Color3.new(1, 0, 0)"));
+        assert_eq!(value("Part", "Color", r#"{"Color3uint8": [255, 0, 0]}"#), dsl("Color3.new(1, 0, 0)"));
+        assert_eq!(value("Part", "Material", r#""Wood""#), dsl("Enum.Material.Wood"));
+        assert_eq!(value("Part", "Material", r#"{"Enum": 512}"#), dsl("Enum.Material.Wood"));
+        assert_eq!(value("Part", "Anchored", "true"), dsl("true"));
+        assert_eq!(value("Frame", "Size", "[[0.5, 0], [1, -10]]"), dsl("UDim2.new(0.5, 0, 1, -10)"));
+        assert_eq!(
+            value(
+                "Part",
+                "CFrame",
+                r#"{"CFrame": {"position": [0, 5, 0], "orientation": [[1, 0, 0], [0, 1, 0], [0, 0, 1]]}}"#
+            ),
+            dsl("CFrame.new(0, 5, 0)")
+        );
+        assert_eq!(value("Folder", "Custom", r#""text""#), dsl("\"text\""));
+        assert!(value("Folder", "Custom", "[1, 2, 3]").is_err());
+        assert!(value("Part", "Size", "true").is_err());
+
+        let project = parse_project(
+            r#"{"name": "Game", "tree": {"$className": "DataModel",
+                "ReplicatedStorage": {"$path": "src/shared"},
+                "Workspace": {"$properties": {"Gravity": 100}, "Baseplate": {"$className": "Part"}}}}"#,
+        )
+        .unwrap();
+        assert_eq!(project.tree.class_name.as_deref(), Some("DataModel"));
+        let (name, storage) = &project.tree.children[0];
+        assert_eq!((name.as_str(), storage.path.as_ref().unwrap().path.as_str()), ("ReplicatedStorage", "src/shared"));
+        assert_eq!(project.tree.children[1].1.children[0].0, "Baseplate");
+    }
+}
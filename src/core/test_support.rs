@@ -0,0 +1,40 @@
+//! Fixtures shared by the unit tests.
+
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// A project directory under the system temp dir. It is removed when dropped, so a
+/// failing assertion doesn't leave it behind for the next run.
+pub struct TempProject {
+    root: PathBuf,
+}
+
+impl TempProject {
+    /// Writes `files` (relative path, content) into a fresh directory named after the test.
+    pub fn new(name: &str, files: &[(&str, &str)]) -> Self {
+        let root = std::env::temp_dir().join(format!("ludock-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        for (path, content) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, content).unwrap();
+        }
+        TempProject { root }
+    }
+}
+
+impl Deref for TempProject {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl Drop for TempProject {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
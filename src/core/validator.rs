@@ -0,0 +1,247 @@
+use crate::core::datamodel::PropertyValue;
+use crate::core::printer::print_value;
+use crate::core::reflection::{EnumDescriptor, PropertyDescriptor, TypeCategory, database};
+
+/// A problem with one DSL property, reported by the loader as a diagnostic.
+#[derive(Debug, PartialEq)]
+pub struct PropertyIssue {
+    /// `UnknownProperty`, `TypeMismatch`, `InvalidEnumItem` or `ReadOnlyProperty`.
+    pub code: &'static str,
+    pub message: String,
+    pub hint: Option<String>,
+}
+
+/// Checks `key = value` against the class database. Classes the database doesn't know
+/// are not checked, since their properties can't be.
+pub fn validate_property(class_name: &str, key: &str, value: &PropertyValue) -> Option<PropertyIssue> {
+    let db = database();
+    // `ClassName` selects the class rather than setting a property.
+    if key == "ClassName" || db.class(class_name).is_none() {
+        return None;
+    }
+
+    if let Some(issue) = unknown_property(class_name, key) {
+        return Some(issue);
+    }
+    let property = db.property(class_name, key)?;
+    if property.is_read_only() {
+        return Some(PropertyIssue {
+            code: "ReadOnlyProperty",
+            message: format!("`{}` is read-only on {}", key, class_name),
+            hint: Some("It is computed by the engine; remove the assignment.".to_string()),
+        });
+    }
+
+    if property.value_type.category == TypeCategory::Enum
+        && let Some(descriptor) = db.enum_descriptor(&property.value_type.name)
+    {
+        return validate_enum(descriptor, property, value);
+    }
+    if accepts(property, value) {
+        return None;
+    }
+    let expected = expected_type(property);
+    Some(PropertyIssue {
+        code: "TypeMismatch",
+        message: format!("`{}` expects {}, got {}", key, expected, type_name(value)),
+        hint: db
+            .default_value(class_name, key)
+            .map(|default| format!("e.g. {} = {}", key, print_value(default))),
+    })
+}
+
+/// `UnknownProperty` if `class_name` is known but has no property `key`. Checked on its
+/// own where a value can't be interpreted without the property's type.
+pub fn unknown_property(class_name: &str, key: &str) -> Option<PropertyIssue> {
+    let db = database();
+    if key == "ClassName" || db.class(class_name).is_none() || db.property(class_name, key).is_some() {
+        return None;
+    }
+    let suggestion = closest(key, db.properties(class_name).map(|p| p.name.as_str()));
+    Some(PropertyIssue {
+        code: "UnknownProperty",
+        message: format!("{} has no property `{}`", class_name, key),
+        hint: suggestion.map(|s| format!("Did you mean `{}`?", s)),
+    })
+}
+
+fn accepts(property: &PropertyDescriptor, value: &PropertyValue) -> bool {
+    let ty = &property.value_type;
+    match ty.category {
+        TypeCategory::Primitive => matches!(
+            (ty.name.as_str(), value),
+            ("bool", PropertyValue::Bool(_))
+                | ("float" | "double" | "int" | "int64", PropertyValue::Number(_))
+                | ("string", PropertyValue::String(_))
+        ),
+        // Content (asset ids, `file://` paths) is written as a string.
+        TypeCategory::DataType if ty.name == "Content" => matches!(value, PropertyValue::String(_)),
+        TypeCategory::DataType => type_name(value) == ty.name,
+        TypeCategory::Class => matches!(value, PropertyValue::Ref(_)),
+        TypeCategory::Enum => true,
+    }
+}
+
+/// Enum properties take `Enum.Material.Wood`, or the bare item name or value.
+fn validate_enum(
+    descriptor: &EnumDescriptor,
+    property: &PropertyDescriptor,
+    value: &PropertyValue,
+) -> Option<PropertyIssue> {
+    let item = match value {
+        PropertyValue::Enum(e) => {
+            let mut parts = e.split('.').skip(1);
+            let (enum_name, item) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+            if enum_name != descriptor.name {
+                return Some(PropertyIssue {
+                    code: "TypeMismatch",
+                    message: format!("`{}` expects Enum.{}, got {}", property.name, descriptor.name, e),
+                    hint: closest(item, descriptor.items.iter().map(|i| i.name.as_str()))
+                        .map(|s| format!("Did you mean `Enum.{}.{}`?", descriptor.name, s)),
+                });
+            }
+            item.to_string()
+        }
+        PropertyValue::String(s) => s.clone(),
+        PropertyValue::Number(n) => {
+            if descriptor.items.iter().any(|i| f64::from(i.value) == *n) {
+                return None;
+            }
+            return Some(PropertyIssue {
+                code: "InvalidEnumItem",
+                message: format!("{} is not a value of Enum.{}", n, descriptor.name),
+                hint: None,
+            });
+        }
+        other => {
+            return Some(PropertyIssue {
+                code: "TypeMismatch",
+                message: format!("`{}` expects Enum.{}, got {}", property.name, descriptor.name, type_name(other)),
+                hint: descriptor
+                    .items
+                    .first()
+                    .map(|i| format!("e.g. {} = Enum.{}.{}", property.name, descriptor.name, i.name)),
+            });
+        }
+    };
+
+    if descriptor.items.iter().any(|i| i.name == item) {
+        return None;
+    }
+    Some(PropertyIssue {
+        code: "InvalidEnumItem",
+        message: format!("`{}` is not an item of Enum.{}", item, descriptor.name),
+        hint: closest(&item, descriptor.items.iter().map(|i| i.name.as_str()))
+            .map(|s| format!("Did you mean `Enum.{}.{}`?", descriptor.name, s)),
+    })
+}
+
+fn expected_type(property: &PropertyDescriptor) -> String {
+    let ty = &property.value_type;
+    match (ty.category, ty.name.as_str()) {
+        (TypeCategory::Primitive, "float" | "double" | "int" | "int64") => "number".to_string(),
+        (TypeCategory::DataType, "Content") => "string".to_string(),
+        (TypeCategory::Class, class) => format!("a {} reference", class),
+        (_, name) => name.to_string(),
+    }
+}
+
+/// The DSL spelling of a value's type, matching the database's `DataType` names.
+fn type_name(value: &PropertyValue) -> &'static str {
+    match value {
+        PropertyValue::String(_) => "string",
+        PropertyValue::Bool(_) => "bool",
+        PropertyValue::Number(_) => "number",
+        PropertyValue::Vector3(_) => "Vector3",
+        PropertyValue::Vector2(_) => "Vector2",
+        PropertyValue::CFrame(_) => "CFrame",
+        PropertyValue::Color3(_) => "Color3",
+        PropertyValue::UDim2(_) => "UDim2",
+        PropertyValue::UDim(_) => "UDim",
+        PropertyValue::Rect(_) => "Rect",
+        PropertyValue::NumberRange(_) => "NumberRange",
+        PropertyValue::NumberSequence(_) => "NumberSequence",
+        PropertyValue::ColorSequence(_) => "ColorSequence",
+        PropertyValue::BrickColor(_) => "BrickColor",
+        PropertyValue::Font(_) => "Font",
+        PropertyValue::PhysicalProperties(_) => "PhysicalProperties",
+        PropertyValue::Faces(_) => "Faces",
+        PropertyValue::Axes(_) => "Axes",
+        PropertyValue::Ref(_) => "Ref",
+        PropertyValue::Enum(_) => "EnumItem",
+    }
+}
+
+/// The candidate closest to `name` (case-insensitive edit distance, counting swapped
+/// letters as one edit), if it is close enough to be a plausible typo.
+fn closest<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    let threshold = (name.chars().count() / 3).max(2);
+    candidates
+        .map(|c| (edit_distance(&name, &c.to_lowercase()), c))
+        .filter(|(d, _)| *d <= threshold)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+/// Optimal string alignment distance.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in d[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::parser::parse_value;
+
+    fn check(class: &str, key: &str, value: &str) -> Option<(&'static str, Option<String>)> {
+        validate_property(class, key, &parse_value(value).unwrap()).map(|i| (i.code, i.hint))
+    }
+
+    #[test]
+    fn test_validate_property() {
+        assert_eq!(check("Part", "Size", "Vector3.new(4, 1, 2)"), None);
+        assert_eq!(check("Part", "Material", "Enum.Material.Wood"), None);
+        assert_eq!(check("Part", "Material", "Wood"), None);
+        assert_eq!(check("Part", "Transparency", "0.5"), None);
+        assert_eq!(check("Script", "Source", "\"print(1)\""), None);
+        assert_eq!(check("NotAClass", "Anything", "1"), None);
+
+        assert_eq!(
+            check("Part", "Szie", "Vector3.new(4, 1, 2)"),
+            Some(("UnknownProperty", Some("Did you mean `Size`?".to_string())))
+        );
+        assert_eq!(check("Part", "Frobnicate", "1"), Some(("UnknownProperty", None)));
+        assert_eq!(
+            check("Part", "Color", "true").map(|(code, _)| code),
+            Some("TypeMismatch")
+        );
+        assert_eq!(
+            check("Part", "Material", "Enum.Material.Wod"),
+            Some(("InvalidEnumItem", Some("Did you mean `Enum.Material.Wood`?".to_string())))
+        );
+        assert_eq!(
+            check("Part", "Material", "Enum.PartType.Ball").map(|(code, _)| code),
+            Some("TypeMismatch")
+        );
+        assert_eq!(check("Part", "Mass", "5").map(|(code, _)| code), Some("ReadOnlyProperty"));
+    }
+}
@@ -1,8 +1,13 @@
 mod commands;
 mod core;
 
-use anyhow::Result;
+use core::errors::LuDockError;
 
-fn main() -> Result<()> {
-    commands::main()
+fn main() {
+    if let Err(e) = commands::main() {
+        eprintln!("Error: {:?}", e);
+        // Typed errors carry their own exit code; anything else is a generic failure.
+        let code = e.downcast_ref::<LuDockError>().map_or(1, LuDockError::exit_code);
+        std::process::exit(code);
+    }
 }